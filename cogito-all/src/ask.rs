@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! The simplest way to ask an AI service a question.
+
+use cogito::client::{AiClient, AiError, AiRequest, AiResponse, AiResult};
+use cogito::{AiModel, ModelClass};
+use cogito_claude::client::ClaudeClient;
+use cogito_openai::client::OpenAIClient;
+use hypertyper::prelude::*;
+use std::fmt;
+
+/// An AI service that can be [asked](ask) a question.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Provider {
+    /// Anthropic Claude.
+    Claude,
+
+    /// OpenAI.
+    OpenAI,
+}
+
+impl Provider {
+    /// The environment variable that holds the provider's API key.
+    pub fn env_var(&self) -> &'static str {
+        match self {
+            Provider::Claude => "CLAUDE_API_KEY",
+            Provider::OpenAI => "OPENAI_API_KEY",
+        }
+    }
+
+    fn auth(&self) -> AiResult<Auth> {
+        let var = self.env_var();
+        Auth::from_env(var).map_err(|_| AiError::Unauthorized(format!("${var} is not set")))
+    }
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Provider::Claude => f.write_str("Claude"),
+            Provider::OpenAI => f.write_str("OpenAI"),
+        }
+    }
+}
+
+/// Sends `prompt` to the `provider`'s model of the given `class` and
+/// returns its response.
+///
+/// This is meant for one-off scripts: the API key is read from the
+/// provider's [environment variable](Provider::env_var), and a new client
+/// is created for each question, so build a client yourself to ask more
+/// than a few questions or to control how the request is generated.
+///
+/// Requires the `ask` feature, which enables both providers.
+///
+/// # Errors
+///
+/// If the provider's environment variable is not set, an
+/// [`AiError::Unauthorized`] error is returned without contacting the AI
+/// service. Otherwise, any error from the AI service is returned.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cogito_all::{ModelClass, Provider};
+///
+/// # async fn run() -> cogito_all::client::AiResult<()> {
+/// let answer = cogito_all::ask(Provider::OpenAI, ModelClass::Cheapest, "hi").await?;
+/// println!("{answer}");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn ask(provider: Provider, class: ModelClass, prompt: &str) -> AiResult<String> {
+    let auth = provider.auth()?;
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    match provider {
+        Provider::Claude => ask_with(ClaudeClient::new(auth, factory), class, prompt).await,
+        Provider::OpenAI => ask_with(OpenAIClient::new(auth, factory), class, prompt).await,
+    }
+}
+
+async fn ask_with<C>(client: C, class: ModelClass, prompt: &str) -> AiResult<String>
+where
+    C: AiClient + Sync,
+    C::AiRequest: Sync,
+{
+    let model = <C::AiRequest as AiRequest>::Model::for_class(class);
+    let request = C::AiRequest::default().model(model).input(prompt);
+    let response = client.send(&request).await?;
+    Ok(response.result())
+}
+
+#[cfg(test)]
+mod tests {
+    mod provider {
+        use super::super::Provider;
+
+        #[test]
+        fn it_names_the_environment_variable_holding_its_key() {
+            assert_eq!(Provider::Claude.env_var(), "CLAUDE_API_KEY");
+            assert_eq!(Provider::OpenAI.env_var(), "OPENAI_API_KEY");
+        }
+
+        #[test]
+        fn it_displays_its_name() {
+            assert_eq!(Provider::Claude.to_string(), "Claude");
+            assert_eq!(Provider::OpenAI.to_string(), "OpenAI");
+        }
+    }
+}
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Cogito and its AI service providers in a single crate.
+//!
+//! This crate re-exports [Cogito](cogito) itself, along with each provider
+//! that is enabled by a feature:
+//!
+//! | Feature  | Module     | Provider        |
+//! | -------- | ---------- | --------------- |
+//! | `claude` | [`claude`] | cogito-claude   |
+//! | `openai` | [`openai`] | cogito-openai   |
+//!
+//! No providers are enabled by default. Providers that are not enabled are
+//! not compiled at all, and neither are their dependencies, so enable only
+//! the providers you use:
+//!
+//! ```toml
+//! [dependencies]
+//! cogito-all = { version = "0.1", features = ["openai"] }
+//! ```
+//!
+//! The `tracing` feature enables request instrumentation in Cogito and in
+//! every enabled provider.
+//!
+//! By default, Cogito [waits](cogito::time::sleep) using a timer of its
+//! own, which does not need a tokio runtime, although sending requests
+//! still does. The `tokio` feature makes it wait using tokio's timer
+//! instead, which is cheaper.
+//!
+//! The `ask` feature enables every provider, along with [`ask()`], the
+//! simplest way to send a prompt to an AI service:
+//!
+//! ```no_run
+//! # #[cfg(feature = "ask")]
+//! # async fn run() -> cogito_all::client::AiResult<()> {
+//! use cogito_all::{ModelClass, Provider};
+//!
+//! let haiku = cogito_all::ask(Provider::Claude, ModelClass::Cheapest, "Write me a haiku.").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub use cogito::*;
+
+#[cfg(feature = "ask")]
+mod ask;
+
+#[cfg(feature = "ask")]
+pub use ask::{Provider, ask};
+
+/// Hypertyper, which provides the authentication data and HTTP client
+/// factories used to create clients.
+pub use hypertyper;
+
+/// The Anthropic Claude provider.
+#[cfg(feature = "claude")]
+pub use cogito_claude as claude;
+
+/// The OpenAI provider.
+#[cfg(feature = "openai")]
+pub use cogito_openai as openai;
+
+/// Convenience module for splat imports.
+///
+/// This contains everything in [Cogito's prelude](cogito::prelude), along
+/// with the Hypertyper types needed to create a client. Each provider's
+/// own types are in its prelude, such as `cogito_all::openai::prelude`.
+pub mod prelude {
+    pub use cogito::prelude::*;
+    pub use hypertyper::prelude::{Auth, HttpClientFactory};
+}
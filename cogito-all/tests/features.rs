@@ -0,0 +1,49 @@
+// Each provider is only available when its feature is enabled. Run these
+// tests with each combination of features to confirm that every
+// combination compiles:
+//
+//     cargo test -p cogito-all
+//     cargo test -p cogito-all --features claude
+//     cargo test -p cogito-all --features openai
+//     cargo test -p cogito-all --features claude,openai
+//     cargo test -p cogito-all --features ask
+
+use cogito_all::prelude::*;
+
+#[cfg(any(feature = "claude", feature = "openai"))]
+fn factory() -> HttpClientFactory {
+    HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
+
+#[test]
+fn it_reexports_cogito() {
+    let policy = RetryPolicy::new(3);
+    assert_eq!(policy, cogito::client::RetryPolicy::new(3));
+}
+
+#[cfg(feature = "claude")]
+#[test]
+fn it_reexports_the_claude_provider() {
+    use cogito_all::claude::prelude::*;
+
+    let client = ClaudeClient::new(Auth::new("some-api-key"), factory());
+    assert!(client.capabilities().vision);
+}
+
+#[cfg(feature = "openai")]
+#[test]
+fn it_reexports_the_openai_provider() {
+    use cogito_all::openai::prelude::*;
+
+    let client = OpenAIClient::new(Auth::new("some-api-key"), factory());
+    assert_eq!(client.model(), OpenAIModel::default());
+}
+
+#[cfg(feature = "ask")]
+#[test]
+fn it_exports_ask() {
+    use cogito_all::{Provider, ask};
+
+    let question = ask(Provider::OpenAI, ModelClass::Cheapest, "hi");
+    drop(question);
+}
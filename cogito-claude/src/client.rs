@@ -31,14 +31,33 @@
 
 use crate::ClaudeModel;
 use crate::service::ClaudeService;
+use crate::stream::ClaudeStreamEvent;
+#[cfg(feature = "tracing")]
+use cogito::client::send_span;
 use cogito::prelude::*;
+use cogito::service::AuthScheme;
+use cogito::stream::{
+    AccumulatedResponse, EventStream, Reconnection, ResponseAccumulator, ResponseDelta,
+    ResumableStream,
+};
+use cogito::{normalize_whitespace, truncate_to_tokens};
 use hypertyper::prelude::*;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
 use std::slice::Iter;
+use std::sync::Arc;
+#[cfg(feature = "tracing")]
+use tracing::Instrument;
 
 #[cfg(doc)]
 use cogito::AiModel;
 
+/// The message sent by a [continuation](ClaudeRequest::from_response_continuation)
+/// request to ask Claude to resume its previous response.
+const CONTINUATION_PROMPT: &str =
+    "Continue your previous response exactly where it left off, without repeating anything.";
+
 /// An Anthropic Claude API client.
 ///
 /// # Examples
@@ -54,27 +73,202 @@ use cogito::AiModel;
 /// let factory = HttpClientFactory::new("my-package", "v1.0.0");
 /// let client = ClaudeClient::new(auth, factory);
 /// ```
-#[derive(Debug)]
-pub struct ClaudeClient<T: HttpPost + Sync> {
-    auth: Auth,
+///
+/// Clients are cheap to clone. Clones share their authentication data and,
+/// when using the default service, the underlying HTTP connection pool,
+/// so a client can be cloned and moved into each task that needs it
+/// instead of being wrapped in an [`Arc`].
+#[derive(Clone, Debug)]
+pub struct ClaudeClient<T: AiPost> {
+    auth: Arc<Auth>,
     service: T,
+    base_url: String,
+    check_context_window: bool,
 }
 
-impl<T: HttpPost + Sync> AiClient for ClaudeClient<T> {
+impl<T: AiPost> AiClient for ClaudeClient<T> {
     type AiRequest = ClaudeRequest;
     type AiResponse = ClaudeResponse;
 
+    /// Sends the request to the Claude API and receives a response.
+    ///
+    /// If the `tracing` feature is enabled, the request is instrumented
+    /// with a [span](cogito::client::send_span) that records the number of
+    /// tokens Claude used.
     async fn send(&self, request: &Self::AiRequest) -> AiResult<Self::AiResponse> {
-        self.service.post(Self::BASE_URI, &self.auth, request).await
+        self.send_with_auth(&self.auth, request).await
+    }
+
+    /// Sends the request to the Claude API using the given authentication
+    /// data, and receives a response.
+    async fn send_with_auth(
+        &self,
+        auth: &Auth,
+        request: &Self::AiRequest,
+    ) -> AiResult<Self::AiResponse> {
+        self.preflight(request)?;
+        let uri = self.endpoint("messages");
+        let response = self.service.try_post(uri, auth, request);
+
+        #[cfg(feature = "tracing")]
+        let span = send_span(Self::provider_name(), &request.model, &request.input_text());
+        #[cfg(feature = "tracing")]
+        let response = response.instrument(span.clone());
+
+        let response: ClaudeResponse = response.await?;
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("input_tokens", response.usage.input_tokens);
+            span.record("output_tokens", response.usage.output_tokens);
+        }
+
+        Ok(response)
+    }
+
+    /// The features supported by the Claude API.
+    ///
+    /// Every Claude model supports streaming, tools, vision, and extended
+    /// thinking. Claude does not support seeds or a JSON mode.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            streaming: true,
+            tools: true,
+            vision: true,
+            reasoning: true,
+            seed: false,
+            json_mode: false,
+        }
+    }
+
+    /// The IDs of the models available from the Claude API's `models`
+    /// endpoint, newest first.
+    async fn models(&self) -> AiResult<Vec<String>> {
+        let uri = self.endpoint("models?limit=1000");
+        let models: ClaudeModelList = self.service.try_get(uri, &self.auth).await?;
+        Ok(models.data.into_iter().map(|model| model.id).collect())
+    }
+
+    /// Checks that the Claude API accepts the client's credentials.
+    ///
+    /// A request for a single output token is sent using the
+    /// [cheapest](ClaudeModel::cheapest()) model, which keeps the cost
+    /// of the check to a minimum.
+    async fn health_check(&self) -> AiResult<()> {
+        let request = ClaudeRequest::default()
+            .model(ClaudeModel::cheapest())
+            .max_tokens(1)
+            .input("ping");
+        self.send(&request).await.map(|_| ())
+    }
+}
+
+impl<T: AiPost> ProviderMetadata for ClaudeClient<T> {
+    fn provider_name() -> &'static str {
+        "Anthropic Claude"
+    }
+
+    fn base_url() -> &'static str {
+        "https://api.anthropic.com/v1"
+    }
+
+    fn auth_scheme() -> AuthScheme {
+        ClaudeService::AUTH_SCHEME
     }
 }
 
-impl<T: HttpPost + Sync> ClaudeClient<T> {
-    /// The base URI for Claude API requests.
-    const BASE_URI: &'static str = "https://api.anthropic.com/v1/messages";
+impl<T: AiPost> ClaudeClient<T> {
+    /// The URI of the Claude API endpoint at the given path.
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{path}", self.base_url)
+    }
+
+    /// Create a new Claude client that sends requests using the given
+    /// `service`.
+    ///
+    /// This allows a client to be built on a service that adds behavior
+    /// around the [`ClaudeService`], such as a
+    /// [`CachingService`](cogito::service::CachingService), or on a mock
+    /// service in tests. The service is responsible for authenticating
+    /// requests in the way the Claude API expects, so it should usually wrap
+    /// a `ClaudeService`.
+    ///
+    /// # Examples
+    ///
+    /// Cache responses to identical requests:
+    ///
+    /// ```
+    /// use cogito_claude::prelude::*;
+    /// use cogito::service::CachingService;
+    /// use hypertyper::prelude::*;
+    ///
+    /// let auth = Auth::new("my-claude-api-key");
+    /// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+    /// let service = CachingService::new(ClaudeService::new(factory));
+    /// let client = ClaudeClient::with_service(auth, service);
+    /// ```
+    pub fn with_service(auth: Auth, service: T) -> Self {
+        let auth = Arc::new(auth);
+        let base_url = Self::base_url().to_string();
+        Self {
+            auth,
+            service,
+            base_url,
+            check_context_window: false,
+        }
+    }
+
+    /// Sends requests to the API at `base_url` instead of the Claude API.
+    ///
+    /// This is useful for talking to a proxy in front of the Claude API,
+    /// or to a local mock server in tests. `base_url` replaces the
+    /// [default base URL](ProviderMetadata::base_url), so it should
+    /// include any version prefix, such as `http://localhost:8080/v1`.
+    pub fn with_base_url(self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { base_url, ..self }
+    }
+
+    /// Checks that each request fits within its model's context window
+    /// before sending it.
+    ///
+    /// A request's estimated input tokens, plus its
+    /// [maximum output tokens](ClaudeRequest::max_tokens), must fit within
+    /// the [context window](cogito::AiModel::context_window), or an
+    /// [`AiError::ContextWindowExceeded`] error is returned without
+    /// contacting the Claude API. Tokens are only
+    /// [estimated](cogito::AiModel::estimate_tokens), and the estimate is
+    /// conservative, so this is off by default.
+    pub fn with_context_window_check(self) -> Self {
+        Self {
+            check_context_window: true,
+            ..self
+        }
+    }
 
-    fn with_service(auth: Auth, service: T) -> Self {
-        Self { auth, service }
+    fn preflight(&self, request: &ClaudeRequest) -> AiResult<()> {
+        if self.check_context_window {
+            request.check_context_window(request.max_tokens as usize)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Counts the input tokens the request would use, without sending it.
+    ///
+    /// Claude's `count_tokens` endpoint is free to use and counts tokens
+    /// exactly as the request's model would, so it is more accurate than
+    /// [estimating](cogito::AiModel::estimate_tokens) them. Only the
+    /// request's model and messages are counted; the endpoint does not
+    /// accept generation options such as `max_tokens`.
+    pub async fn count_tokens(&self, request: &ClaudeRequest) -> AiResult<u64> {
+        let uri = self.endpoint("messages/count_tokens");
+        let body = ClaudeCountTokensRequest {
+            model: &request.model,
+            messages: &request.messages,
+        };
+        let count: ClaudeTokenCount = self.service.try_post(uri, &self.auth, &body).await?;
+        Ok(count.input_tokens)
     }
 }
 
@@ -85,6 +279,95 @@ impl ClaudeClient<ClaudeService> {
         let service = ClaudeService::new(factory);
         Self::with_service(auth, service)
     }
+
+    /// Create a new Claude API client that requests the given version of
+    /// the Claude API.
+    ///
+    /// See [`ClaudeService::with_version()`] for more information.
+    pub fn new_with_version(auth: Auth, factory: HttpClientFactory, version: &str) -> Self {
+        let service = ClaudeService::with_version(factory, version);
+        Self::with_service(auth, service)
+    }
+
+    /// Sends the request to the Claude API and receives a response, along
+    /// with the rate-limit budget Claude reported with it.
+    ///
+    /// This is useful for pacing requests, or for showing users how many
+    /// requests they have left.
+    pub async fn send_with_rate_limits(
+        &self,
+        request: &ClaudeRequest,
+    ) -> AiResult<(ClaudeResponse, RateLimitStatus)> {
+        self.preflight(request)?;
+        let uri = self.endpoint("messages");
+        self.service
+            .try_post_with_rate_limits(uri, &self.auth, request)
+            .await
+    }
+
+    /// Sends the request to the Claude API and streams the response as it
+    /// is generated.
+    pub async fn stream(
+        &self,
+        request: &ClaudeRequest,
+    ) -> AiResult<EventStream<ClaudeStreamEvent>> {
+        self.preflight(request)?;
+        let uri = self.endpoint("messages");
+        let request = ClaudeRequest {
+            stream: true,
+            ..request.clone()
+        };
+        self.service.post_stream(uri, &self.auth, &request).await
+    }
+
+    /// Sends the request to the Claude API and streams the response,
+    /// reconnecting up to `max_retries` times if the connection drops
+    /// partway through it.
+    ///
+    /// The stream reconnects with a
+    /// [continuation](ClaudeRequest::from_stream_continuation) that
+    /// prefills the text received so far, so Claude only sends the rest of
+    /// the response. The complete response is every text delta across all
+    /// of the connections.
+    pub async fn stream_resumable<'a>(
+        &'a self,
+        request: &'a ClaudeRequest,
+        max_retries: u32,
+    ) -> AiResult<
+        ResumableStream<
+            ClaudeStreamEvent,
+            impl FnMut(&[ClaudeStreamEvent]) -> Reconnection<'a, ClaudeStreamEvent>,
+        >,
+    > {
+        let stream = self.stream(request).await?;
+        let reconnect =
+            move |received: &[ClaudeStreamEvent]| -> Reconnection<'a, ClaudeStreamEvent> {
+                let request = ClaudeRequest::from_stream_continuation(request, received);
+                Box::pin(async move { self.stream(&request).await })
+            };
+        Ok(ResumableStream::new(stream, max_retries, reconnect))
+    }
+
+    /// Sends the request to the Claude API, streaming the response, and
+    /// returns the response once the stream is finished.
+    ///
+    /// If Claude reports an error partway through the stream, such as an
+    /// `overloaded_error`, it is returned as an error.
+    pub async fn send_streamed(&self, request: &ClaudeRequest) -> AiResult<AccumulatedResponse> {
+        let mut stream = self.stream(request).await?;
+        let mut accumulator = ResponseAccumulator::new();
+        let mut usage = None;
+        while let Some(event) = stream.next().await {
+            let event = event?;
+            if let Some(error) = event.error() {
+                return Err(error);
+            }
+            usage = event.usage(usage);
+            accumulator.extend(event.delta());
+        }
+        accumulator.extend(usage.map(ResponseDelta::Usage));
+        Ok(accumulator.finish())
+    }
 }
 
 /// Parameters and data for a Claude API request.
@@ -94,11 +377,41 @@ impl ClaudeClient<ClaudeService> {
 /// `ClaudeRequest` uses a builder pattern to build up its internal
 /// structure over time, allowing you to use default values for
 /// values you do not care about.
-#[derive(Debug, Deserialize, Serialize)]
+///
+/// Requests can be cloned, so a request can be kept as a template for
+/// other requests that differ only in a few options:
+///
+/// ```
+/// use cogito::client::AiRequest;
+/// use cogito_claude::client::ClaudeRequest;
+///
+/// let template = ClaudeRequest::default().instructions("You are a poet.");
+/// let precise = template.clone().temperature(0.2).input("Write me a haiku.");
+/// let creative = template.clone().temperature(1.0).input("Write me a haiku.");
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct ClaudeRequest {
     model: ClaudeModel,
     max_tokens: u32,
     messages: Vec<ClaudeMessage>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<ClaudeMetadata>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ToolChoice>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+
+    #[serde(flatten)]
+    extra: Map<String, Value>,
 }
 
 impl Default for ClaudeRequest {
@@ -107,6 +420,12 @@ impl Default for ClaudeRequest {
             model: ClaudeModel::default(),
             max_tokens: 1024,
             messages: vec![],
+            stop_sequences: vec![],
+            metadata: None,
+            temperature: None,
+            tool_choice: None,
+            stream: false,
+            extra: Map::new(),
         }
     }
 }
@@ -127,6 +446,10 @@ impl AiRequest for ClaudeRequest {
         Self { model, ..self }
     }
 
+    fn model_used(&self) -> ClaudeModel {
+        self.model.clone()
+    }
+
     fn instructions(self, instructions: impl Into<String>) -> Self {
         self.input(instructions)
     }
@@ -137,12 +460,391 @@ impl AiRequest for ClaudeRequest {
         messages.push(message);
         Self { messages, ..self }
     }
+
+    /// The request's current input.
+    ///
+    /// Claude requests accumulate input as a series of messages, so this
+    /// is the content of every user message, joined by newlines.
+    fn input_text(&self) -> Cow<'_, str> {
+        let text = self
+            .messages
+            .iter()
+            .filter(|m| m.role == ClaudeRole::User)
+            .map(|m| m.content.text())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Cow::Owned(text)
+    }
+
+    /// Adds a field that is not otherwise supported to the request's body.
+    ///
+    /// This is useful for using Claude API options that are not yet
+    /// supported by `ClaudeRequest`, such as `top_k`.
+    fn extra(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.extra.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Appends more text to the request's input.
+    ///
+    /// Claude requests accumulate input as a series of messages, so `more`
+    /// is added as another user message, exactly like
+    /// [`input()`](ClaudeRequest::input). The earlier messages are left
+    /// untouched, including their cache control settings.
+    fn append_input(self, more: impl Into<String>) -> Self {
+        self.input(more)
+    }
+
+    /// Adds an example exchange to the request's messages.
+    ///
+    /// The example is added as a user message containing `input`, followed
+    /// by an assistant message containing `output`, so that the examples
+    /// alternate between the user and Claude.
+    fn example(self, input: impl Into<String>, output: impl Into<String>) -> Self {
+        let mut messages = self.messages;
+        messages.push(ClaudeMessage::with_content(input));
+        messages.push(ClaudeMessage {
+            role: ClaudeRole::Assistant,
+            content: ClaudeMessageContent::Text(output.into()),
+        });
+        Self { messages, ..self }
+    }
+
+    /// Truncates the request's input so that it fits within `max_tokens`.
+    ///
+    /// The oldest messages are removed first, so the most recent turns of
+    /// the conversation are kept. The final user message is never removed,
+    /// but it is truncated if it does not fit on its own. Truncated
+    /// messages keep their cache control settings.
+    fn truncate_input_to(self, max_tokens: usize, model: &ClaudeModel) -> Self {
+        let mut messages = self.messages;
+        let mut remaining = max_tokens;
+        // The final user message is what Claude responds to, so room is
+        // made for it before any other message.
+        let last = messages.iter().rposition(|m| m.role == ClaudeRole::User);
+        if let Some(i) = last {
+            let message = messages[i].clone().truncate_to(remaining, model);
+            remaining -= model.estimate_tokens(&message.content.text());
+            messages[i] = message;
+        }
+        let mut fits = true;
+        let mut messages = messages
+            .into_iter()
+            .enumerate()
+            .rev()
+            .filter(|(i, m)| {
+                if Some(*i) == last {
+                    return true;
+                }
+                let tokens = model.estimate_tokens(&m.content.text());
+                fits = fits && tokens <= remaining;
+                if fits {
+                    remaining -= tokens;
+                }
+                fits
+            })
+            .map(|(_, m)| m)
+            .collect::<Vec<_>>();
+        messages.reverse();
+        Self { messages, ..self }
+    }
+
+    /// Normalizes the whitespace in each of the request's messages.
+    ///
+    /// Messages keep their cache control settings.
+    fn normalize_input(self) -> Self {
+        let messages = self
+            .messages
+            .into_iter()
+            .map(ClaudeMessage::normalize)
+            .collect();
+        Self { messages, ..self }
+    }
+}
+
+impl ClaudeRequest {
+    /// Appends input to the request and marks it for prompt caching.
+    ///
+    /// Claude caches the prompt up to and including cached input, so
+    /// later requests that begin with the same input can reuse it
+    /// instead of processing it again, which is faster and cheaper.
+    /// This works best for a large prefix shared by many requests, such
+    /// as a long document, followed by a smaller [input](AiRequest::input)
+    /// that varies from request to request.
+    ///
+    /// For more information, see [prompt caching] in the Claude platform
+    /// documentation.
+    ///
+    /// [prompt caching]: https://platform.claude.com/docs/en/build-with-claude/prompt-caching
+    pub fn input_cached(self, input: impl Into<String>) -> Self {
+        let message = ClaudeMessage::with_cached_content(input);
+        let mut messages = self.messages.clone();
+        messages.push(message);
+        Self { messages, ..self }
+    }
+
+    /// Appends a base64-encoded image to the request's input.
+    ///
+    /// `media_type` is the image's MIME type, such as `image/png`, and
+    /// `data` is the image itself, encoded as base64. The image is added to
+    /// the most recent user message, so it can be combined with text in a
+    /// single message:
+    ///
+    /// ```
+    /// use cogito::prelude::*;
+    /// use cogito_claude::client::ClaudeRequest;
+    ///
+    /// let request = ClaudeRequest::default()
+    ///     .input("What is in this image?")
+    ///     .input_image_base64("image/png", "iVBORw0KGgo=");
+    /// ```
+    ///
+    /// If the request does not have any input yet, the image is sent in a
+    /// message of its own.
+    ///
+    /// For more information, see [vision] in the Claude platform
+    /// documentation.
+    ///
+    /// [vision]: https://platform.claude.com/docs/en/build-with-claude/vision
+    pub fn input_image_base64(self, media_type: &str, data: &str) -> Self {
+        let block = ClaudeContentBlock::Image {
+            source: ClaudeImageSource::Base64 {
+                media_type: media_type.to_string(),
+                data: data.to_string(),
+            },
+        };
+        let mut messages = self.messages;
+        match messages.last_mut() {
+            Some(message) if message.role == ClaudeRole::User => message.content.push(block),
+            _ => messages.push(ClaudeMessage::with_blocks(vec![block])),
+        }
+        Self { messages, ..self }
+    }
+
+    /// Prefills the beginning of Claude's response.
+    ///
+    /// Claude continues its response from the end of `text`, which is
+    /// useful for steering the format of the response, or for resuming a
+    /// [stream](cogito::stream::ResumableStream) whose connection dropped
+    /// partway through a response by resending the text received so far.
+    /// The Claude API rejects prefilled text that ends in whitespace, so
+    /// any trailing whitespace is removed.
+    pub fn prefill(self, text: &str) -> Self {
+        let message = ClaudeMessage {
+            role: ClaudeRole::Assistant,
+            content: ClaudeMessageContent::Text(text.trim_end().to_string()),
+        };
+        let mut messages = self.messages;
+        messages.push(message);
+        Self { messages, ..self }
+    }
+
+    /// Builds a request that continues generating a previous response.
+    ///
+    /// This is useful when Claude [ran out of output
+    /// tokens](ClaudeResponse::hit_max_tokens) before finishing its
+    /// response. The new request uses the same settings as the original
+    /// `request`, and its messages are the original messages followed by
+    /// the `response` as an assistant message and a user message asking
+    /// Claude to continue, so sending it picks up where the previous
+    /// response left off.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use cogito_claude::prelude::*;
+    /// # use hypertyper::prelude::*;
+    /// # async fn run() -> cogito::client::AiResult<String> {
+    /// let auth = Auth::from_env("CLAUDE_API_KEY").expect("no API key");
+    /// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+    /// let client = ClaudeClient::new(auth, factory);
+    ///
+    /// let mut request = ClaudeRequest::default().input("Write me an epic poem.");
+    /// let mut response = client.send(&request).await?;
+    /// let mut poem = response.result();
+    /// while response.hit_max_tokens() {
+    ///     request = ClaudeRequest::from_response_continuation(&request, &response);
+    ///     response = client.send(&request).await?;
+    ///     poem.push(' ');
+    ///     poem.push_str(&response.result());
+    /// }
+    /// # Ok(poem)
+    /// # }
+    /// ```
+    pub fn from_response_continuation(request: &ClaudeRequest, response: &ClaudeResponse) -> Self {
+        let mut messages = request.messages.clone();
+        messages.push(ClaudeMessage {
+            role: ClaudeRole::Assistant,
+            content: ClaudeMessageContent::Text(response.text()),
+        });
+        messages.push(ClaudeMessage {
+            role: ClaudeRole::User,
+            content: ClaudeMessageContent::Text(CONTINUATION_PROMPT.to_string()),
+        });
+        Self {
+            messages,
+            ..request.clone()
+        }
+    }
+
+    /// Builds a request that continues a streamed response whose
+    /// connection dropped, given the `received` events.
+    ///
+    /// The new request is the original `request` with the text received
+    /// so far [prefilled](ClaudeRequest::prefill), so that Claude picks up
+    /// where it left off. Claude may repeat any whitespace that was trimmed
+    /// from the end of the prefill. If no text was received, nothing is
+    /// lost by starting over, so `request` is returned unchanged.
+    ///
+    /// [`ClaudeClient::stream_resumable()`] uses this to reconnect.
+    pub fn from_stream_continuation(
+        request: &ClaudeRequest,
+        received: &[ClaudeStreamEvent],
+    ) -> Self {
+        let text: String = received
+            .iter()
+            .filter_map(|e| match e.delta() {
+                Some(ResponseDelta::Text(text)) => Some(text),
+                _ => None,
+            })
+            .collect();
+        if text.trim_end().is_empty() {
+            return request.clone();
+        }
+        request.clone().prefill(&text)
+    }
+
+    /// Sets the maximum number of tokens Claude will generate in its
+    /// response.
+    ///
+    /// Claude may stop before reaching this limit. If not specified, at
+    /// most 1,024 tokens are generated.
+    pub fn max_tokens(self, max_tokens: u32) -> Self {
+        Self { max_tokens, ..self }
+    }
+
+    /// Lets Claude generate as many tokens as the request's model allows.
+    ///
+    /// This sets the [maximum number of tokens](ClaudeRequest::max_tokens)
+    /// to the [model's limit](ClaudeModel::max_output_tokens), so it should
+    /// be called after the request's model is set.
+    pub fn max_tokens_for_model(self) -> Self {
+        let max_tokens = self.model.max_output_tokens();
+        Self { max_tokens, ..self }
+    }
+
+    /// Sets custom sequences of text that will cause Claude to stop
+    /// generating its response.
+    ///
+    /// If Claude stops because it encounters one of these sequences, the
+    /// matched sequence is reported by the response's
+    /// [`stop_sequence()`](ClaudeResponse::stop_sequence).
+    pub fn stop_sequences<I, S>(self, stop_sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let stop_sequences = stop_sequences.into_iter().map(Into::into).collect();
+        Self {
+            stop_sequences,
+            ..self
+        }
+    }
+
+    /// Sets an identifier for the user on whose behalf the request is made.
+    ///
+    /// Anthropic uses this identifier to help detect abuse, so it should be
+    /// stable for each user of your application. It should be an opaque
+    /// value, such as a UUID or a hash, rather than a name, email address,
+    /// or any other identifying information.
+    pub fn user_id(self, user_id: impl Into<String>) -> Self {
+        let user_id = user_id.into();
+        let metadata = Some(ClaudeMetadata { user_id });
+        Self { metadata, ..self }
+    }
+
+    /// Sets the amount of randomness in Claude's response.
+    ///
+    /// Claude accepts temperatures from 0.0 to 1.0: lower temperatures
+    /// produce more analytical responses, while higher temperatures produce
+    /// more creative ones. This range is narrower than some other AI
+    /// services allow, so temperatures outside of it are clamped to the
+    /// nearest valid value rather than being rejected by the Claude API.
+    /// For example, a temperature of 1.5 is sent as 1.0. If not specified,
+    /// Claude uses a temperature of 1.0.
+    pub fn temperature(self, temperature: f32) -> Self {
+        let temperature = Some(temperature.clamp(0.0, 1.0));
+        Self {
+            temperature,
+            ..self
+        }
+    }
+
+    /// Sets how Claude chooses which tool to use, if any.
+    ///
+    /// This only has an effect if the request provides tools, which can be
+    /// passed as an [extra](AiRequest::extra) `tools` field. If not
+    /// specified, Claude decides for itself whether to use a tool.
+    pub fn tool_choice(self, tool_choice: ToolChoice) -> Self {
+        let tool_choice = Some(tool_choice);
+        Self {
+            tool_choice,
+            ..self
+        }
+    }
+
+    /// Sets the [maximum number of tokens](ClaudeRequest::max_tokens) in
+    /// place.
+    pub fn set_max_tokens(&mut self, max_tokens: u32) -> &mut Self {
+        self.update(|r| r.max_tokens(max_tokens))
+    }
+
+    /// Sets the [temperature](ClaudeRequest::temperature) in place.
+    pub fn set_temperature(&mut self, temperature: f32) -> &mut Self {
+        self.update(|r| r.temperature(temperature))
+    }
+}
+
+/// How Claude chooses which tool to use in its response.
+///
+/// See [`ClaudeRequest::tool_choice()`].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ToolChoice {
+    /// Claude decides whether to use any of the provided tools.
+    Auto,
+
+    /// Claude must use one of the provided tools, but can choose which.
+    Any,
+
+    /// Claude must use the named tool.
+    Tool {
+        /// The name of the tool.
+        name: String,
+    },
+
+    /// Claude must not use any tools.
+    None,
+}
+
+impl ToolChoice {
+    /// Forces Claude to use the tool with the given `name`.
+    pub fn tool(name: impl Into<String>) -> Self {
+        let name = name.into();
+        ToolChoice::Tool { name }
+    }
+}
+
+/// Metadata about a Claude API request.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct ClaudeMetadata {
+    user_id: String,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 struct ClaudeMessage {
     role: ClaudeRole,
-    content: String,
+    content: ClaudeMessageContent,
 }
 
 impl ClaudeMessage {
@@ -151,11 +853,168 @@ impl ClaudeMessage {
             // I think we always want to use user but who knows, the
             // documentation is sparse.
             role: ClaudeRole::User,
-            content: content.into(),
+            content: ClaudeMessageContent::Text(content.into()),
+        }
+    }
+
+    pub fn with_cached_content(content: impl Into<String>) -> Self {
+        let block = ClaudeContentBlock::Text {
+            text: content.into(),
+            cache_control: Some(ClaudeCacheControl::Ephemeral),
+        };
+        Self::with_blocks(vec![block])
+    }
+
+    pub fn with_blocks(blocks: Vec<ClaudeContentBlock>) -> Self {
+        Self {
+            role: ClaudeRole::User,
+            content: ClaudeMessageContent::Blocks(blocks),
+        }
+    }
+
+    pub fn truncate_to(self, max_tokens: usize, model: &ClaudeModel) -> Self {
+        let content = self.content.truncate_to(max_tokens, model);
+        Self { content, ..self }
+    }
+
+    pub fn normalize(self) -> Self {
+        let content = self.content.normalize();
+        Self { content, ..self }
+    }
+}
+
+/// Content of a message in a Claude API request.
+///
+/// Content can be sent as a plain string, or as a series of content blocks
+/// when options such as cache control need to be attached to it, or when
+/// it includes something other than text, such as an image.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+enum ClaudeMessageContent {
+    Text(String),
+    Blocks(Vec<ClaudeContentBlock>),
+}
+
+impl ClaudeMessageContent {
+    /// The text of the content, with multiple blocks joined by newlines.
+    pub fn text(&self) -> Cow<'_, str> {
+        match self {
+            ClaudeMessageContent::Text(text) => Cow::Borrowed(text),
+            ClaudeMessageContent::Blocks(blocks) => {
+                let text = blocks
+                    .iter()
+                    .filter_map(|b| match b {
+                        ClaudeContentBlock::Text { text, .. } => Some(text.as_str()),
+                        ClaudeContentBlock::Image { .. } => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Cow::Owned(text)
+            }
+        }
+    }
+
+    /// Truncates the content so that it fits within `max_tokens`.
+    pub fn truncate_to(self, max_tokens: usize, model: &ClaudeModel) -> Self {
+        match self {
+            ClaudeMessageContent::Text(text) => {
+                ClaudeMessageContent::Text(truncate_to_tokens(&text, max_tokens, model).to_string())
+            }
+            ClaudeMessageContent::Blocks(blocks) => {
+                let mut remaining = max_tokens;
+                let blocks = blocks
+                    .into_iter()
+                    .map_while(|b| {
+                        if remaining == 0 {
+                            return None;
+                        }
+                        let ClaudeContentBlock::Text {
+                            text,
+                            cache_control,
+                        } = b
+                        else {
+                            return Some(b);
+                        };
+                        let text = truncate_to_tokens(&text, remaining, model).to_string();
+                        remaining -= model.estimate_tokens(&text);
+                        Some(ClaudeContentBlock::Text {
+                            text,
+                            cache_control,
+                        })
+                    })
+                    .collect();
+                ClaudeMessageContent::Blocks(blocks)
+            }
+        }
+    }
+
+    /// Normalizes the whitespace in the content's text.
+    pub fn normalize(self) -> Self {
+        match self {
+            ClaudeMessageContent::Text(text) => {
+                ClaudeMessageContent::Text(normalize_whitespace(&text))
+            }
+            ClaudeMessageContent::Blocks(blocks) => {
+                let blocks = blocks
+                    .into_iter()
+                    .map(|b| match b {
+                        ClaudeContentBlock::Text {
+                            text,
+                            cache_control,
+                        } => ClaudeContentBlock::Text {
+                            text: normalize_whitespace(&text),
+                            cache_control,
+                        },
+                        b => b,
+                    })
+                    .collect();
+                ClaudeMessageContent::Blocks(blocks)
+            }
+        }
+    }
+
+    /// Appends a block to the content, converting plain text content to
+    /// the block form.
+    pub fn push(&mut self, block: ClaudeContentBlock) {
+        match self {
+            ClaudeMessageContent::Text(text) => {
+                let text = ClaudeContentBlock::Text {
+                    text: std::mem::take(text),
+                    cache_control: None,
+                };
+                *self = ClaudeMessageContent::Blocks(vec![text, block]);
+            }
+            ClaudeMessageContent::Blocks(blocks) => blocks.push(block),
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClaudeContentBlock {
+    Text {
+        text: String,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<ClaudeCacheControl>,
+    },
+    Image {
+        source: ClaudeImageSource,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClaudeImageSource {
+    Base64 { media_type: String, data: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClaudeCacheControl {
+    Ephemeral,
+}
+
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 enum ClaudeRole {
@@ -164,10 +1023,12 @@ enum ClaudeRole {
 }
 
 /// A response from the Claude API.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct ClaudeResponse {
     id: String,
 
+    model: String,
+
     // TODO: Use an enum, when I figure out what the possible values are
     #[serde(rename = "type")]
     response_type: String,
@@ -176,55 +1037,175 @@ pub struct ClaudeResponse {
 
     content: Vec<ClaudeContent>,
 
+    stop_sequence: Option<String>,
+
     // Useful for debugging
     usage: ClaudeUsage,
+
+    #[serde(flatten)]
+    extra: Value,
 }
 
 impl AiResponse for ClaudeResponse {
+    /// The response's text.
+    ///
+    /// In debug builds with the `tracing` feature enabled, a warning is
+    /// logged if Claude [ran out of tokens](ClaudeResponse::hit_max_tokens),
+    /// so that silently truncated results are noticed during development.
     fn result(&self) -> String {
-        self.content()
-            .map(|c| c.text.as_str())
-            .collect::<Vec<_>>()
-            .join("\n")
-            .trim()
-            .to_string()
+        self.warn_if_truncated();
+        self.text()
     }
-}
 
-impl ClaudeResponse {
-    /// Claude API response output, as a series of responses.
-    ///
-    /// There should be at least one item in the output, but there could
-    /// be multiple output objects.
-    fn content(&self) -> Iter<'_, ClaudeContent> {
-        self.content.iter()
+    /// The custom stop sequence that caused Claude to stop generating its
+    /// response, if any.
+    fn stop_sequence(&self) -> Option<&str> {
+        self.stop_sequence.as_deref()
     }
-}
 
-#[derive(Debug, Deserialize, Serialize)]
-struct ClaudeContent {
-    // TODO: Use an enum, when I figure out what the possible values are
-    #[serde(rename = "type")]
-    content_type: String,
+    fn usage(&self) -> Option<Usage> {
+        let usage = &self.usage;
+        Some(Usage {
+            input_tokens: usage.input_tokens
+                + usage.cache_creation_input_tokens
+                + usage.cache_read_input_tokens,
+            output_tokens: usage.output_tokens,
+            cached_input_tokens: usage.cache_read_input_tokens,
+        })
+    }
 
-    text: String,
+    /// The fields of the Claude API response that are not otherwise
+    /// modeled, such as the response's `stop_reason`.
+    fn raw(&self) -> Option<&Value> {
+        Some(&self.extra)
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct ClaudeUsage {
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_creation_input_tokens: u64,
-    cache_read_input_tokens: u64,
-    cache_creation: ClaudeCacheCreation,
-}
+impl TryFrom<Value> for ClaudeResponse {
+    type Error = serde_json::Error;
 
-#[derive(Debug, Deserialize, Serialize)]
+    /// Reads a response from JSON that has already been parsed, such as a
+    /// response received by a webhook.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+impl ClaudeResponse {
+    /// The model that generated the response.
+    ///
+    /// This is the specific, versioned model that served the request, such
+    /// as `claude-sonnet-4-5-20250929`, which may differ from the model
+    /// alias that was requested.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Why Claude stopped generating its response, such as `end_turn`,
+    /// `max_tokens`, or `stop_sequence`.
+    pub fn stop_reason(&self) -> Option<&str> {
+        self.extra.get("stop_reason").and_then(Value::as_str)
+    }
+
+    /// True if Claude stopped because it ran out of output tokens.
+    ///
+    /// The rest of the response can be generated by sending a
+    /// [continuation](ClaudeRequest::from_response_continuation) request.
+    pub fn hit_max_tokens(&self) -> bool {
+        self.stop_reason() == Some("max_tokens")
+    }
+
+    /// The text of every content block, joined by newlines.
+    fn text(&self) -> String {
+        self.content()
+            .filter_map(|c| c.text())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    /// Logs a warning if Claude ran out of tokens.
+    ///
+    /// This is a no-op in release builds or without the `tracing` feature.
+    fn warn_if_truncated(&self) {
+        #[cfg(all(debug_assertions, feature = "tracing"))]
+        if self.hit_max_tokens() {
+            tracing::warn!("result of a Claude response that hit max_tokens was used");
+        }
+    }
+
+    /// Claude API response output, as a series of responses.
+    ///
+    /// There should be at least one item in the output, but there could
+    /// be multiple output objects.
+    fn content(&self) -> Iter<'_, ClaudeContent> {
+        self.content.iter()
+    }
+}
+
+/// A block of content in a Claude API response.
+///
+/// Most content is text, but Claude also returns other types of blocks,
+/// such as server tool use and web search results, which have no text.
+/// New types of blocks are added from time to time, so blocks are not
+/// required to have text.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct ClaudeContent {
+    // TODO: Use an enum, when I figure out what the possible values are
+    #[serde(rename = "type")]
+    content_type: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+impl ClaudeContent {
+    /// The block's text, if it has any.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct ClaudeUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_input_tokens: u64,
+    cache_read_input_tokens: u64,
+    cache_creation: ClaudeCacheCreation,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 struct ClaudeCacheCreation {
     ephemeral_5m_input_tokens: u64,
     ephemeral_1h_input_tokens: u64,
 }
 
+/// A list of models from the Claude API's `models` endpoint.
+#[derive(Debug, Deserialize)]
+struct ClaudeModelList {
+    data: Vec<ClaudeModelObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeModelObject {
+    id: String,
+}
+
+/// The body of a request to the Claude API's `count_tokens` endpoint.
+#[derive(Debug, Serialize)]
+struct ClaudeCountTokensRequest<'a> {
+    model: &'a ClaudeModel,
+    messages: &'a [ClaudeMessage],
+}
+
+/// A response from the Claude API's `count_tokens` endpoint.
+#[derive(Debug, Deserialize)]
+struct ClaudeTokenCount {
+    input_tokens: u64,
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -278,11 +1259,13 @@ mod tests {
         use super::super::{ClaudeClient, ClaudeRequest};
         use super::load_data;
         use cogito::prelude::*;
+        use cogito::service::CachingService;
+        use cogito::testing::MockService;
         use hypertyper::prelude::*;
         use serde::Serialize;
         use serde::de::DeserializeOwned;
 
-        #[derive(Default)]
+        #[derive(Clone, Default)]
         struct TestApiService {}
 
         impl HttpPost for TestApiService {
@@ -297,6 +1280,8 @@ mod tests {
             }
         }
 
+        impl AiPost for TestApiService {}
+
         impl TestApiService {
             fn load_data(&self) -> String {
                 load_data("responses_multi")
@@ -310,6 +1295,129 @@ mod tests {
             }
         }
 
+        #[cfg(feature = "tracing")]
+        #[tokio::test]
+        async fn it_records_a_span() {
+            use crate::ClaudeModel;
+            use cogito::testing::SpanRecorder;
+
+            let recorder = SpanRecorder::default();
+            let _guard = tracing::subscriber::set_default(recorder.clone());
+            let client = ClaudeClient::test();
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .input("Hello, world");
+            client.send(&request).await.expect("could not send request");
+            let field = |name| recorder.field(name);
+            assert_eq!(field("provider").as_deref(), Some("Anthropic Claude"));
+            assert_eq!(field("model").as_deref(), Some("claude-haiku-4-5"));
+            assert_eq!(field("input_tokens_estimate").as_deref(), Some("3"));
+            assert_eq!(field("input_tokens").as_deref(), Some("10"));
+            assert_eq!(field("output_tokens").as_deref(), Some("12"));
+        }
+
+        #[test]
+        fn it_reports_its_capabilities() {
+            let capabilities = ClaudeClient::test().capabilities();
+            assert!(capabilities.streaming);
+            assert!(capabilities.tools);
+            assert!(capabilities.vision);
+            assert!(capabilities.reasoning);
+            assert!(!capabilities.seed);
+            assert!(!capabilities.json_mode);
+        }
+
+        #[tokio::test]
+        async fn it_counts_tokens() {
+            #[derive(Clone, Default)]
+            struct TokenCountService;
+
+            impl HttpPost for TokenCountService {
+                async fn post<U, D, R>(&self, _uri: U, _auth: &Auth, _data: &D) -> HttpResult<R>
+                where
+                    U: IntoUrl + Send,
+                    D: Serialize + Sync,
+                    R: DeserializeOwned,
+                {
+                    Ok(serde_json::from_str(r#"{"input_tokens": 42}"#)?)
+                }
+            }
+
+            impl AiPost for TokenCountService {}
+
+            let client = ClaudeClient::with_service(Auth::new("some-api-key"), TokenCountService);
+            let request = ClaudeRequest::default().input("Hello, world");
+            assert_eq!(client.count_tokens(&request).await.unwrap(), 42);
+        }
+
+        #[tokio::test]
+        async fn it_rejects_requests_that_exceed_the_context_window() {
+            let service = MockService::with_response(load_data("responses_multi"));
+            let client = ClaudeClient::with_service(Auth::new("some-api-key"), service.clone())
+                .with_context_window_check();
+            let request = ClaudeRequest::default()
+                .max_tokens(8192)
+                .input("word ".repeat(200_000));
+
+            let result = client.send(&request).await;
+            assert!(
+                matches!(
+                    result,
+                    Err(AiError::ContextWindowExceeded {
+                        needed: 258_192,
+                        available: 200_000
+                    })
+                ),
+                "{result:?}"
+            );
+            assert!(service.requests().is_empty());
+        }
+
+        #[tokio::test]
+        async fn it_sends_oversized_requests_without_a_context_window_check() {
+            let service = MockService::with_response(load_data("responses_multi"));
+            let client = ClaudeClient::with_service(Auth::new("some-api-key"), service.clone());
+            let request = ClaudeRequest::default().input("word ".repeat(200_000));
+
+            assert!(client.send(&request).await.is_ok());
+            assert_eq!(service.requests().len(), 1);
+        }
+
+        #[tokio::test]
+        async fn it_sends_requests_through_a_caching_service() {
+            let service = MockService::with_response(load_data("responses_multi"));
+            let caching = CachingService::new(service.clone());
+            let client = ClaudeClient::with_service(Auth::new("some-api-key"), caching);
+            let request = ClaudeRequest::default().input("write a haiku about ai");
+
+            let first = client.send(&request).await.expect("could not send request");
+            let second = client.send(&request).await.expect("could not send request");
+            assert_eq!(first, second);
+            assert_eq!(service.requests().len(), 1);
+        }
+
+        #[tokio::test]
+        async fn it_checks_its_health() {
+            let client = ClaudeClient::test();
+            assert!(client.health_check().await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn it_can_be_cloned_across_tasks() {
+            let client = ClaudeClient::test();
+            let handles = (0..2).map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let request = ClaudeRequest::default().input("Hello, world");
+                    client.send(&request).await
+                })
+            });
+            for handle in handles.collect::<Vec<_>>() {
+                let response = handle.await.expect("task panicked");
+                assert!(response.is_ok());
+            }
+        }
+
         #[tokio::test]
         async fn it_sends_a_request_and_returns_a_response() {
             let client = ClaudeClient::test();
@@ -326,11 +1434,190 @@ mod tests {
         }
     }
 
+    mod metadata {
+        use super::super::ClaudeClient;
+        use crate::service::ClaudeService;
+        use cogito::prelude::*;
+        use cogito::service::AuthScheme;
+        use hypertyper::prelude::*;
+
+        type Client = ClaudeClient<ClaudeService>;
+
+        #[test]
+        fn it_returns_its_provider_name() {
+            assert_eq!(Client::provider_name(), "Anthropic Claude");
+        }
+
+        #[test]
+        fn it_returns_its_base_url() {
+            assert_eq!(Client::base_url(), "https://api.anthropic.com/v1");
+        }
+
+        #[test]
+        fn it_returns_its_auth_scheme() {
+            assert_eq!(Client::auth_scheme(), AuthScheme::Header("x-api-key"));
+        }
+
+        fn client() -> Client {
+            let auth = Auth::new("some-api-key");
+            let factory = HttpClientFactory::new("cogito-claude", "test");
+            Client::new(auth, factory)
+        }
+
+        #[test]
+        fn it_returns_an_endpoint() {
+            assert_eq!(
+                client().endpoint("messages"),
+                "https://api.anthropic.com/v1/messages"
+            );
+        }
+
+        #[test]
+        fn it_returns_an_endpoint_at_an_overridden_base_url() {
+            let client = client().with_base_url("http://localhost:8080/v1/");
+            assert_eq!(
+                client.endpoint("messages"),
+                "http://localhost:8080/v1/messages"
+            );
+        }
+    }
+
     mod request {
-        use super::super::ClaudeRequest;
+        use super::super::{
+            CONTINUATION_PROMPT, ClaudeCacheControl, ClaudeContentBlock, ClaudeMessageContent,
+            ClaudeRequest, ClaudeResponse, ToolChoice,
+        };
+        use super::load_data;
         use crate::ClaudeModel;
         use crate::client::ClaudeRole;
+        use crate::stream::ClaudeStreamEvent;
         use cogito::prelude::*;
+        use serde_json::json;
+
+        #[test]
+        fn it_builds_a_continuation_request() {
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .max_tokens(16)
+                .input("Write me an epic poem.");
+            let response: ClaudeResponse =
+                serde_json::from_str(&load_data("responses_max_tokens")).unwrap();
+            let request = ClaudeRequest::from_response_continuation(&request, &response);
+
+            assert_eq!(request.model, ClaudeModel::Haiku45);
+            assert_eq!(request.max_tokens, 16);
+            let messages = request
+                .messages
+                .iter()
+                .map(|m| (m.role.clone(), m.content.text().into_owned()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                messages,
+                vec![
+                    (ClaudeRole::User, "Write me an epic poem.".to_string()),
+                    (
+                        ClaudeRole::Assistant,
+                        "Sing, O Muse, of the model that could not stop".to_string()
+                    ),
+                    (ClaudeRole::User, CONTINUATION_PROMPT.to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn it_sets_fields_in_place() {
+            let chained = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .max_tokens(256)
+                .temperature(1.5)
+                .input("Be creative, Claude!")
+                .extra("top_k", 5);
+
+            let mut request = ClaudeRequest::default();
+            request.set_model(ClaudeModel::Haiku45).set_max_tokens(256);
+            request.set_temperature(1.5);
+            request.set_input("Be creative, Claude!");
+            request.set_extra("top_k", 5);
+
+            assert_eq!(
+                serde_json::to_value(&request).unwrap(),
+                serde_json::to_value(&chained).unwrap()
+            );
+        }
+
+        #[test]
+        fn it_treats_instructions_as_input() {
+            let request = ClaudeRequest::default()
+                .instructions("You are a poet.")
+                .input("Write a haiku.");
+            assert!(!request.has_instructions());
+            assert_eq!(request.input_text(), "You are a poet.\nWrite a haiku.");
+        }
+
+        #[test]
+        fn it_clones_a_template_without_changing_it() {
+            let template = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .instructions("You are a poet.")
+                .temperature(0.2);
+            let original = template.clone();
+
+            let creative = template.clone().temperature(1.0).input("Write a haiku.");
+            assert_eq!(template, original);
+            assert_ne!(creative, template);
+            assert_eq!(creative.temperature, Some(1.0));
+            assert_eq!(template.temperature, Some(0.2));
+        }
+
+        #[test]
+        fn it_realizes_a_prompt_with_examples() {
+            let request: ClaudeRequest = Prompt::new()
+                .system("Translate English to French.")
+                .example("cheese", "fromage")
+                .example("bread", "pain")
+                .user("wine")
+                .realize();
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(
+                json["messages"],
+                serde_json::json!([
+                    {"role": "user", "content": "Translate English to French."},
+                    {"role": "user", "content": "cheese"},
+                    {"role": "assistant", "content": "fromage"},
+                    {"role": "user", "content": "bread"},
+                    {"role": "assistant", "content": "pain"},
+                    {"role": "user", "content": "wine"},
+                ])
+            );
+        }
+
+        #[test]
+        fn it_serializes_extra_fields() {
+            let request = ClaudeRequest::default()
+                .input("Be creative, Claude!")
+                .extra("top_k", 5);
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["top_k"], 5);
+            assert_eq!(json["max_tokens"], 1024);
+        }
+
+        #[test]
+        fn it_does_not_change_requests_in_privacy_mode() {
+            let request = ClaudeRequest::default()
+                .user_id("some-user")
+                .input("Keep this secret, Claude!");
+            let expected = serde_json::to_value(&request).unwrap();
+            let actual = serde_json::to_value(request.privacy_mode()).unwrap();
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn it_reports_the_model_used() {
+            let request = ClaudeRequest::default();
+            assert_eq!(request.model_used(), ClaudeModel::default());
+            let request = request.model(ClaudeModel::Haiku45);
+            assert_eq!(request.model_used(), ClaudeModel::Haiku45);
+        }
 
         #[test]
         fn it_serializes() {
@@ -354,6 +1641,468 @@ mod tests {
             )
         }
 
+        #[test]
+        fn it_serializes_image_input() {
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .input("What is in this image?")
+                .input_image_base64("image/png", "iVBORw0KGgo=");
+            let expected = r#"{
+  "model": "claude-haiku-4-5",
+  "max_tokens": 1024,
+  "messages": [
+    {
+      "role": "user",
+      "content": [
+        {
+          "type": "text",
+          "text": "What is in this image?"
+        },
+        {
+          "type": "image",
+          "source": {
+            "type": "base64",
+            "media_type": "image/png",
+            "data": "iVBORw0KGgo="
+          }
+        }
+      ]
+    }
+  ]
+}"#;
+            let actual = serde_json::to_string_pretty(&request).expect("could not serialize json");
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            );
+            assert_eq!(request.input_text(), "What is in this image?");
+        }
+
+        #[test]
+        fn it_prefills_the_response() {
+            let request = ClaudeRequest::default()
+                .input("Write me a haiku.")
+                .prefill("Silent circuits hum \n");
+            let actual = serde_json::to_value(&request).expect("could not serialize json");
+            assert_eq!(
+                actual["messages"][1],
+                serde_json::json!({"role": "assistant", "content": "Silent circuits hum"})
+            );
+            assert_eq!(request.input_text(), "Write me a haiku.");
+        }
+
+        #[test]
+        fn it_continues_a_dropped_stream_by_prefilling_the_text_received() {
+            let request = ClaudeRequest::default().input("Write me a haiku.");
+            let received: Vec<ClaudeStreamEvent> = [
+                r#"{"type": "message_start", "message": {"usage": {"input_tokens": 4}}}"#,
+                r#"{"type": "content_block_delta", "delta": {"type": "text_delta", "text": "Silent "}}"#,
+                r#"{"type": "content_block_delta", "delta": {"type": "text_delta", "text": "circuits "}}"#,
+            ]
+            .iter()
+            .map(|e| serde_json::from_str(e).expect("could not parse event"))
+            .collect();
+            let continuation = ClaudeRequest::from_stream_continuation(&request, &received);
+            assert_eq!(continuation, request.clone().prefill("Silent circuits"));
+            assert_eq!(
+                ClaudeRequest::from_stream_continuation(&request, &received[..1]),
+                request
+            );
+        }
+
+        #[test]
+        fn it_sends_an_image_without_text_in_its_own_message() {
+            let request = ClaudeRequest::default().input_image_base64("image/jpeg", "/9j/4AAQ");
+            let actual = serde_json::to_value(&request).expect("could not serialize json");
+            assert_eq!(
+                actual["messages"],
+                serde_json::json!([{
+                    "role": "user",
+                    "content": [{
+                        "type": "image",
+                        "source": {"type": "base64", "media_type": "image/jpeg", "data": "/9j/4AAQ"},
+                    }],
+                }])
+            );
+        }
+
+        #[test]
+        fn it_serializes_cached_input() {
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .input_cached("Cache me, Claude!")
+                .input("Serialize me, Claude!");
+            let expected = r#"{
+  "model": "claude-haiku-4-5",
+  "max_tokens": 1024,
+  "messages": [
+    {
+      "role": "user",
+      "content": [
+        {
+          "type": "text",
+          "text": "Cache me, Claude!",
+          "cache_control": {
+            "type": "ephemeral"
+          }
+        }
+      ]
+    },
+    {
+      "role": "user",
+      "content": "Serialize me, Claude!"
+    }
+  ]
+}"#;
+            let actual = serde_json::to_string_pretty(&request).expect("could not serialize json");
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            )
+        }
+
+        #[test]
+        fn it_deserializes_cached_input() {
+            let data = r#"{
+  "model": "claude-haiku-4-5",
+  "max_tokens": 1024,
+  "messages": [
+    {
+      "role": "user",
+      "content": [
+        {
+          "type": "text",
+          "text": "Cache me, Claude!",
+          "cache_control": {"type": "ephemeral"}
+        }
+      ]
+    }
+  ]
+}"#;
+            let request: ClaudeRequest =
+                serde_json::from_str(data).expect("could not deserialize json");
+            let message = request.messages.first().expect("could not get message");
+            assert_eq!(
+                message.content,
+                ClaudeMessageContent::Blocks(vec![ClaudeContentBlock::Text {
+                    text: "Cache me, Claude!".to_string(),
+                    cache_control: Some(ClaudeCacheControl::Ephemeral),
+                }])
+            );
+        }
+
+        #[test]
+        fn it_sets_max_tokens_to_the_model_limit() {
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .max_tokens_for_model();
+            assert_eq!(request.max_tokens, 64_000);
+
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Opus41)
+                .max_tokens_for_model();
+            assert_eq!(request.max_tokens, 32_000);
+        }
+
+        #[test]
+        fn it_serializes_max_tokens() {
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .max_tokens(1)
+                .input("Serialize me, Claude!");
+            let expected = r#"{
+  "model": "claude-haiku-4-5",
+  "max_tokens": 1,
+  "messages": [
+    {
+      "role": "user",
+      "content": "Serialize me, Claude!"
+    }
+  ]
+}"#;
+            let actual = serde_json::to_string_pretty(&request).expect("could not serialize json");
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            )
+        }
+
+        #[test]
+        fn it_serializes_stop_sequences() {
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .input("Serialize me, Claude!")
+                .stop_sequences(["Beep", "Boop"]);
+            let expected = r#"{
+  "model": "claude-haiku-4-5",
+  "max_tokens": 1024,
+  "messages": [
+    {
+      "role": "user",
+      "content": "Serialize me, Claude!"
+    }
+  ],
+  "stop_sequences": [
+    "Beep",
+    "Boop"
+  ]
+}"#;
+            let actual = serde_json::to_string_pretty(&request).expect("could not serialize json");
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            )
+        }
+
+        #[test]
+        fn it_serializes_a_temperature() {
+            let request = ClaudeRequest::default().temperature(0.25).input("hi");
+            let actual = serde_json::to_value(&request).expect("could not serialize json");
+            assert_eq!(actual["temperature"], 0.25);
+        }
+
+        #[test]
+        fn it_does_not_serialize_a_temperature_by_default() {
+            let request = ClaudeRequest::default().input("hi");
+            let actual = serde_json::to_value(&request).expect("could not serialize json");
+            assert!(actual.get("temperature").is_none());
+        }
+
+        #[test]
+        fn it_serializes_a_tool_choice() {
+            let test_cases = vec![
+                (ToolChoice::Auto, json!({"type": "auto"})),
+                (ToolChoice::Any, json!({"type": "any"})),
+                (
+                    ToolChoice::tool("get_weather"),
+                    json!({"type": "tool", "name": "get_weather"}),
+                ),
+                (ToolChoice::None, json!({"type": "none"})),
+            ];
+            for (tool_choice, expected) in test_cases {
+                let request = ClaudeRequest::default()
+                    .tool_choice(tool_choice.clone())
+                    .input("hi");
+                let actual = serde_json::to_value(&request).expect("could not serialize json");
+                assert_eq!(actual["tool_choice"], expected, "{tool_choice:?}");
+            }
+        }
+
+        #[test]
+        fn it_does_not_serialize_a_tool_choice_by_default() {
+            let request = ClaudeRequest::default().input("hi");
+            let actual = serde_json::to_value(&request).expect("could not serialize json");
+            assert!(actual.get("tool_choice").is_none());
+        }
+
+        #[test]
+        fn it_clamps_the_temperature_to_claudes_range() {
+            let request = ClaudeRequest::default().temperature(1.5);
+            assert_eq!(request.temperature, Some(1.0));
+
+            let request = ClaudeRequest::default().temperature(-0.5);
+            assert_eq!(request.temperature, Some(0.0));
+        }
+
+        #[test]
+        fn it_serializes_a_user_id() {
+            let request = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .user_id("u123")
+                .input("hi");
+            let expected = r#"{
+  "model": "claude-haiku-4-5",
+  "max_tokens": 1024,
+  "messages": [
+    {
+      "role": "user",
+      "content": "hi"
+    }
+  ],
+  "metadata": {
+    "user_id": "u123"
+  }
+}"#;
+            let actual = serde_json::to_string_pretty(&request).expect("could not serialize json");
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            )
+        }
+
+        #[test]
+        fn it_serializes_without_metadata_by_default() {
+            let request = ClaudeRequest::default().input("hi");
+            let actual = serde_json::to_value(&request).expect("could not serialize json");
+            assert!(actual.get("metadata").is_none());
+        }
+
+        #[test]
+        fn it_deserializes_a_user_id() {
+            let data = r#"{
+  "model": "claude-haiku-4-5",
+  "max_tokens": 1024,
+  "messages": [],
+  "metadata": {
+    "user_id": "u123"
+  }
+}"#;
+            let request: ClaudeRequest = serde_json::from_str(data).expect("could not parse json");
+            let metadata = request.metadata.expect("no metadata");
+            assert_eq!(metadata.user_id, "u123");
+        }
+
+        #[test]
+        fn it_returns_its_input_text() {
+            let request = ClaudeRequest::default().input("Inspect me, Claude!");
+            assert_eq!(request.input_text(), "Inspect me, Claude!");
+        }
+
+        #[test]
+        fn it_joins_the_input_text_of_every_message() {
+            let request = ClaudeRequest::default()
+                .instructions("Please treat this as a test.")
+                .input("Inspect me, Claude!");
+            assert_eq!(
+                request.input_text(),
+                "Please treat this as a test.\nInspect me, Claude!"
+            );
+        }
+
+        #[test]
+        fn it_does_not_truncate_input_that_fits() {
+            let request = ClaudeRequest::default()
+                .input("Short and sweet.")
+                .truncate_input_to(4, &ClaudeModel::Haiku45);
+            assert_eq!(request.input_text(), "Short and sweet.");
+        }
+
+        #[test]
+        fn it_truncates_long_input() {
+            let request = ClaudeRequest::default()
+                .input("This input is a little too long.")
+                .truncate_input_to(4, &ClaudeModel::Haiku45);
+            assert_eq!(request.input_text(), "This input is a ");
+        }
+
+        #[test]
+        fn it_truncates_messages_from_the_front() {
+            let request = ClaudeRequest::default()
+                .input("Please treat this as a test.")
+                .input("Inspect me, Claude!")
+                .truncate_input_to(9, &ClaudeModel::Haiku45);
+            assert_eq!(request.messages.len(), 1);
+            assert_eq!(request.input_text(), "Inspect me, Claude!");
+
+            let request = request
+                .input("Keep me, Claude!")
+                .truncate_input_to(9, &ClaudeModel::Haiku45);
+            assert_eq!(request.messages.len(), 2);
+            assert_eq!(
+                request.input_text(),
+                "Inspect me, Claude!\nKeep me, Claude!"
+            );
+        }
+
+        #[test]
+        fn it_keeps_the_most_recent_turns() {
+            let request = ClaudeRequest::default()
+                .example("Write me a haiku.", "Silent circuits hum")
+                .example("Write another one.", "Tokens drift like snow")
+                .input("Now write a limerick.")
+                .truncate_input_to(17, &ClaudeModel::Haiku45);
+            let text = request
+                .messages
+                .iter()
+                .map(|m| m.content.text())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                text,
+                vec![
+                    "Write another one.",
+                    "Tokens drift like snow",
+                    "Now write a limerick."
+                ]
+            );
+        }
+
+        #[test]
+        fn it_truncates_the_final_user_message_that_does_not_fit() {
+            let request = ClaudeRequest::default()
+                .example("Write me a haiku.", "Silent circuits hum")
+                .input("Now write a limerick.")
+                .truncate_input_to(4, &ClaudeModel::Haiku45);
+            assert_eq!(request.messages.len(), 1);
+            assert_eq!(request.input_text(), "Now write a lime");
+        }
+
+        #[test]
+        fn it_normalizes_messages() {
+            let request = ClaudeRequest::default()
+                .input("  hi \n\n ")
+                .input_cached("Inspect me,   \n\n\n\nClaude!\n")
+                .normalize_input();
+            assert_eq!(request.messages.len(), 2);
+            assert_eq!(
+                request.messages[0].content,
+                ClaudeMessageContent::Text("hi".to_string())
+            );
+            let block = ClaudeContentBlock::Text {
+                text: "Inspect me,\n\nClaude!".to_string(),
+                cache_control: Some(ClaudeCacheControl::Ephemeral),
+            };
+            assert_eq!(
+                request.messages[1].content,
+                ClaudeMessageContent::Blocks(vec![block])
+            );
+        }
+
+        #[test]
+        fn it_keeps_cache_control_when_truncating() {
+            let request = ClaudeRequest::default()
+                .input_cached("This input is a little too long.")
+                .truncate_input_to(4, &ClaudeModel::Haiku45);
+            let block = ClaudeContentBlock::Text {
+                text: "This input is a ".to_string(),
+                cache_control: Some(ClaudeCacheControl::Ephemeral),
+            };
+            assert_eq!(
+                request.messages[0].content,
+                ClaudeMessageContent::Blocks(vec![block])
+            );
+        }
+
+        #[test]
+        fn it_has_a_stable_cache_key() {
+            let a = ClaudeRequest::default()
+                .model(ClaudeModel::Haiku45)
+                .max_tokens(256)
+                .input("Write me a haiku.");
+            let b = ClaudeRequest::default()
+                .input("Write me a haiku.")
+                .max_tokens(256)
+                .model(ClaudeModel::Haiku45);
+            assert_eq!(a.cache_key(), b.cache_key());
+            assert_ne!(a.cache_key(), a.max_tokens(512).cache_key());
+        }
+
+        #[test]
+        fn it_appends_input_as_messages() {
+            let request = ClaudeRequest::default()
+                .input_cached("Write me a haiku.")
+                .append_input("Make it about robots.")
+                .append_input("Make it rhyme.");
+            assert_eq!(request.messages.len(), 3);
+            assert!(matches!(
+                request.messages[0].content,
+                ClaudeMessageContent::Blocks(_)
+            ));
+            assert_eq!(
+                request.input_text(),
+                "Write me a haiku.\nMake it about robots.\nMake it rhyme."
+            );
+        }
+
         #[test]
         fn it_deserializes() {
             let data = r#"{
@@ -377,7 +2126,7 @@ mod tests {
                 .nth(0)
                 .expect("could not get message");
             assert_eq!(message.role, ClaudeRole::User);
-            assert_eq!(message.content, "Serialize me, Claude!");
+            assert_eq!(message.content.text(), "Serialize me, Claude!");
         }
     }
 
@@ -386,18 +2135,54 @@ mod tests {
         use super::load_data;
         use crate::client::ClaudeRole;
         use cogito::prelude::*;
+        use cogito::testing::assert_response_result;
 
         fn load_response(filename: &str) -> ClaudeResponse {
             let data = load_data(filename);
             serde_json::from_str(&data).expect("could not parse json")
         }
 
+        #[test]
+        fn it_converts_from_a_json_value() {
+            let value: serde_json::Value = serde_json::from_str(&load_data("responses")).unwrap();
+            let response = ClaudeResponse::try_from(value).expect("could not convert value");
+            assert_eq!(response, load_response("responses"));
+        }
+
+        #[test]
+        fn it_does_not_convert_a_json_value_that_is_not_a_response() {
+            let value = serde_json::json!({"type": "error", "error": {"type": "overloaded_error"}});
+            let err = ClaudeResponse::try_from(value).unwrap_err();
+            assert!(err.to_string().contains("missing field"), "{err}");
+        }
+
+        #[test]
+        fn it_equals_itself_after_a_round_trip() {
+            let response = load_response("responses");
+            let serialized = serde_json::to_string(&response).unwrap();
+            let deserialized: ClaudeResponse = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(deserialized, response);
+        }
+
         #[test]
         fn it_returns_an_id() {
             let resp = load_response("responses");
             assert_eq!(resp.id, "msg_01UiL2duVWmZVLJf83nn6gLQ");
         }
 
+        #[test]
+        fn it_reports_output_tokens() {
+            let resp = load_response("responses");
+            assert_eq!(resp.output_tokens(), Some(12));
+            assert_eq!(resp.usage_matches_output(0.5), Some(true));
+        }
+
+        #[test]
+        fn it_returns_its_model() {
+            let resp = load_response("responses");
+            assert_eq!(resp.model(), "claude-sonnet-4-5-20250929");
+        }
+
         #[test]
         fn it_returns_a_response_type() {
             let resp = load_response("responses");
@@ -410,6 +2195,15 @@ mod tests {
             assert_eq!(resp.role, ClaudeRole::Assistant);
         }
 
+        #[test]
+        fn it_reports_usage() {
+            let resp = load_response("responses");
+            let usage = resp.usage().expect("no usage");
+            assert_eq!(usage.input_tokens, 10);
+            assert_eq!(usage.output_tokens, 12);
+            assert_eq!(usage.cached_input_tokens, 0);
+        }
+
         #[test]
         fn it_returns_usage() {
             let resp = load_response("responses");
@@ -423,19 +2217,75 @@ mod tests {
         }
 
         #[test]
-        fn it_concatenates_a_single_response() {
+        fn it_exposes_unmodeled_fields() {
             let resp = load_response("responses");
-            let text = resp.result();
-            assert_eq!(text, "Hello! How can I help you today?");
+            let raw = resp.raw().expect("no raw response");
+            assert_eq!(raw["stop_reason"], "end_turn");
         }
 
         #[test]
-        fn it_concatenates_many_responses() {
-            let resp = load_response("responses_multi");
-            let text = resp.result();
+        fn it_returns_no_stop_sequence_when_none_was_matched() {
+            let resp = load_response("responses");
+            assert_eq!(resp.stop_sequence(), None);
+        }
+
+        #[test]
+        fn it_returns_its_stop_reason() {
+            let resp = load_response("responses");
+            assert_eq!(resp.stop_reason(), Some("end_turn"));
+            assert!(!resp.hit_max_tokens());
+        }
+
+        #[test]
+        fn it_reports_hitting_max_tokens() {
+            let resp = load_response("responses_max_tokens");
+            assert_eq!(resp.stop_reason(), Some("max_tokens"));
+            assert!(resp.hit_max_tokens());
+        }
+
+        #[cfg(all(debug_assertions, feature = "tracing"))]
+        #[test]
+        fn it_warns_when_the_result_of_a_truncated_response_is_used() {
+            use cogito::testing::SpanRecorder;
+
+            let recorder = SpanRecorder::default();
+            let _guard = tracing::subscriber::set_default(recorder.clone());
+            load_response("responses").result();
+            assert_eq!(recorder.field("message"), None);
+            load_response("responses_max_tokens").result();
             assert_eq!(
-                text,
-                "Hello! How can I help you today?\nI am a friendly robot.\nBeep beep!"
+                recorder.field("message").as_deref(),
+                Some("result of a Claude response that hit max_tokens was used")
+            );
+        }
+
+        #[test]
+        fn it_returns_the_matched_stop_sequence() {
+            let resp = load_response("responses_stop_sequence");
+            assert_eq!(resp.stop_sequence(), Some("Beep"));
+        }
+
+        #[test]
+        fn it_concatenates_a_single_response() {
+            let data = load_data("responses");
+            assert_response_result::<ClaudeResponse>(&data, "Hello! How can I help you today?");
+        }
+
+        #[test]
+        fn it_concatenates_many_responses() {
+            let data = load_data("responses_multi");
+            assert_response_result::<ClaudeResponse>(
+                &data,
+                "Hello! How can I help you today?\nI am a friendly robot.\nBeep beep!",
+            );
+        }
+
+        #[test]
+        fn it_skips_content_without_text() {
+            let data = load_data("responses_citations");
+            assert_response_result::<ClaudeResponse>(
+                &data,
+                "I'll search for that.\nRobots write haiku about silent circuits.",
             );
         }
     }
@@ -449,7 +2299,21 @@ mod tests {
             let content: ClaudeContent =
                 serde_json::from_str(json_str).expect("could not parse json");
             assert_eq!(content.content_type, "text");
-            assert_eq!(content.text, "Hello! How can I help you today?");
+            assert_eq!(content.text(), Some("Hello! How can I help you today?"));
+        }
+
+        #[test]
+        fn it_deserializes_a_block_without_text() {
+            let json_str = r#"{
+                "type": "server_tool_use",
+                "id": "srvtoolu_01WYG3ziw53XMcoyKL4XcZmE",
+                "name": "web_search",
+                "input": {"query": "robot haiku"}
+            }"#;
+            let content: ClaudeContent =
+                serde_json::from_str(json_str).expect("could not parse json");
+            assert_eq!(content.content_type, "server_tool_use");
+            assert_eq!(content.text(), None);
         }
     }
 
@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Errors reported by the Claude API.
+//!
+//! When a request fails, the Claude API responds with an error object
+//! instead of a message:
+//!
+//! ```json
+//! {
+//!   "type": "error",
+//!   "error": {
+//!     "type": "overloaded_error",
+//!     "message": "Overloaded"
+//!   }
+//! }
+//! ```
+//!
+//! [`ClaudeApiError`] represents these error objects, and is converted to
+//! an [`AiError`] when it is returned by a [client](crate::client).
+
+use cogito::client::AiError;
+use cogito::service::{RawResponse, retry_after};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// An error reported by the Claude API.
+///
+/// Claude API errors are converted to the closest [`AiError`]: an
+/// `invalid_request_error` is a [bad request](AiError::BadRequest), an
+/// `authentication_error` is [unauthorized](AiError::Unauthorized), a
+/// `permission_error` is [forbidden](AiError::Forbidden), and a
+/// `rate_limit_error` is [rate limited](AiError::RateLimited). Any other
+/// type of error, such as an `overloaded_error`, is reported as an
+/// [`AiError::Api`] error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaudeApiError {
+    status: StatusCode,
+    error_type: String,
+    message: String,
+    retry_after: Option<Duration>,
+}
+
+impl ClaudeApiError {
+    /// Reads the error object from a raw response, if its body is one.
+    pub fn from_raw_response(response: &RawResponse) -> Option<Self> {
+        let (status, headers, body) = response;
+        let body = serde_json::from_str::<ClaudeErrorBody>(body).ok()?;
+        if body.body_type != "error" {
+            return None;
+        }
+        Some(Self {
+            status: *status,
+            error_type: body.error.error_type,
+            message: body.error.message,
+            retry_after: retry_after(headers),
+        })
+    }
+
+    /// Creates an error reported partway through a streamed response.
+    ///
+    /// The response's status was already sent as `200 OK` before the error
+    /// occurred, so the status is the one Claude uses for the same type of
+    /// error when it is reported in place of a response.
+    pub(crate) fn from_stream(error_type: String, message: String) -> Self {
+        let status = match error_type.as_str() {
+            "invalid_request_error" => StatusCode::BAD_REQUEST,
+            "authentication_error" => StatusCode::UNAUTHORIZED,
+            "permission_error" => StatusCode::FORBIDDEN,
+            "not_found_error" => StatusCode::NOT_FOUND,
+            "request_too_large" => StatusCode::PAYLOAD_TOO_LARGE,
+            "rate_limit_error" => StatusCode::TOO_MANY_REQUESTS,
+            "overloaded_error" => StatusCode::from_u16(529).expect("529 is a valid status"),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self {
+            status,
+            error_type,
+            message,
+            retry_after: None,
+        }
+    }
+
+    /// The status of the response that reported the error.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The type of error, such as `overloaded_error`.
+    pub fn error_type(&self) -> &str {
+        &self.error_type
+    }
+
+    /// Claude's description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<ClaudeApiError> for AiError {
+    fn from(error: ClaudeApiError) -> Self {
+        match error.error_type.as_str() {
+            "invalid_request_error" => AiError::BadRequest(error.message),
+            "authentication_error" => AiError::Unauthorized(error.message),
+            "permission_error" => AiError::Forbidden(error.message),
+            "rate_limit_error" => AiError::RateLimited {
+                retry_after: error.retry_after,
+                body_snippet: error.message,
+            },
+            _ => AiError::Api {
+                status: error.status,
+                kind: error.error_type,
+                message: error.message,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeErrorBody {
+    #[serde(rename = "type")]
+    body_type: String,
+
+    error: ClaudeErrorDetails,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeErrorDetails {
+    #[serde(rename = "type")]
+    error_type: String,
+
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    mod claude_api_error {
+        use super::super::ClaudeApiError;
+        use cogito::client::AiError;
+        use reqwest::StatusCode;
+        use reqwest::header::{self, HeaderMap};
+        use std::fs;
+        use std::time::Duration;
+
+        fn load_data(filename: &str) -> String {
+            fs::read_to_string(format!("tests/data/{filename}.json"))
+                .expect("could not load test data")
+        }
+
+        fn error(status: u16, error_type: &str, message: &str) -> ClaudeApiError {
+            let body = format!(
+                r#"{{"type": "error", "error": {{"type": "{error_type}", "message": "{message}"}}}}"#
+            );
+            let status = StatusCode::from_u16(status).unwrap();
+            ClaudeApiError::from_raw_response(&(status, HeaderMap::new(), body))
+                .expect("could not read error")
+        }
+
+        #[test]
+        fn it_reads_an_error_response() {
+            let status = StatusCode::from_u16(529).unwrap();
+            let response = (status, HeaderMap::new(), load_data("errors_overloaded"));
+            let error = ClaudeApiError::from_raw_response(&response).expect("no error");
+            assert_eq!(error.status(), status);
+            assert_eq!(error.error_type(), "overloaded_error");
+            assert_eq!(error.message(), "Overloaded");
+        }
+
+        #[test]
+        fn it_does_not_read_a_message_response() {
+            let response = (StatusCode::OK, HeaderMap::new(), load_data("responses"));
+            assert_eq!(ClaudeApiError::from_raw_response(&response), None);
+        }
+
+        #[test]
+        fn it_does_not_read_a_response_that_is_not_json() {
+            let response = (
+                StatusCode::BAD_GATEWAY,
+                HeaderMap::new(),
+                "Bad Gateway".into(),
+            );
+            assert_eq!(ClaudeApiError::from_raw_response(&response), None);
+        }
+
+        #[test]
+        fn it_converts_an_overloaded_error() {
+            let error = AiError::from(error(529, "overloaded_error", "Overloaded"));
+            assert!(error.is_retryable());
+            match error {
+                AiError::Api {
+                    status,
+                    kind,
+                    message,
+                } => {
+                    assert_eq!(status.as_u16(), 529);
+                    assert_eq!(kind, "overloaded_error");
+                    assert_eq!(message, "Overloaded");
+                }
+                other => panic!("expected an API error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn it_converts_an_invalid_request_error() {
+            let error = AiError::from(error(400, "invalid_request_error", "max_tokens: required"));
+            match error {
+                AiError::BadRequest(message) => assert_eq!(message, "max_tokens: required"),
+                other => panic!("expected a bad request error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn it_converts_an_authentication_error() {
+            let error = AiError::from(error(401, "authentication_error", "invalid x-api-key"));
+            match error {
+                AiError::Unauthorized(message) => assert_eq!(message, "invalid x-api-key"),
+                other => panic!("expected an unauthorized error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn it_converts_a_permission_error() {
+            let error = AiError::from(error(403, "permission_error", "no access to model"));
+            match error {
+                AiError::Forbidden(message) => assert_eq!(message, "no access to model"),
+                other => panic!("expected a forbidden error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn it_converts_a_rate_limit_error() {
+            let body = r#"{"type": "error", "error": {"type": "rate_limit_error", "message": "Slow down"}}"#;
+            let mut headers = HeaderMap::new();
+            headers.insert(header::RETRY_AFTER, "20".parse().unwrap());
+            let response = (StatusCode::TOO_MANY_REQUESTS, headers, body.to_string());
+            let error = ClaudeApiError::from_raw_response(&response).expect("no error");
+            match AiError::from(error) {
+                AiError::RateLimited {
+                    retry_after,
+                    body_snippet,
+                } => {
+                    assert_eq!(retry_after, Some(Duration::from_secs(20)));
+                    assert_eq!(body_snippet, "Slow down");
+                }
+                other => panic!("expected a rate limit error, got {other:?}"),
+            }
+        }
+    }
+}
@@ -43,9 +43,12 @@
 //! [pricing documentation]: https://platform.claude.com/docs/en/about-claude/pricing
 
 pub mod client;
+pub mod error;
 pub mod service;
+pub mod stream;
 
 use cogito::AiModel;
+use cogito::client::Pricing;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -68,6 +71,19 @@ use std::fmt;
 /// `cogito_claude` module documentation for more details, or visit Anthropic's
 /// [pricing] documentation for the latest prices.
 ///
+/// # Serialization
+///
+/// Models are serialized using the model aliases used by the Claude API,
+/// such as `claude-sonnet-4-5`, which is also how they are
+/// [displayed](fmt::Display). Common shorthands, such as `sonnet-4.5`, are
+/// accepted when deserializing a model, so configuration files can be a
+/// little more forgiving. The bare family names `sonnet`, `haiku`, and
+/// `opus` refer to the latest model in each family.
+///
+/// Any other model whose name begins with `claude-` is deserialized as
+/// [`ClaudeModel::Other`], so new models can be named in configuration
+/// files before they are added to this crate.
+///
 /// [cost breakdown]: self#Cost
 /// [default model]: ClaudeModel::default()
 /// [model overview]: https://platform.claude.com/docs/en/about-claude/models/overview
@@ -76,35 +92,115 @@ use std::fmt;
 /// [Opus 4.1]: ClaudeModel::Opus41
 /// [Opus 4.5]: ClaudeModel::Opus45
 /// [Sonnet 4.5]: ClaudeModel::Sonnet45
-#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 pub enum ClaudeModel {
     /// Anthropic's flagship model.
     ///
     /// According to Anthropic, this model is "our smartest model for
     /// complex agents and coding".
     #[default]
-    #[serde(rename = "claude-sonnet-4-5")]
+    #[serde(
+        rename = "claude-sonnet-4-5",
+        alias = "claude-sonnet-4.5",
+        alias = "sonnet-4-5",
+        alias = "sonnet-4.5",
+        alias = "sonnet"
+    )]
     Sonnet45,
 
     /// Anthropic's fastest and cheapest model.
     ///
     /// According to Anthropic, this model is "our fastest model with
     /// near-frontier intelligence".
-    #[serde(rename = "claude-haiku-4-5")]
+    #[serde(
+        rename = "claude-haiku-4-5",
+        alias = "claude-haiku-4.5",
+        alias = "haiku-4-5",
+        alias = "haiku-4.5",
+        alias = "haiku"
+    )]
     Haiku45,
 
     /// A premium model.
     ///
     /// This model "combines maximum intelligence with practical performance".
-    #[serde(rename = "claude-opus-4-5")]
+    #[serde(
+        rename = "claude-opus-4-5",
+        alias = "claude-opus-4.5",
+        alias = "opus-4-5",
+        alias = "opus-4.5",
+        alias = "opus"
+    )]
     Opus45,
 
     /// An expensive, premium model.
     ///
     /// According to Anthropic, an "exceptional model for specialize
     /// reasoning tasks".
-    #[serde(rename = "claude-opus-4-1")]
+    #[serde(
+        rename = "claude-opus-4-1",
+        alias = "claude-opus-4.1",
+        alias = "opus-4-1",
+        alias = "opus-4.1"
+    )]
     Opus41,
+
+    /// Any other Claude model, such as one that was released after this
+    /// version of the crate, named by its Claude API model ID.
+    ///
+    /// The [`AiModel`] classes, such as [`AiModel::flagship()`], always
+    /// refer to one of the known models.
+    #[serde(untagged, deserialize_with = "deserialize_other")]
+    Other(String),
+}
+
+/// Deserializes the name of an unknown model, as long as it is a Claude
+/// model.
+fn deserialize_other<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let name = String::deserialize(deserializer)?;
+    if name.starts_with("claude-") {
+        Ok(name)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "unknown Claude model: {name}"
+        )))
+    }
+}
+
+impl ClaudeModel {
+    /// The family the model belongs to: `"Sonnet"`, `"Haiku"`, or `"Opus"`.
+    ///
+    /// This is useful for grouping models, such as in a settings menu.
+    /// The family of an [other](ClaudeModel::Other) model is guessed from
+    /// its name, and is `"Claude"` if it cannot be guessed.
+    pub fn family(&self) -> &'static str {
+        match self {
+            ClaudeModel::Sonnet45 => "Sonnet",
+            ClaudeModel::Haiku45 => "Haiku",
+            ClaudeModel::Opus45 | ClaudeModel::Opus41 => "Opus",
+            ClaudeModel::Other(name) => ["Sonnet", "Haiku", "Opus"]
+                .into_iter()
+                .find(|family| name.contains(&family.to_lowercase()))
+                .unwrap_or("Claude"),
+        }
+    }
+
+    /// The most tokens the model can generate in a single response, as
+    /// documented by Anthropic.
+    ///
+    /// The limit of an [other](ClaudeModel::Other) model is unknown, so
+    /// a conservative 4,096 tokens, which every Claude model supports, is
+    /// assumed.
+    pub fn max_output_tokens(&self) -> u32 {
+        match self {
+            ClaudeModel::Sonnet45 | ClaudeModel::Haiku45 | ClaudeModel::Opus45 => 64_000,
+            ClaudeModel::Opus41 => 32_000,
+            ClaudeModel::Other(_) => 4_096,
+        }
+    }
 }
 
 impl AiModel for ClaudeModel {
@@ -127,6 +223,47 @@ impl AiModel for ClaudeModel {
     fn fastest() -> Self {
         ClaudeModel::Haiku45
     }
+
+    /// The model's price, as listed in the [cost breakdown](crate#Cost).
+    ///
+    /// Cached input is read from Claude's prompt cache at a tenth of the
+    /// input price. The price of [other](ClaudeModel::Other) models is
+    /// unknown.
+    fn pricing(&self) -> Option<Pricing> {
+        let (input, output) = match self {
+            ClaudeModel::Sonnet45 => (3.0, 15.0),
+            ClaudeModel::Haiku45 => (1.0, 5.0),
+            ClaudeModel::Opus45 => (5.0, 25.0),
+            ClaudeModel::Opus41 => (15.0, 75.0),
+            ClaudeModel::Other(_) => return None,
+        };
+        Some(Pricing {
+            input,
+            cached_input: input / 10.0,
+            output,
+        })
+    }
+
+    /// The model's training data cutoff. The cutoff of
+    /// [other](ClaudeModel::Other) models is unknown.
+    fn knowledge_cutoff(&self) -> Option<&'static str> {
+        match self {
+            ClaudeModel::Sonnet45 => Some("2025-01"),
+            ClaudeModel::Haiku45 => Some("2025-02"),
+            ClaudeModel::Opus45 => Some("2025-05"),
+            ClaudeModel::Opus41 => Some("2025-01"),
+            ClaudeModel::Other(_) => None,
+        }
+    }
+
+    /// Every Claude model has a 200K-token context window. The context
+    /// window of [other](ClaudeModel::Other) models is unknown.
+    fn context_window(&self) -> Option<usize> {
+        match self {
+            ClaudeModel::Other(_) => None,
+            _ => Some(200_000),
+        }
+    }
 }
 
 impl fmt::Display for ClaudeModel {
@@ -150,8 +287,10 @@ impl fmt::Display for ClaudeModel {
 /// in your project.
 pub mod prelude {
     pub use crate::ClaudeModel;
-    pub use crate::client::{ClaudeClient, ClaudeRequest, ClaudeResponse};
+    pub use crate::client::{ClaudeClient, ClaudeRequest, ClaudeResponse, ToolChoice};
+    pub use crate::error::ClaudeApiError;
     pub use crate::service::ClaudeService;
+    pub use crate::stream::ClaudeStreamEvent;
     pub use cogito::AiModel;
     pub use cogito::client::{AiClient, AiRequest, AiResponse};
 }
@@ -173,4 +312,133 @@ mod tests {
             assert_eq!(model.to_string(), descriptor, "ClaudeModel::{:?}", model);
         }
     }
+
+    #[test]
+    fn it_returns_the_model_for_a_class() {
+        use cogito::ModelClass;
+
+        assert_eq!(
+            ClaudeModel::for_class(ModelClass::Flagship),
+            ClaudeModel::Sonnet45
+        );
+        assert_eq!(
+            ClaudeModel::for_class(ModelClass::Cheapest),
+            ClaudeModel::Haiku45
+        );
+        assert_eq!(ClaudeModel::Haiku45.class_of(), Some(ModelClass::Cheapest));
+        assert_eq!(ClaudeModel::Opus41.class_of(), None);
+    }
+
+    #[test]
+    fn it_deserializes_shorthand_descriptors() {
+        let test_cases = vec![
+            ("claude-sonnet-4-5", ClaudeModel::Sonnet45),
+            ("claude-sonnet-4.5", ClaudeModel::Sonnet45),
+            ("sonnet-4-5", ClaudeModel::Sonnet45),
+            ("sonnet-4.5", ClaudeModel::Sonnet45),
+            ("sonnet", ClaudeModel::Sonnet45),
+            ("claude-haiku-4.5", ClaudeModel::Haiku45),
+            ("haiku-4.5", ClaudeModel::Haiku45),
+            ("haiku", ClaudeModel::Haiku45),
+            ("claude-opus-4.5", ClaudeModel::Opus45),
+            ("opus-4.5", ClaudeModel::Opus45),
+            ("opus", ClaudeModel::Opus45),
+            ("claude-opus-4.1", ClaudeModel::Opus41),
+            ("opus-4.1", ClaudeModel::Opus41),
+        ];
+
+        for (descriptor, model) in test_cases {
+            let actual: ClaudeModel = serde_json::from_str(&format!("\"{descriptor}\""))
+                .unwrap_or_else(|_| panic!("could not deserialize {descriptor}"));
+            assert_eq!(actual, model, "{descriptor}");
+        }
+    }
+
+    #[test]
+    fn it_groups_models_by_family() {
+        let test_cases = vec![
+            (ClaudeModel::Sonnet45, "Sonnet"),
+            (ClaudeModel::Haiku45, "Haiku"),
+            (ClaudeModel::Opus45, "Opus"),
+            (ClaudeModel::Opus41, "Opus"),
+            (ClaudeModel::Other("claude-opus-5".to_string()), "Opus"),
+            (ClaudeModel::Other("claude-poet-1".to_string()), "Claude"),
+        ];
+
+        for (model, family) in test_cases {
+            assert_eq!(model.family(), family, "ClaudeModel::{:?}", model);
+        }
+    }
+
+    #[test]
+    fn it_prices_models() {
+        let pricing = ClaudeModel::Sonnet45.pricing().unwrap();
+        assert_eq!(pricing.input, 3.0);
+        assert_eq!(pricing.cached_input, 0.3);
+        assert_eq!(pricing.output, 15.0);
+
+        assert_eq!(
+            ClaudeModel::Other("claude-poet-1".to_string()).pricing(),
+            None
+        );
+    }
+
+    #[test]
+    fn it_returns_knowledge_cutoffs() {
+        assert_eq!(ClaudeModel::Sonnet45.knowledge_cutoff(), Some("2025-01"));
+        assert_eq!(ClaudeModel::Opus45.knowledge_cutoff(), Some("2025-05"));
+        assert_eq!(
+            ClaudeModel::Other("claude-poet-1".to_string()).knowledge_cutoff(),
+            None
+        );
+    }
+
+    #[test]
+    fn it_returns_context_windows() {
+        assert_eq!(ClaudeModel::Haiku45.context_window(), Some(200_000));
+        assert_eq!(
+            ClaudeModel::Other("claude-poet-1".to_string()).context_window(),
+            None
+        );
+    }
+
+    #[test]
+    fn it_reports_maximum_output_tokens() {
+        let test_cases = vec![
+            (ClaudeModel::Sonnet45, 64_000),
+            (ClaudeModel::Haiku45, 64_000),
+            (ClaudeModel::Opus45, 64_000),
+            (ClaudeModel::Opus41, 32_000),
+            (ClaudeModel::Other("claude-poet-1".to_string()), 4_096),
+        ];
+
+        for (model, max_tokens) in test_cases {
+            assert_eq!(
+                model.max_output_tokens(),
+                max_tokens,
+                "ClaudeModel::{:?}",
+                model
+            );
+        }
+    }
+
+    #[test]
+    fn it_deserializes_an_unknown_claude_model() {
+        let model: ClaudeModel = serde_json::from_str("\"claude-opus-5\"").unwrap();
+        assert_eq!(model, ClaudeModel::Other("claude-opus-5".to_string()));
+        assert_eq!(serde_json::to_string(&model).unwrap(), "\"claude-opus-5\"");
+        assert_eq!(model.to_string(), "claude-opus-5");
+    }
+
+    #[test]
+    fn it_does_not_deserialize_an_unknown_model_from_another_provider() {
+        let result = serde_json::from_str::<ClaudeModel>("\"gpt-5\"");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_displays_the_canonical_descriptor_for_a_shorthand() {
+        let model: ClaudeModel = serde_json::from_str("\"sonnet\"").unwrap();
+        assert_eq!(model.to_string(), "claude-sonnet-4-5");
+    }
 }
@@ -19,11 +19,18 @@
 //! [`hypertyper.service`]: https://docs.rs/hypertyper/latest/hypertyper/service/index.html
 //! [`Service`]: https://docs.rs/cogito/latest/cogito/service/struct.Service.html
 
+use crate::error::ClaudeApiError;
+use cogito::client::AiResult;
+use cogito::rate_limit::{RateLimitHeaders, RateLimitStatus};
+use cogito::service::{
+    AiPost, AuthScheme, HttpGet, RawResponse, decode_raw_response, read_raw_response,
+};
+use cogito::stream::{EVENT_STREAM_CONTENT_TYPE, EventStream};
 use hypertyper::prelude::*;
 use log::debug;
-use reqwest::header;
+use reqwest::{RequestBuilder, header};
 use serde::Serialize;
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, IgnoredAny};
 
 /// Communicates with the Claude API over HTTP.
 ///
@@ -31,19 +38,147 @@ use serde::de::DeserializeOwned;
 /// less just wraps a Reqwest client, making it easier to swap out the
 /// service for a deterministic service when writing tests. Most Claude API
 /// clients should use this `Service` by default.
-#[derive(Debug)]
+///
+/// Cloning a service is cheap: clones share the same connection pool.
+#[derive(Clone, Debug)]
 pub struct ClaudeService {
     client: HttpClient,
+    version: String,
 }
 
 impl ClaudeService {
-    const ANTHROPIC_VERSION: &'static str = "2023-06-01";
+    /// The version of the Claude API that services use by default.
+    pub const ANTHROPIC_VERSION: &'static str = "2023-06-01";
+
+    /// Claude expects API keys to be sent in an `x-api-key` header.
+    pub(crate) const AUTH_SCHEME: AuthScheme = AuthScheme::Header("x-api-key");
+
+    /// The headers Claude uses to report the remaining rate-limit budget.
+    pub const RATE_LIMIT_HEADERS: RateLimitHeaders = RateLimitHeaders {
+        remaining_requests: "anthropic-ratelimit-requests-remaining",
+        remaining_tokens: "anthropic-ratelimit-tokens-remaining",
+        reset_requests: "anthropic-ratelimit-requests-reset",
+        reset_tokens: "anthropic-ratelimit-tokens-reset",
+    };
 
     /// Creates a new HTTP service that communicates using clients from the
     /// given factory.
     pub fn new(factory: HttpClientFactory) -> Self {
+        Self::with_version(factory, Self::ANTHROPIC_VERSION)
+    }
+
+    /// Creates a new HTTP service that requests the given version of the
+    /// Claude API, instead of the [default](ClaudeService::ANTHROPIC_VERSION).
+    ///
+    /// The version is sent in the `anthropic-version` header of every
+    /// request. Consult the [versioning] documentation for the versions the
+    /// Claude API accepts.
+    ///
+    /// [versioning]: https://platform.claude.com/docs/en/api/versioning
+    pub fn with_version(factory: HttpClientFactory, version: &str) -> Self {
         let client = factory.create();
-        Self { client }
+        let version = version.to_string();
+        Self { client, version }
+    }
+
+    /// The version of the Claude API the service requests.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn request<U, D>(&self, uri: U, auth: &Auth, data: &D) -> RequestBuilder
+    where
+        U: IntoUrl,
+        D: Serialize,
+    {
+        let request = self
+            .client
+            .post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("anthropic-version", &self.version)
+            .json(data);
+        Self::AUTH_SCHEME.apply(request, auth)
+    }
+
+    fn get_request<U: IntoUrl>(&self, uri: U, auth: &Auth) -> RequestBuilder {
+        let request = self
+            .client
+            .get(uri)
+            .header("anthropic-version", &self.version);
+        Self::AUTH_SCHEME.apply(request, auth)
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and return the raw response.
+    ///
+    /// Unlike [`post()`](HttpPost::post), the response's status and
+    /// headers are returned along with its body, which is not decoded, and
+    /// unsuccessful responses are not reported as errors. This is useful
+    /// for inspecting Claude's rate-limit headers, such as
+    /// `anthropic-ratelimit-requests-remaining`, to pace requests.
+    pub async fn post_raw<U, D>(&self, uri: U, auth: &Auth, data: &D) -> HttpResult<RawResponse>
+    where
+        U: IntoUrl,
+        D: Serialize,
+    {
+        let response = self.request(uri, auth, data).send().await?;
+        read_raw_response(response).await
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and return the decoded response along with
+    /// the rate-limit budget reported by its headers.
+    ///
+    /// Unsuccessful responses are reported as errors in the same way as
+    /// [`try_post()`](AiPost::try_post).
+    pub async fn try_post_with_rate_limits<U, D, R>(
+        &self,
+        uri: U,
+        auth: &Auth,
+        data: &D,
+    ) -> AiResult<(R, RateLimitStatus)>
+    where
+        U: IntoUrl,
+        D: Serialize,
+        R: DeserializeOwned,
+    {
+        let response = self.post_raw(uri, auth, data).await?;
+        let rate_limits = RateLimitStatus::from_headers(&response.1, &Self::RATE_LIMIT_HEADERS);
+        let object = decode(&response)?;
+        Ok((object, rate_limits))
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and stream the response.
+    ///
+    /// Claude streams responses as server-sent events, each of which is
+    /// deserialized to the type specified by the `R` type parameter as soon
+    /// as it is received. Unsuccessful responses are reported as errors in
+    /// the same way as [`try_post()`](AiPost::try_post).
+    pub async fn post_stream<U, D, R>(
+        &self,
+        uri: U,
+        auth: &Auth,
+        data: &D,
+    ) -> AiResult<EventStream<R>>
+    where
+        U: IntoUrl,
+        D: Serialize,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .request(uri, auth, data)
+            .header(header::ACCEPT, EVENT_STREAM_CONTENT_TYPE)
+            .send()
+            .await
+            .map_err(HttpError::from)?;
+        let status = response.status();
+        if !status.is_client_error() && !status.is_server_error() {
+            return Ok(EventStream::new(response));
+        }
+        let response = read_raw_response(response).await?;
+        decode::<IgnoredAny>(&response)?;
+        unreachable!("an unsuccessful response is always decoded as an error")
     }
 }
 
@@ -54,15 +189,7 @@ impl HttpPost for ClaudeService {
         D: Serialize + Sync,
         R: DeserializeOwned,
     {
-        let response = self
-            .client
-            .post(uri)
-            .header(header::CONTENT_TYPE, "application/json")
-            .header("anthropic-version", Self::ANTHROPIC_VERSION)
-            .header("x-api-key", auth.api_key())
-            .json(data)
-            .send()
-            .await?;
+        let response = self.request(uri, auth, data).send().await?;
 
         debug!("HTTP response is:\n{response:?}");
 
@@ -70,3 +197,75 @@ impl HttpPost for ClaudeService {
         Ok(json_object)
     }
 }
+
+impl HttpGet for ClaudeService {
+    async fn get<U, R>(&self, uri: U, auth: &Auth) -> HttpResult<R>
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned,
+    {
+        let response = self.get_request(uri, auth).send().await?;
+
+        debug!("HTTP response is:\n{response:?}");
+
+        let json_object = response.json::<R>().await?;
+        Ok(json_object)
+    }
+}
+
+impl AiPost for ClaudeService {
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body.
+    ///
+    /// Unlike [`post()`](HttpPost::post), if Claude responds with an
+    /// [error object](ClaudeApiError), it is returned as an error, and if
+    /// the response cannot otherwise be deserialized, the returned error
+    /// includes the beginning of the raw response body.
+    async fn try_post<U, D, R>(&self, uri: U, auth: &Auth, data: &D) -> AiResult<R>
+    where
+        U: IntoUrl + Send,
+        D: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .request(uri, auth, data)
+            .send()
+            .await
+            .map_err(HttpError::from)?;
+
+        debug!("HTTP response is:\n{response:?}");
+
+        let response = read_raw_response(response).await?;
+        decode(&response)
+    }
+
+    /// Send a GET request to the `uri`.
+    ///
+    /// Errors are reported in the same way as
+    /// [`try_post()`](AiPost::try_post).
+    async fn try_get<U, R>(&self, uri: U, auth: &Auth) -> AiResult<R>
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .get_request(uri, auth)
+            .send()
+            .await
+            .map_err(HttpError::from)?;
+
+        debug!("HTTP response is:\n{response:?}");
+
+        let response = read_raw_response(response).await?;
+        decode(&response)
+    }
+}
+
+/// Deserializes the body of a raw response, reporting Claude's error
+/// objects as errors.
+fn decode<R: DeserializeOwned>(response: &RawResponse) -> AiResult<R> {
+    match ClaudeApiError::from_raw_response(response) {
+        Some(error) => Err(error.into()),
+        None => decode_raw_response(response),
+    }
+}
@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Streamed responses from the Claude API.
+//!
+//! When a request is [streamed](crate::client::ClaudeClient::stream), the
+//! response is sent as a series of [`ClaudeStreamEvent`]s, as server-sent
+//! events, as it is generated. Each event can be translated into a
+//! [`ResponseDelta`], so that a [`ResponseAccumulator`] can reassemble the
+//! complete response.
+//!
+//! [`ResponseAccumulator`]: cogito::stream::ResponseAccumulator
+
+use crate::error::ClaudeApiError;
+use cogito::client::{AiError, Usage};
+use cogito::stream::{ResponseDelta, Resumable};
+use serde::Deserialize;
+
+/// An event in a streamed Claude API response.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ClaudeStreamEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+
+    #[serde(default)]
+    message: Option<ClaudeStreamedMessage>,
+
+    #[serde(default)]
+    delta: Option<ClaudeStreamDelta>,
+
+    #[serde(default)]
+    usage: Option<ClaudeStreamUsage>,
+
+    #[serde(default)]
+    error: Option<ClaudeStreamError>,
+}
+
+impl ClaudeStreamEvent {
+    /// The type of event, such as `content_block_delta`.
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    /// The piece of the response text carried by the event, if any.
+    ///
+    /// Text deltas become [text](ResponseDelta::Text). Claude reports its
+    /// usage in pieces, across the events that start and finish the
+    /// message, so usage is combined by [`usage()`](Self::usage) instead.
+    pub fn delta(&self) -> Option<ResponseDelta> {
+        let delta = self.delta.as_ref()?;
+        match (self.event_type(), delta.delta_type.as_str()) {
+            ("content_block_delta", "text_delta") => delta.text.clone().map(ResponseDelta::Text),
+            _ => None,
+        }
+    }
+
+    /// Combines the usage reported by the event with the `usage` reported
+    /// by earlier events.
+    ///
+    /// The event that starts the message reports its input tokens, and the
+    /// event that finishes it reports its output tokens, so the usage of
+    /// the whole response is known once the stream is finished.
+    pub fn usage(&self, usage: Option<Usage>) -> Option<Usage> {
+        let reported = match self.event_type() {
+            "message_start" => self.message.as_ref().map(|m| &m.usage),
+            "message_delta" => self.usage.as_ref(),
+            _ => None,
+        };
+        let Some(reported) = reported else {
+            return usage;
+        };
+        let mut usage = usage.unwrap_or_default();
+        if let Some(input_tokens) = reported.input_tokens {
+            let cache_creation = reported.cache_creation_input_tokens.unwrap_or(0);
+            let cache_read = reported.cache_read_input_tokens.unwrap_or(0);
+            usage.input_tokens = input_tokens + cache_creation + cache_read;
+            usage.cached_input_tokens = cache_read;
+        }
+        if let Some(output_tokens) = reported.output_tokens {
+            usage.output_tokens = output_tokens;
+        }
+        Some(usage)
+    }
+
+    /// The error reported by the event, if Claude failed partway through
+    /// the response.
+    ///
+    /// Claude reports errors such as `overloaded_error` in the stream after
+    /// the response has started, and they are converted to an [`AiError`]
+    /// in the same way as [`ClaudeApiError`]s.
+    pub fn error(&self) -> Option<AiError> {
+        let error = self.error.as_ref()?;
+        let error = ClaudeApiError::from_stream(error.error_type.clone(), error.message.clone());
+        Some(error.into())
+    }
+}
+
+impl Resumable for ClaudeStreamEvent {}
+
+/// The message attached to the event that starts a stream.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct ClaudeStreamedMessage {
+    #[serde(default)]
+    usage: ClaudeStreamUsage,
+}
+
+/// A piece of a content block, or the changes to the message when it
+/// finishes, which have no type.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct ClaudeStreamDelta {
+    #[serde(default, rename = "type")]
+    delta_type: String,
+
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// The usage reported by an event, which may only be part of the usage of
+/// the whole response.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+struct ClaudeStreamUsage {
+    #[serde(default)]
+    input_tokens: Option<u64>,
+
+    #[serde(default)]
+    output_tokens: Option<u64>,
+
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u64>,
+
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
+}
+
+/// An error reported partway through a stream.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct ClaudeStreamError {
+    #[serde(rename = "type")]
+    error_type: String,
+
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    mod stream_event {
+        use super::super::ClaudeStreamEvent;
+        use cogito::client::{AiError, Usage};
+        use cogito::stream::{ResponseDelta, Resumable};
+
+        fn parse(json_str: &str) -> ClaudeStreamEvent {
+            serde_json::from_str(json_str).expect("could not parse json")
+        }
+
+        #[test]
+        fn it_returns_a_text_delta() {
+            let event = parse(
+                r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Silent"}}"#,
+            );
+            assert_eq!(event.event_type(), "content_block_delta");
+            assert_eq!(event.sequence_number(), None);
+            assert_eq!(
+                event.delta(),
+                Some(ResponseDelta::Text("Silent".to_string()))
+            );
+        }
+
+        #[test]
+        fn it_returns_nothing_for_other_deltas() {
+            let event = parse(
+                r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "input_json_delta", "partial_json": "{\"city\""}}"#,
+            );
+            assert_eq!(event.delta(), None);
+
+            let event = parse(r#"{"type": "content_block_stop", "index": 0}"#);
+            assert_eq!(event.delta(), None);
+        }
+
+        #[test]
+        fn it_combines_the_usage_of_a_message() {
+            let start = parse(
+                r#"{
+                    "type": "message_start",
+                    "message": {
+                        "id": "msg_123",
+                        "type": "message",
+                        "role": "assistant",
+                        "content": [],
+                        "usage": {
+                            "input_tokens": 10,
+                            "cache_creation_input_tokens": 4,
+                            "cache_read_input_tokens": 2,
+                            "output_tokens": 1
+                        }
+                    }
+                }"#,
+            );
+            let finish = parse(
+                r#"{"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 18}}"#,
+            );
+            let usage = start.usage(None);
+            let usage = parse(r#"{"type": "ping"}"#).usage(usage);
+            let usage = finish.usage(usage);
+            let expected = Usage {
+                input_tokens: 16,
+                output_tokens: 18,
+                cached_input_tokens: 2,
+            };
+            assert_eq!(usage, Some(expected));
+        }
+
+        #[test]
+        fn it_returns_no_usage_before_any_is_reported() {
+            let event = parse(r#"{"type": "ping"}"#);
+            assert_eq!(event.usage(None), None);
+        }
+
+        #[test]
+        fn it_returns_an_error() {
+            let event = parse(
+                r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#,
+            );
+            let error = event.error().expect("no error");
+            assert!(error.is_retryable());
+            match error {
+                AiError::Api {
+                    status,
+                    kind,
+                    message,
+                } => {
+                    assert_eq!(status.as_u16(), 529);
+                    assert_eq!(kind, "overloaded_error");
+                    assert_eq!(message, "Overloaded");
+                }
+                other => panic!("expected an API error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn it_returns_no_error_for_other_events() {
+            let event = parse(r#"{"type": "message_stop"}"#);
+            assert!(event.error().is_none());
+        }
+    }
+}
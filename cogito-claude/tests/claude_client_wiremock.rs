@@ -0,0 +1,430 @@
+use cogito::client::AiError;
+use cogito::stream::ResponseAccumulator;
+use cogito_claude::prelude::*;
+use hypertyper::prelude::*;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use wiremock::matchers::{body_json, body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// These tests exercise the full request path, from the client through the
+// HTTP service, against a local mock of the Claude API, so they don't need
+// an API key.
+
+fn load_data(filename: &str) -> String {
+    fs::read_to_string(format!("tests/data/{filename}.json")).expect("could not load test data")
+}
+
+fn client(server: &MockServer) -> ClaudeClient<ClaudeService> {
+    let auth = Auth::new("some-api-key");
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    ClaudeClient::new(auth, factory).with_base_url(format!("{}/v1", server.uri()))
+}
+
+fn request() -> ClaudeRequest {
+    ClaudeRequest::default()
+        .model(ClaudeModel::Haiku45)
+        .input("Hello, world")
+}
+
+#[tokio::test]
+async fn it_sends_a_request_and_returns_a_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("x-api-key", "some-api-key"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .and(header("content-type", "application/json"))
+        .and(body_partial_json(serde_json::json!({
+            "model": "claude-haiku-4-5",
+            "messages": [{"role": "user", "content": "Hello, world"}],
+        })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(load_data("responses_multi"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let resp = client(&server)
+        .send(&request())
+        .await
+        .expect("could not make Claude API request");
+    assert_eq!(
+        resp.result(),
+        "Hello! How can I help you today?\nI am a friendly robot.\nBeep beep!"
+    );
+}
+
+#[tokio::test]
+async fn it_sends_a_request_with_per_request_auth() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("x-api-key", "customer-api-key"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(load_data("responses_multi"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client(&server)
+        .send_with_auth(&Auth::new("customer-api-key"), &request())
+        .await
+        .expect("could not make Claude API request");
+}
+
+#[tokio::test]
+async fn it_reports_an_unauthorized_request() {
+    let server = MockServer::start().await;
+    let body = r#"{"type": "error", "error": {"type": "authentication_error"}}"#;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(AiError::Unauthorized(message)) => assert_eq!(message, body),
+        other => panic!("expected unauthorized error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_reports_a_forbidden_request() {
+    let server = MockServer::start().await;
+    let body = r#"{"type": "error", "error": {"type": "permission_error"}}"#;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(403).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(AiError::Forbidden(message)) => assert_eq!(message, body),
+        other => panic!("expected forbidden error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_reports_a_rate_limited_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).set_body_string("Slow down!"))
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(AiError::RateLimited { retry_after, .. }) => assert_eq!(retry_after, None),
+        other => panic!("expected rate limited error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_reports_the_rate_limit_budget() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(load_data("responses"), "application/json")
+                .insert_header("anthropic-ratelimit-requests-remaining", "49")
+                .insert_header("anthropic-ratelimit-tokens-remaining", "39000")
+                .insert_header("anthropic-ratelimit-requests-reset", "2025-10-16T12:00:30Z")
+                .insert_header("anthropic-ratelimit-tokens-reset", "2025-10-16T12:00:00Z"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (resp, rate_limits) = client(&server)
+        .send_with_rate_limits(&request())
+        .await
+        .expect("could not make Claude API request");
+    assert_eq!(resp.result(), "Hello! How can I help you today?");
+    assert_eq!(rate_limits.remaining_requests, Some(49));
+    assert_eq!(rate_limits.remaining_tokens, Some(39000));
+    let reset_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_760_616_030);
+    assert_eq!(rate_limits.reset_at, Some(reset_at));
+}
+
+#[tokio::test]
+async fn it_reports_a_failed_request_with_rate_limits() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid x-api-key"))
+        .mount(&server)
+        .await;
+
+    match client(&server).send_with_rate_limits(&request()).await {
+        Err(AiError::Unauthorized(body)) => assert_eq!(body, "invalid x-api-key"),
+        other => panic!("expected an unauthorized error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_reports_an_overloaded_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(529)
+                .set_body_raw(load_data("errors_overloaded"), "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(AiError::Api { kind, message, .. }) => {
+            assert_eq!(kind, "overloaded_error");
+            assert_eq!(message, "Overloaded");
+        }
+        other => panic!("expected an API error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_lists_available_models() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header("x-api-key", "some-api-key"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(load_data("models"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let models = client(&server)
+        .models()
+        .await
+        .expect("could not list Claude models");
+    assert_eq!(
+        models,
+        vec!["claude-haiku-4-5-20251001", "claude-sonnet-4-5-20250929"]
+    );
+}
+
+#[tokio::test]
+async fn it_counts_tokens() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(header("x-api-key", "some-api-key"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .and(body_json(serde_json::json!({
+            "model": "claude-haiku-4-5",
+            "messages": [{"role": "user", "content": "Hello, world"}],
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "input_tokens": 42,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let tokens = client(&server)
+        .count_tokens(&request())
+        .await
+        .expect("could not count tokens");
+    assert_eq!(tokens, 42);
+}
+
+#[tokio::test]
+async fn it_sends_an_overridden_api_version() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("anthropic-version", "2025-10-01"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(load_data("responses_multi"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let auth = Auth::new("some-api-key");
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let client = ClaudeClient::new_with_version(auth, factory, "2025-10-01")
+        .with_base_url(format!("{}/v1", server.uri()));
+    client
+        .send(&request())
+        .await
+        .expect("could not make Claude API request");
+}
+
+fn events(events: &[(&str, &str)]) -> String {
+    events
+        .iter()
+        .map(|(event, data)| format!("event: {event}\ndata: {data}\n\n"))
+        .collect()
+}
+
+#[tokio::test]
+async fn it_streams_a_response_with_usage() {
+    let body = events(&[
+        (
+            "message_start",
+            r#"{"type": "message_start", "message": {"id": "msg_1", "usage": {"input_tokens": 12, "output_tokens": 1}}}"#,
+        ),
+        (
+            "content_block_delta",
+            r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hello, "}}"#,
+        ),
+        ("ping", r#"{"type": "ping"}"#),
+        (
+            "content_block_delta",
+            r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "world"}}"#,
+        ),
+        (
+            "message_delta",
+            r#"{"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 4}}"#,
+        ),
+        ("message_stop", r#"{"type": "message_stop"}"#),
+    ]);
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("accept", "text/event-stream"))
+        .and(body_partial_json(serde_json::json!({"stream": true})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server)
+        .send_streamed(&request())
+        .await
+        .expect("could not stream Claude API response");
+    assert_eq!(response.result(), "Hello, world");
+    let usage = response.usage().expect("no usage");
+    assert_eq!(usage.input_tokens, 12);
+    assert_eq!(usage.output_tokens, 4);
+}
+
+#[tokio::test]
+async fn it_reports_an_error_partway_through_a_stream() {
+    let body = events(&[
+        (
+            "content_block_delta",
+            r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hello"}}"#,
+        ),
+        (
+            "error",
+            r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#,
+        ),
+    ]);
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let error = client(&server)
+        .send_streamed(&request())
+        .await
+        .expect_err("expected an error");
+    assert!(error.is_retryable());
+}
+
+/// Serves each body in turn as a stream of server-sent events, one per
+/// connection, and records the body of each request.
+///
+/// wiremock always sends complete responses, so a stream that drops is
+/// served by hand: a body shorter than `length` is cut off.
+fn serve_events(bodies: Vec<(usize, String)>) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind");
+    let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&requests);
+    thread::spawn(move || {
+        for (stream, (length, body)) in listener.incoming().zip(bodies) {
+            let mut stream = stream.expect("could not accept connection");
+            let mut reader = BufReader::new(&stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("could not read request");
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("content-length:") {
+                    content_length = value.trim().parse().expect("invalid content length");
+                }
+            }
+            let mut request = vec![0; content_length];
+            reader
+                .read_exact(&mut request)
+                .expect("could not read body");
+            let request = serde_json::from_slice(&request).expect("request is not json");
+            recorded.lock().unwrap().push(request);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n{body}"
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (base_url, requests)
+}
+
+#[tokio::test]
+async fn it_resumes_a_stream_that_drops_once() {
+    let dropped = events(&[
+        (
+            "content_block_delta",
+            r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Silent "}}"#,
+        ),
+        (
+            "content_block_delta",
+            r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "circuits"}}"#,
+        ),
+    ]);
+    let resumed = events(&[
+        (
+            "content_block_delta",
+            r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": " hum"}}"#,
+        ),
+        ("message_stop", r#"{"type": "message_stop"}"#),
+    ]);
+    let (base_url, requests) = serve_events(vec![(1000, dropped), (resumed.len(), resumed)]);
+    let auth = Auth::new("some-api-key");
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let client = ClaudeClient::new(auth, factory).with_base_url(base_url);
+
+    let request = request();
+    let stream = client
+        .stream_resumable(&request, 1)
+        .await
+        .expect("could not stream Claude API response");
+    let events = stream.collect().await.expect("could not resume stream");
+    let mut accumulator = ResponseAccumulator::new();
+    accumulator.extend(events.iter().filter_map(|e| e.delta()));
+    assert_eq!(accumulator.finish().result(), "Silent circuits hum");
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["stream"], true);
+    assert_eq!(
+        requests[0]["messages"],
+        serde_json::json!([{"role": "user", "content": "Hello, world"}])
+    );
+    assert_eq!(
+        requests[1]["messages"],
+        serde_json::json!([
+            {"role": "user", "content": "Hello, world"},
+            {"role": "assistant", "content": "Silent circuits"},
+        ])
+    );
+}
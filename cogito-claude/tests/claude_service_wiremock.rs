@@ -0,0 +1,66 @@
+use cogito::service::HttpGet;
+use cogito_claude::prelude::*;
+use hypertyper::prelude::*;
+use serde_json::Value;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// These tests exercise the Claude service against a local mock of the
+// Claude API, so they don't need an API key.
+
+fn service() -> ClaudeService {
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    ClaudeService::new(factory)
+}
+
+#[tokio::test]
+async fn it_sends_a_get_request_and_decodes_the_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models/claude-haiku-4-5"))
+        .and(header("x-api-key", "some-api-key"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"type": "model", "id": "claude-haiku-4-5-20251001", "display_name": "Claude Haiku 4.5"}"#,
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let auth = Auth::new("some-api-key");
+    let uri = format!("{}/v1/models/claude-haiku-4-5", server.uri());
+    let model: Value = service()
+        .get(uri, &auth)
+        .await
+        .expect("could not make Claude API request");
+    assert_eq!(model["id"], "claude-haiku-4-5-20251001");
+    assert_eq!(model["display_name"], "Claude Haiku 4.5");
+}
+
+#[tokio::test]
+async fn it_sends_an_overridden_api_version() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header("anthropic-version", "2025-10-01"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data": []}"#))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let service = ClaudeService::with_version(factory, "2025-10-01");
+    assert_eq!(service.version(), "2025-10-01");
+    let auth = Auth::new("some-api-key");
+    let uri = format!("{}/v1/models", server.uri());
+    let models: Value = service
+        .get(uri, &auth)
+        .await
+        .expect("could not make Claude API request");
+    assert_eq!(models["data"], serde_json::json!([]));
+}
+
+#[test]
+fn it_uses_the_default_api_version() {
+    assert_eq!(service().version(), ClaudeService::ANTHROPIC_VERSION);
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Sends a prompt to the OpenAI API and prints the response.
+//!
+//! Reads an API key from `$OPENAI_API_KEY`:
+//!
+//! ```text
+//! cargo run --example chat -- --model gpt-4o "Write me a haiku."
+//! ```
+//!
+//! If `--model` is not given, the default model is used.
+
+use cogito::client::AiError;
+use cogito_openai::prelude::*;
+use hypertyper::prelude::*;
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: chat [--model <model>] <prompt>";
+
+fn parse_args() -> Result<(Option<OpenAIModel>, String), String> {
+    let mut model = None;
+    let mut prompt = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-m" | "--model" => {
+                let name = args.next().ok_or("--model requires a value")?;
+                let value = serde_json::Value::String(name.clone());
+                let parsed =
+                    serde_json::from_value(value).map_err(|_| format!("unknown model: {name}"))?;
+                model = Some(parsed);
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if prompt.is_none() => prompt = Some(arg),
+            _ => return Err(format!("unexpected argument: {arg}")),
+        }
+    }
+    let prompt = prompt.ok_or(USAGE)?;
+    Ok((model, prompt))
+}
+
+fn describe(err: &AiError) -> String {
+    match err {
+        AiError::Unauthorized(_) => "the API key was rejected; is $OPENAI_API_KEY valid?".into(),
+        AiError::RateLimited { retry_after, .. } => match retry_after {
+            Some(delay) => format!("rate limited; try again in {} seconds", delay.as_secs()),
+            None => "rate limited; try again later".into(),
+        },
+        AiError::BadRequest(body) => format!("the request was rejected: {body}"),
+        err if err.is_retryable() => format!("{err}; try again later"),
+        err => err.to_string(),
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let (model, prompt) = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let auth = match Auth::from_env("OPENAI_API_KEY") {
+        Ok(auth) => auth,
+        Err(_) => {
+            eprintln!("$OPENAI_API_KEY is not set");
+            return ExitCode::FAILURE;
+        }
+    };
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let client = OpenAIClient::new(auth, factory);
+
+    let request = OpenAIRequest::default().input(prompt);
+    let request = match model {
+        Some(model) => request.model(model),
+        None => request,
+    };
+
+    match client.send(&request).await {
+        Ok(response) => {
+            println!("{}", response.result());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}", describe(&err));
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! OpenAI Batch API client.
+//!
+//! The [Batch API] processes large numbers of requests asynchronously, at
+//! half the cost of sending them individually. Batches are completed within
+//! 24 hours, which makes them a good fit for offline jobs that do not need
+//! an immediate response, such as processing thousands of prompts overnight.
+//!
+//! Submitting a batch with [`BatchClient::submit()`] returns a
+//! [`BatchHandle`], which can be used to check on the batch's
+//! [status](BatchClient::status), or to wait for its
+//! [results](BatchClient::results). Each request in a batch succeeds or
+//! fails on its own, so the results include every successful response,
+//! even if some of the batch's requests failed.
+//!
+//! [Batch API]: https://platform.openai.com/docs/guides/batch
+
+use crate::client::{OpenAIRequest, OpenAIResponse};
+use crate::service::OpenAIService;
+use cogito::client::{AiError, AiResult, ProviderMetadata};
+use cogito::service::{check_status, decode_raw_response, decode_response};
+use cogito::stream::EventStream;
+use hypertyper::prelude::*;
+use reqwest::header::HeaderMap;
+use reqwest::multipart::{Form, Part};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The API endpoint that batched requests are sent to.
+const BATCH_ENDPOINT: &str = "/v1/responses";
+
+/// Sends batches of requests to the OpenAI Batch API.
+///
+/// # Examples
+///
+/// Submit a batch of requests, and later wait for the responses:
+///
+/// ```no_run
+/// use cogito_openai::batch::BatchClient;
+/// use cogito_openai::prelude::*;
+/// use hypertyper::prelude::*;
+///
+/// # async fn run() -> cogito::client::AiResult<()> {
+/// let auth = Auth::new("my-openai-api-key");
+/// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+/// let client = BatchClient::new(auth, factory);
+/// let requests = ["ai", "robots", "computers"]
+///     .iter()
+///     .map(|topic| OpenAIRequest::default().input(format!("Write a haiku about {topic}.")))
+///     .collect();
+/// let handle = client.submit(requests).await?;
+/// for result in client.results(&handle).await? {
+///     match result {
+///         Ok(response) => println!("{}", response.result()),
+///         Err(err) => eprintln!("request failed: {err}"),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Like [`OpenAIClient`](crate::client::OpenAIClient), a batch client is
+/// cheap to clone.
+#[derive(Clone, Debug)]
+pub struct BatchClient {
+    auth: Arc<Auth>,
+    service: OpenAIService,
+    base_url: String,
+    poll_interval: Duration,
+}
+
+impl BatchClient {
+    /// Create a new batch client using the given authentication data and
+    /// the given factory to create underlying HTTP clients.
+    pub fn new(auth: Auth, factory: HttpClientFactory) -> Self {
+        Self::with_service(auth, OpenAIService::new(factory))
+    }
+
+    /// Create a new batch client that sends requests using the given
+    /// `service`.
+    ///
+    /// This is useful for sending requests on behalf of a specific
+    /// [organization](OpenAIService::organization) or
+    /// [project](OpenAIService::project).
+    pub fn with_service(auth: Auth, service: OpenAIService) -> Self {
+        let auth = Arc::new(auth);
+        let base_url = crate::client::OpenAIClient::<OpenAIService>::base_url().to_string();
+        Self {
+            auth,
+            service,
+            base_url,
+            poll_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Sends requests to the API at `base_url` instead of the OpenAI API.
+    ///
+    /// See [`OpenAIClient::with_base_url()`](crate::client::OpenAIClient::with_base_url).
+    pub fn with_base_url(self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { base_url, ..self }
+    }
+
+    /// Sets how long [`results()`](BatchClient::results) waits between
+    /// checks on the status of a batch.
+    ///
+    /// Batches can take hours to complete, so by default, the status is
+    /// checked once a minute.
+    pub fn poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            ..self
+        }
+    }
+
+    /// Submits a batch of requests.
+    ///
+    /// The requests are uploaded as a file, and a batch is created to
+    /// process them. Requests that do not specify a model use the
+    /// [default](crate::OpenAIModel::default()) model.
+    pub async fn submit(&self, requests: Vec<OpenAIRequest>) -> AiResult<BatchHandle> {
+        let file = self.upload(&requests).await?;
+        let body = CreateBatch {
+            input_file_id: &file.id,
+            endpoint: BATCH_ENDPOINT,
+            completion_window: "24h",
+        };
+        let request = self.post("batches").json(&body);
+        let batch: Batch = self.send(request).await?;
+        Ok(BatchHandle { id: batch.id })
+    }
+
+    /// The current status of the batch.
+    pub async fn status(&self, handle: &BatchHandle) -> AiResult<BatchStatus> {
+        Ok(self.batch(handle).await?.status)
+    }
+
+    /// Waits for the batch to complete and returns the result of each of
+    /// its requests.
+    ///
+    /// Results are returned in the same order as the requests that were
+    /// [submitted](BatchClient::submit). Each result is either the
+    /// request's response or the reason it failed, which is read from the
+    /// batch's error file, so the responses to successful requests are
+    /// returned even if other requests failed. If the batch as a whole
+    /// fails, expires, or is cancelled, an [`AiError::Failed`] error is
+    /// returned instead.
+    pub async fn results(&self, handle: &BatchHandle) -> AiResult<Vec<AiResult<OpenAIResponse>>> {
+        let batch = loop {
+            let batch = self.batch(handle).await?;
+            match batch.status {
+                BatchStatus::Completed => break batch,
+                status if status.is_finished() => {
+                    let message = format!("batch {} is {status:?}", handle.id);
+                    return Err(AiError::Failed(message));
+                }
+                _ => cogito::time::sleep(self.poll_interval).await,
+            }
+        };
+
+        let file_ids = [batch.output_file_id, batch.error_file_id];
+        let mut outputs = Vec::new();
+        for file_id in file_ids.into_iter().flatten() {
+            outputs.extend(self.download(&file_id).await?);
+        }
+        outputs.sort_by_key(|output| index(&output.custom_id));
+
+        let total = batch
+            .request_counts
+            .map_or(outputs.len(), |counts| counts.total as usize);
+        let mut outputs = outputs.into_iter().peekable();
+        let mut results = Vec::with_capacity(total);
+        for i in 0..total {
+            let result = match outputs.next_if(|output| index(&output.custom_id) == i) {
+                Some(output) => output.into_result(),
+                None => {
+                    let message = format!("batch {} has no result for request {i}", handle.id);
+                    Err(AiError::Failed(message))
+                }
+            };
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Downloads a batch's output or error file.
+    async fn download(&self, file_id: &str) -> AiResult<Vec<BatchOutput>> {
+        let request = self.get(&format!("files/{file_id}/content"));
+        let response = request.send().await.map_err(HttpError::from)?;
+        let response = check_status(response).await?;
+        EventStream::new(response).collect().await
+    }
+
+    async fn upload(&self, requests: &[OpenAIRequest]) -> AiResult<File> {
+        let body = jsonl(requests).map_err(HttpError::from)?;
+        let part = Part::bytes(body.into_bytes())
+            .file_name("batch.jsonl")
+            .mime_str("application/jsonl")
+            .map_err(HttpError::from)?;
+        let form = Form::new().text("purpose", "batch").part("file", part);
+        let request = self.post("files").multipart(form);
+        self.send(request).await
+    }
+
+    async fn batch(&self, handle: &BatchHandle) -> AiResult<Batch> {
+        let request = self.get(&format!("batches/{}", handle.id));
+        self.send(request).await
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        let request = self.service.http_client().post(self.endpoint(path));
+        self.service.authorize(request, &self.auth)
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        let request = self.service.http_client().get(self.endpoint(path));
+        self.service.authorize(request, &self.auth)
+    }
+
+    async fn send<R: DeserializeOwned>(&self, request: RequestBuilder) -> AiResult<R> {
+        let response = request.send().await.map_err(HttpError::from)?;
+        decode_response(response).await
+    }
+
+    /// The URI of the OpenAI API endpoint at the given path.
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{path}", self.base_url)
+    }
+}
+
+/// Identifies a batch that has been submitted to the OpenAI Batch API.
+///
+/// Handles can be serialized, so a batch can be submitted by one process
+/// and its results retrieved later by another.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BatchHandle {
+    id: String,
+}
+
+impl BatchHandle {
+    /// Creates a handle for the batch with the given ID.
+    pub fn new(id: impl Into<String>) -> Self {
+        let id = id.into();
+        Self { id }
+    }
+
+    /// The batch's ID.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// The status of a batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// The input file is being validated before the batch can begin.
+    Validating,
+
+    /// The input file failed validation.
+    Failed,
+
+    /// The batch is being processed.
+    InProgress,
+
+    /// The batch has been processed and its results are being prepared.
+    Finalizing,
+
+    /// The batch has been processed and its results are ready.
+    Completed,
+
+    /// The batch was not completed within 24 hours.
+    Expired,
+
+    /// The batch is being cancelled.
+    Cancelling,
+
+    /// The batch was cancelled.
+    Cancelled,
+}
+
+impl BatchStatus {
+    /// True if the batch will not make any further progress.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            BatchStatus::Failed
+                | BatchStatus::Completed
+                | BatchStatus::Expired
+                | BatchStatus::Cancelled
+        )
+    }
+}
+
+/// A request in a batch's input file.
+#[derive(Debug, Serialize)]
+struct BatchInput<'a> {
+    custom_id: String,
+    method: &'static str,
+    url: &'static str,
+    body: &'a OpenAIRequest,
+}
+
+/// The result of a request in a batch's output or error file.
+#[derive(Debug, Deserialize)]
+struct BatchOutput {
+    custom_id: String,
+    #[serde(default)]
+    response: Option<BatchOutputResponse>,
+    #[serde(default)]
+    error: Option<BatchOutputError>,
+}
+
+impl BatchOutput {
+    /// The request's response, or the reason it failed.
+    ///
+    /// Unsuccessful responses are reported as errors in the same way as
+    /// responses to requests that are not batched.
+    fn into_result(self) -> AiResult<OpenAIResponse> {
+        if let Some(error) = self.error {
+            let message = match error.code {
+                Some(code) => format!("{} ({code})", error.message),
+                None => error.message,
+            };
+            return Err(AiError::Failed(message));
+        }
+        let Some(response) = self.response else {
+            let message = format!("{} has no response", self.custom_id);
+            return Err(AiError::Failed(message));
+        };
+        let status =
+            StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        decode_raw_response(&(status, HeaderMap::new(), response.body.to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchOutputResponse {
+    status_code: u16,
+    body: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchOutputError {
+    #[serde(default)]
+    code: Option<String>,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateBatch<'a> {
+    input_file_id: &'a str,
+    endpoint: &'static str,
+    completion_window: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+struct Batch {
+    id: String,
+    status: BatchStatus,
+    output_file_id: Option<String>,
+    #[serde(default)]
+    error_file_id: Option<String>,
+    request_counts: Option<RequestCounts>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestCounts {
+    total: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct File {
+    id: String,
+}
+
+/// The custom ID identifying the request at `index` in a batch.
+fn custom_id(index: usize) -> String {
+    format!("request-{index}")
+}
+
+/// The index of the request identified by `custom_id` in a batch.
+fn index(custom_id: &str) -> usize {
+    custom_id
+        .strip_prefix("request-")
+        .and_then(|i| i.parse().ok())
+        .unwrap_or(usize::MAX)
+}
+
+/// Serializes `requests` as a batch input file.
+fn jsonl(requests: &[OpenAIRequest]) -> serde_json::Result<String> {
+    let mut body = String::new();
+    for (i, request) in requests.iter().enumerate() {
+        let input = BatchInput {
+            custom_id: custom_id(i),
+            method: "POST",
+            url: BATCH_ENDPOINT,
+            body: request,
+        };
+        body.push_str(&serde_json::to_string(&input)?);
+        body.push('\n');
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    mod batch_input {
+        use super::super::{custom_id, index, jsonl};
+        use crate::client::OpenAIRequest;
+        use cogito::client::AiRequest;
+
+        #[test]
+        fn it_round_trips_custom_ids() {
+            assert_eq!(custom_id(12), "request-12");
+            assert_eq!(index(&custom_id(12)), 12);
+        }
+
+        #[test]
+        fn it_sorts_unknown_custom_ids_last() {
+            assert_eq!(index("something-else"), usize::MAX);
+        }
+
+        #[test]
+        fn it_serializes_one_request_per_line() {
+            let requests = vec![
+                OpenAIRequest::default().input("Write a haiku."),
+                OpenAIRequest::default().input("Write a limerick."),
+            ];
+            let body = jsonl(&requests).expect("could not serialize requests");
+            let lines = body.lines().collect::<Vec<_>>();
+            assert_eq!(
+                lines,
+                vec![
+                    r#"{"custom_id":"request-0","method":"POST","url":"/v1/responses","body":{"model":"gpt-5","input":"Write a haiku.","store":false}}"#,
+                    r#"{"custom_id":"request-1","method":"POST","url":"/v1/responses","body":{"model":"gpt-5","input":"Write a limerick.","store":false}}"#,
+                ]
+            );
+        }
+    }
+
+    mod batch_status {
+        use super::super::BatchStatus;
+
+        #[test]
+        fn it_deserializes() {
+            let status: BatchStatus = serde_json::from_str(r#""in_progress""#).unwrap();
+            assert_eq!(status, BatchStatus::InProgress);
+        }
+
+        #[test]
+        fn it_is_finished() {
+            assert!(BatchStatus::Completed.is_finished());
+            assert!(BatchStatus::Expired.is_finished());
+            assert!(!BatchStatus::Finalizing.is_finished());
+        }
+    }
+
+    mod batch_output {
+        use super::super::BatchOutput;
+        use cogito::client::AiError;
+
+        #[test]
+        fn it_reports_unsuccessful_responses() {
+            let output: BatchOutput = serde_json::from_str(
+                r#"{"custom_id":"request-0","response":{"status_code":400,"body":{"error":{"message":"Invalid input"}}},"error":null}"#,
+            )
+            .unwrap();
+            match output.into_result() {
+                Err(AiError::BadRequest(body)) => assert!(body.contains("Invalid input")),
+                other => panic!("expected bad request, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn it_reports_missing_responses() {
+            let output: BatchOutput = serde_json::from_str(r#"{"custom_id":"request-3"}"#).unwrap();
+            match output.into_result() {
+                Err(AiError::Failed(message)) => assert_eq!(message, "request-3 has no response"),
+                other => panic!("expected failed error, got {other:?}"),
+            }
+        }
+    }
+}
@@ -34,11 +34,27 @@
 //! [`cogito::service::Auth`]: https://docs.rs/cogito/latest/cogito/service/struct.Auth.html
 
 use crate::OpenAIModel;
+use crate::service::OpenAIService;
+use crate::stream::OpenAIStreamEvent;
+#[cfg(feature = "tracing")]
+use cogito::client::send_span;
 use cogito::prelude::*;
+use cogito::service::AuthScheme;
+use cogito::stream::{
+    AccumulatedResponse, EventStream, Reconnection, ResponseAccumulator, ResponseDelta,
+    ResumableStream,
+};
+use cogito::{normalize_whitespace, truncate_to_tokens};
 use hypertyper::prelude::*;
 use itertools::Itertools;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::slice::Iter;
+use std::sync::Arc;
+#[cfg(feature = "tracing")]
+use tracing::Instrument;
 
 #[cfg(doc)]
 use cogito::AiModel;
@@ -58,37 +74,335 @@ use cogito::AiModel;
 /// let factory = HttpClientFactory::new("my-package", "v1.0.0");
 /// let client = OpenAIClient::new(auth, factory);
 /// ```
-#[derive(Debug)]
-pub struct OpenAIClient<T: HttpPost + Sync> {
-    auth: Auth,
+///
+/// If your API key belongs to multiple organizations or projects, specify
+/// which one requests should be made on behalf of:
+///
+/// ```
+/// use cogito_openai::client::OpenAIClient;
+/// use hypertyper::prelude::*;
+///
+/// let auth = Auth::new("my-openai-api-key");
+/// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+/// let client = OpenAIClient::new(auth, factory)
+///     .organization("org-123")
+///     .project("proj_abc");
+/// ```
+///
+/// Clients are cheap to clone. Clones share their authentication data and,
+/// when using the default service, the underlying HTTP connection pool,
+/// so a client can be cloned and moved into each task that needs it
+/// instead of being wrapped in an [`Arc`].
+#[derive(Clone, Debug)]
+pub struct OpenAIClient<T: AiPost> {
+    auth: Arc<Auth>,
     service: T,
+    model: OpenAIModel,
+    base_url: String,
+    check_context_window: bool,
 }
 
-impl<T: HttpPost + Sync> AiClient for OpenAIClient<T> {
+impl<T: AiPost> AiClient for OpenAIClient<T> {
     type AiRequest = OpenAIRequest;
     type AiResponse = OpenAIResponse;
 
+    /// Sends the request to the OpenAI API and receives a response.
+    ///
+    /// If the request does not specify a model, the client's
+    /// [model](OpenAIClient::model) is used.
+    ///
+    /// If the `tracing` feature is enabled, the request is instrumented
+    /// with a [span](cogito::client::send_span).
     async fn send(&self, request: &Self::AiRequest) -> AiResult<Self::AiResponse> {
-        self.service.post(Self::BASE_URI, &self.auth, request).await
+        self.send_with_auth(&self.auth, request).await
+    }
+
+    /// Sends the request to the OpenAI API using the given authentication
+    /// data, and receives a response.
+    ///
+    /// The client's organization and project, if any, are still sent with
+    /// the request.
+    async fn send_with_auth(
+        &self,
+        auth: &Auth,
+        request: &Self::AiRequest,
+    ) -> AiResult<Self::AiResponse> {
+        let uri = self.endpoint("responses");
+        let request = request.with_default_model(self.model);
+        self.preflight(&request)?;
+        let response = self.service.try_post(uri, auth, &*request);
+
+        #[cfg(feature = "tracing")]
+        let response = {
+            let model = request.model.unwrap_or_default();
+            response.instrument(send_span(
+                Self::provider_name(),
+                &model,
+                &request.input_text(),
+            ))
+        };
+
+        response.await
+    }
+
+    /// The features supported by the client's [model](OpenAIClient::model).
+    ///
+    /// If a request specifies a different model, that model may support
+    /// different features.
+    fn capabilities(&self) -> Capabilities {
+        self.model.capabilities()
+    }
+
+    /// The IDs of the models available to the client's API key, from
+    /// OpenAI's `models` endpoint.
+    async fn models(&self) -> AiResult<Vec<String>> {
+        let uri = self.endpoint("models");
+        let models: OpenAIModelList = self.service.try_get(uri, &self.auth).await?;
+        Ok(models.data.into_iter().map(|model| model.id).collect())
+    }
+
+    /// Checks that the OpenAI API accepts the client's credentials.
+    ///
+    /// The [models](AiClient::models) available to the client's API key are
+    /// listed, which is free, so the check costs nothing.
+    async fn health_check(&self) -> AiResult<()> {
+        self.models().await.map(|_| ())
     }
 }
 
-impl<T: HttpPost + Sync> OpenAIClient<T> {
-    /// The base URI for OpenAI API requests.
-    const BASE_URI: &'static str = "https://api.openai.com/v1/responses";
+impl<T: AiPost> ProviderMetadata for OpenAIClient<T> {
+    fn provider_name() -> &'static str {
+        "OpenAI"
+    }
+
+    fn base_url() -> &'static str {
+        "https://api.openai.com/v1"
+    }
+
+    fn auth_scheme() -> AuthScheme {
+        OpenAIService::AUTH_SCHEME
+    }
+}
+
+impl<T: AiPost> OpenAIClient<T> {
+    /// The URI of the OpenAI API endpoint at the given path.
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{path}", self.base_url)
+    }
+
+    /// Create a new OpenAI client that sends requests using the given
+    /// `service`.
+    ///
+    /// This allows a client to be built on a service that adds behavior
+    /// around the [`OpenAIService`], such as a
+    /// [`CachingService`](cogito::service::CachingService), or on a mock
+    /// service in tests. The service is responsible for authenticating
+    /// requests in the way the OpenAI API expects, so it should usually wrap
+    /// a `OpenAIService`.
+    ///
+    /// # Examples
+    ///
+    /// Cache responses to identical requests:
+    ///
+    /// ```
+    /// use cogito_openai::prelude::*;
+    /// use cogito::service::CachingService;
+    /// use hypertyper::prelude::*;
+    ///
+    /// let auth = Auth::new("my-openai-api-key");
+    /// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+    /// let service = CachingService::new(OpenAIService::new(factory));
+    /// let client = OpenAIClient::with_service(auth, service);
+    /// ```
+    pub fn with_service(auth: Auth, service: T) -> Self {
+        let auth = Arc::new(auth);
+        let model = OpenAIModel::default();
+        let base_url = Self::base_url().to_string();
+        Self {
+            auth,
+            service,
+            model,
+            base_url,
+            check_context_window: false,
+        }
+    }
+
+    /// Sends requests to the API at `base_url` instead of the OpenAI API.
+    ///
+    /// This is useful for talking to OpenAI-compatible gateways, or to a
+    /// local mock server in tests. `base_url` replaces the
+    /// [default base URL](ProviderMetadata::base_url), so it should
+    /// include any version prefix, such as `http://localhost:8080/v1`.
+    pub fn with_base_url(self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { base_url, ..self }
+    }
+
+    /// Checks that each request fits within its model's context window
+    /// before sending it.
+    ///
+    /// A request's estimated input tokens, plus its `max_output_tokens`
+    /// [extra](AiRequest::extra) field, if any, must fit within the
+    /// [context window](AiModel::context_window), or an
+    /// [`AiError::ContextWindowExceeded`] error is returned without
+    /// contacting the OpenAI API. Tokens are only
+    /// [estimated](AiModel::estimate_tokens), and the estimate is
+    /// conservative, so this is off by default.
+    pub fn with_context_window_check(self) -> Self {
+        Self {
+            check_context_window: true,
+            ..self
+        }
+    }
+
+    fn preflight(&self, request: &OpenAIRequest) -> AiResult<()> {
+        if !self.check_context_window {
+            return Ok(());
+        }
+        let max_output_tokens = request
+            .extra
+            .get("max_output_tokens")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        request.check_context_window(max_output_tokens as usize)
+    }
 
-    fn with_service(auth: Auth, service: T) -> Self {
-        Self { auth, service }
+    /// The model used for requests that do not specify a model.
+    pub fn model(&self) -> OpenAIModel {
+        self.model
     }
 }
 
-impl OpenAIClient<Service> {
+impl OpenAIClient<OpenAIService> {
     /// Create a new OpenAI client using the given authentication data and
     /// the given factory to create underlying HTTP clients.
     pub fn new(auth: Auth, factory: HttpClientFactory) -> Self {
-        let service = Service::new(factory);
+        let service = OpenAIService::new(factory);
         Self::with_service(auth, service)
     }
+
+    /// Create a new OpenAI client that uses the given `model` for any
+    /// request that does not specify a model.
+    ///
+    /// # Examples
+    ///
+    /// Use the best model for every request:
+    ///
+    /// ```
+    /// use cogito_openai::prelude::*;
+    /// use hypertyper::prelude::*;
+    ///
+    /// let auth = Auth::new("my-openai-api-key");
+    /// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+    /// let client = OpenAIClient::new_with_model(auth, factory, OpenAIModel::best());
+    /// let request = OpenAIRequest::default().input("Write me a haiku.");
+    /// ```
+    pub fn new_with_model(auth: Auth, factory: HttpClientFactory, model: OpenAIModel) -> Self {
+        Self {
+            model,
+            ..Self::new(auth, factory)
+        }
+    }
+
+    /// Sets the organization that requests are made on behalf of.
+    ///
+    /// This is only necessary if your API key belongs to multiple
+    /// organizations. See [`OpenAIService::organization()`].
+    pub fn organization(self, organization: impl Into<String>) -> Self {
+        let service = self.service.organization(organization);
+        Self { service, ..self }
+    }
+
+    /// Sets the project that requests are made on behalf of.
+    ///
+    /// This is only necessary if your API key has access to multiple
+    /// projects. See [`OpenAIService::project()`].
+    pub fn project(self, project: impl Into<String>) -> Self {
+        let service = self.service.project(project);
+        Self { service, ..self }
+    }
+
+    /// Sends the request to the OpenAI API and receives a response, along
+    /// with the rate-limit budget OpenAI reported with it.
+    ///
+    /// This is useful for pacing requests, or for showing users how many
+    /// requests they have left. If the request does not specify a model,
+    /// the client's [model](OpenAIClient::model) is used.
+    pub async fn send_with_rate_limits(
+        &self,
+        request: &OpenAIRequest,
+    ) -> AiResult<(OpenAIResponse, RateLimitStatus)> {
+        let uri = self.endpoint("responses");
+        let request = request.with_default_model(self.model);
+        self.preflight(&request)?;
+        self.service
+            .try_post_with_rate_limits(uri, &self.auth, &*request)
+            .await
+    }
+
+    /// Sends the request to the OpenAI API and streams the response as it
+    /// is generated.
+    ///
+    /// The request is always [streamed](OpenAIRequest::stream), and its
+    /// usage is reported by the event that finishes the response.
+    ///
+    /// The OpenAI API streams responses as server-sent events, but
+    /// OpenAI-compatible gateways that stream newline-delimited JSON are
+    /// supported as well; see [`OpenAIService::post_stream()`].
+    pub async fn stream(
+        &self,
+        request: &OpenAIRequest,
+    ) -> AiResult<EventStream<OpenAIStreamEvent>> {
+        let uri = self.endpoint("responses");
+        let request = request.with_default_model(self.model).into_owned();
+        self.preflight(&request)?;
+        let request = request.stream();
+        self.service.post_stream(uri, &self.auth, &request).await
+    }
+
+    /// Sends the request to the OpenAI API and streams the response,
+    /// reconnecting up to `max_retries` times if the connection drops
+    /// partway through it.
+    ///
+    /// The response is [stored](OpenAIRequest::store), so that when the
+    /// connection drops, the stream can reconnect with a
+    /// [continuation](OpenAIRequest::from_stream_continuation) that picks
+    /// up from the partial response. Each continuation is a new response,
+    /// so the complete response is every text delta across all of them.
+    pub async fn stream_resumable<'a>(
+        &'a self,
+        request: &'a OpenAIRequest,
+        max_retries: u32,
+    ) -> AiResult<
+        ResumableStream<
+            OpenAIStreamEvent,
+            impl FnMut(&[OpenAIStreamEvent]) -> Reconnection<'a, OpenAIStreamEvent>,
+        >,
+    > {
+        let mut current = request.clone().store(true);
+        let stream = self.stream(&current).await?;
+        let reconnect =
+            move |received: &[OpenAIStreamEvent]| -> Reconnection<'a, OpenAIStreamEvent> {
+                current = OpenAIRequest::from_stream_continuation(&current, received);
+                let request = current.clone();
+                Box::pin(async move { self.stream(&request).await })
+            };
+        Ok(ResumableStream::new(stream, max_retries, reconnect).renumbers_on_reconnect())
+    }
+
+    /// Sends the request to the OpenAI API, streaming the response, and
+    /// returns the response once the stream is finished.
+    ///
+    /// The response's [usage](AiResponse::usage) is available if OpenAI
+    /// reported it when the response finished.
+    pub async fn send_streamed(&self, request: &OpenAIRequest) -> AiResult<AccumulatedResponse> {
+        let mut stream = self.stream(request).await?;
+        let mut accumulator = ResponseAccumulator::new();
+        while let Some(event) = stream.next().await {
+            accumulator.extend(event?.delta());
+        }
+        Ok(accumulator.finish())
+    }
 }
 
 /// Parameters and data for an OpenAI API request.
@@ -106,16 +420,82 @@ impl OpenAIClient<Service> {
 ///
 /// let request = OpenAIRequest::default().model(OpenAIModel::Gpt5).input("Write me a haiku.");
 /// ```
-#[derive(Debug, Default, Deserialize, Serialize)]
+///
+/// Input can also be built up as a conversation of [messages], which
+/// lets you provide earlier turns of the conversation as context:
+///
+/// ```
+/// use cogito::client::AiRequest;
+/// use cogito_openai::client::{OpenAIRequest, OpenAIRole};
+///
+/// let request = OpenAIRequest::default()
+///     .message(OpenAIRole::User, "Write me a haiku.")
+///     .message(OpenAIRole::Assistant, "Silent circuits hum")
+///     .message(OpenAIRole::User, "Now write another one.");
+/// ```
+///
+/// Requests can be cloned, so a request can be kept as a template for
+/// other requests that differ only in a few options:
+///
+/// ```
+/// use cogito::client::AiRequest;
+/// use cogito_openai::client::{Effort, OpenAIRequest};
+///
+/// let template = OpenAIRequest::default().instructions("You are a poet.");
+/// let quick = template.clone().reasoning_effort(Effort::Low).input("Write me a haiku.");
+/// let careful = template.clone().reasoning_effort(Effort::High).input("Write me a sonnet.");
+/// ```
+///
+/// [messages]: OpenAIRequest::message
+///
+/// # Serialization
+///
+/// Requests are serialized with their fields in a stable order, so
+/// request bodies can safely be compared against snapshots: `model`,
+/// `instructions`, `input`, `store`, and then any other options that
+/// have been set, in the order they were added to the API, followed by
+/// any [extra](AiRequest::extra) fields. Options that have not been set
+/// are omitted.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 pub struct OpenAIRequest {
-    model: OpenAIModel,
+    // Fields are serialized in the order they are declared, so new fields
+    // must be added at the end to keep request bodies stable.
+    #[serde(default, serialize_with = "serialize_model")]
+    model: Option<OpenAIModel>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     instructions: Option<String>,
 
-    input: String,
+    input: OpenAIInput,
 
     store: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    logit_bias: BTreeMap<u32, f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<OpenAITextOptions>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning: Option<OpenAIReasoningOptions>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_response_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parallel_tool_calls: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service_tier: Option<ServiceTier>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+
+    #[serde(flatten)]
+    extra: Map<String, Value>,
 }
 
 impl AiRequest for OpenAIRequest {
@@ -124,13 +504,26 @@ impl AiRequest for OpenAIRequest {
 
     /// Sets the model used by the OpenAI API request.
     ///
-    /// If not specified, the [default](OpenAIModel::default()) model
-    /// will be used. If you are on a budget, you can also try using the
-    /// [least expensive](OpenAIModel::cheapest()) instead.
+    /// If not specified, the [client's model](OpenAIClient::new_with_model)
+    /// will be used, which is the [default](OpenAIModel::default()) model
+    /// unless the client was configured otherwise. If you are on a budget,
+    /// you can also try using the [least expensive](OpenAIModel::cheapest())
+    /// instead.
     fn model(self, model: OpenAIModel) -> Self {
+        let model = Some(model);
         Self { model, ..self }
     }
 
+    /// The model the request will use.
+    ///
+    /// If the request does not specify a model, this is the
+    /// [default](OpenAIModel::default()) model. Note that a client
+    /// [configured](OpenAIClient::new_with_model) with a different model
+    /// sends such requests using its own model instead.
+    fn model_used(&self) -> OpenAIModel {
+        self.model.unwrap_or_default()
+    }
+
     /// Sets optional instructions for the request.
     ///
     /// Instructions provide high-level instructions on how a GPT model should
@@ -154,221 +547,1913 @@ impl AiRequest for OpenAIRequest {
     ///
     /// If [instructions](OpenAIRequest::instructions) are provided,
     /// the instructions take precedence over this input.
+    ///
+    /// Setting the input replaces any [messages](OpenAIRequest::message)
+    /// that were previously added to the request.
     fn input(self, input: impl Into<String>) -> Self {
-        let input = input.into();
+        let input = OpenAIInput::Text(input.into());
         Self { input, ..self }
     }
-}
 
-/// A response from the OpenAI API.
-#[derive(Debug, Deserialize, Serialize)]
-pub struct OpenAIResponse {
-    output: Vec<OpenAIOutput>,
-}
+    /// The request's current input.
+    ///
+    /// If the input is a series of messages, this is the content of every
+    /// user message, joined by newlines.
+    fn input_text(&self) -> Cow<'_, str> {
+        self.input.text()
+    }
 
-impl AiResponse for OpenAIResponse {
-    fn result(&self) -> String {
-        self.concatenate()
+    /// True if [instructions](OpenAIRequest::instructions) have been set.
+    ///
+    /// Instructions are sent separately from the input, and take
+    /// precedence over it.
+    fn has_instructions(&self) -> bool {
+        self.instructions.is_some()
     }
-}
 
-impl OpenAIResponse {
-    /// The response from an OpenAI API request.
+    /// Adds a field that is not otherwise supported to the request's body.
     ///
-    /// This is the concatenation of all [output] and is the entire response
-    /// from an OpenAI AI model.
+    /// This is useful for using OpenAI API options that are not yet
+    /// supported by `OpenAIRequest`, such as `service_tier`. Extra fields
+    /// are serialized after all of the request's other fields.
+    fn extra(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.extra.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Truncates the request's input so that it fits within `max_tokens`.
     ///
-    /// You should call [`result()`] instead of calling this method directly
-    /// so other API providers can easily be swapped in for the OpenAI
-    /// provider, but it is available in case your code needs it for some
-    /// reason.
+    /// If the input is a series of [messages](OpenAIRequest::message),
+    /// the oldest messages are removed first, so the most recent turns of
+    /// the conversation are kept. The final user message is never removed,
+    /// but it is truncated if it does not fit on its own.
+    fn truncate_input_to(self, max_tokens: usize, model: &OpenAIModel) -> Self {
+        let input = self.input.truncate_to(max_tokens, model);
+        Self { input, ..self }
+    }
+
+    /// Normalizes the whitespace in the request's input.
     ///
-    /// [output]: OpenAIResponse::output
-    /// [`result()`]: OpenAIResponse::result
-    fn concatenate(&self) -> String {
-        self.output()
-            .map(|o| o.concatenate())
-            .join("\n")
-            .trim()
-            .to_string()
+    /// If the input is a series of [messages](OpenAIRequest::message),
+    /// each message is normalized.
+    fn normalize_input(self) -> Self {
+        let input = self.input.normalize();
+        Self { input, ..self }
     }
 
-    /// GPT response output, as a series of responses.
+    /// Appends more text to the request's input.
     ///
-    /// There should be at least item in the output, but there could be
-    /// multiple output objects.
-    fn output(&self) -> Iter<'_, OpenAIOutput> {
-        self.output.iter()
+    /// Plain text input is joined to `more` with a newline. If the input
+    /// is a series of [messages](OpenAIRequest::message), `more` is added
+    /// as another user message instead.
+    fn append_input(self, more: impl Into<String>) -> Self {
+        match self.input {
+            OpenAIInput::Text(ref text) if text.is_empty() => self.input(more),
+            OpenAIInput::Text(ref text) => {
+                let input = format!("{text}\n{}", more.into());
+                self.input(input)
+            }
+            OpenAIInput::Messages(_) => self.message(OpenAIRole::User, more),
+        }
     }
-}
 
-/*
-   Prior to GPT-5, the content of a response was a vector of
-   output structs, and the response had a "type" of "message".
-   GPT-5 introduced a "reasoning" type that lacks a "content" field,
-   instead having a "summary" field. We're not terribly interested
-   in that output right now so we don't do anything with it, but
-   we have to handle that type of output regardless, and just ignore it.
-*/
-/// Generated GPT output.
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(tag = "type", rename_all = "lowercase")]
-enum OpenAIOutput {
-    /// Contents of a meaningful response from the LLM.
-    Message { content: Vec<OpenAIContent> },
+    /// Adds an example exchange to the request's input.
+    ///
+    /// The example is added as a user [message](OpenAIRequest::message)
+    /// containing `input`, followed by an assistant message containing
+    /// `output`.
+    fn example(self, input: impl Into<String>, output: impl Into<String>) -> Self {
+        self.message(OpenAIRole::User, input)
+            .message(OpenAIRole::Assistant, output)
+    }
 
-    /// Metadata about the reasoning employed by a GPT-5 model.
-    Reasoning,
+    /// Configures the request so that OpenAI does not
+    /// [store](OpenAIRequest::store) its response.
+    ///
+    /// OpenAI may still retain requests for a limited time for abuse
+    /// monitoring, unless your organization has arranged otherwise.
+    fn privacy_mode(self) -> Self {
+        self.store(false)
+    }
 }
 
-impl OpenAIOutput {
-    /// Contents of the GPT API response.
+impl OpenAIRequest {
+    /// The request, using the given `model` if no model was specified.
+    fn with_default_model(&self, model: OpenAIModel) -> Cow<'_, Self> {
+        match self.model {
+            Some(_) => Cow::Borrowed(self),
+            None => Cow::Owned(self.clone().model(model)),
+        }
+    }
+
+    /// Builds a request that continues generating a previous response.
     ///
-    /// There should be at least one piece of content in the output,
-    /// but there could be multiple content objects.
-    pub fn content(&self) -> Iter<'_, OpenAIContent> {
-        match self {
-            OpenAIOutput::Message { content } => content.iter(),
-            OpenAIOutput::Reasoning => [].iter(),
+    /// This is useful when a response was cut short, such as when it ran
+    /// out of output tokens. The new request uses the same settings as
+    /// the original `request`, and its input is the original input
+    /// followed by the `response` as an assistant message, so sending it
+    /// picks up where the previous response left off. A single completion
+    /// is always requested, regardless of the original request's
+    /// [number of completions](OpenAIRequest::n).
+    ///
+    /// # Examples
+    ///
+    /// Keep generating until the response is complete:
+    ///
+    /// ```no_run
+    /// use cogito::prelude::*;
+    /// use cogito_openai::client::{OpenAIClient, OpenAIRequest};
+    /// use hypertyper::prelude::*;
+    ///
+    /// # async fn run() -> AiResult<String> {
+    /// let auth = Auth::new("my-openai-api-key");
+    /// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+    /// let client = OpenAIClient::new(auth, factory);
+    ///
+    /// let mut request = OpenAIRequest::default().input("Write me an epic poem.");
+    /// let mut response = client.send(&request).await?;
+    /// let mut poem = response.result();
+    /// while response.is_incomplete() {
+    ///     request = OpenAIRequest::from_response_continuation(&request, &response);
+    ///     response = client.send(&request).await?;
+    ///     poem.push_str(&response.result());
+    /// }
+    /// # Ok(poem)
+    /// # }
+    /// ```
+    pub fn from_response_continuation(request: &OpenAIRequest, response: &OpenAIResponse) -> Self {
+        let mut messages = request.input.clone().into_messages();
+        messages.push(OpenAIMessage::new(
+            OpenAIRole::Assistant,
+            response.concatenate(),
+        ));
+        let input = OpenAIInput::Messages(messages);
+        Self {
+            input,
+            n: None,
+            ..request.clone()
         }
     }
 
-    /// Concatenates all output text from [`content()`](OpenAIOutput::content())
-    /// into a single string.
-    pub fn concatenate(&self) -> String {
-        // Might make sense to return an Option here to support reasoning type...
-        self.content()
-            .filter(|c| c.is_output_text())
-            .map(|c| c.text())
-            .join("\n")
+    /// Builds a request that continues a streamed response whose
+    /// connection dropped, given the `received` events.
+    ///
+    /// The new request continues from the last response that was started
+    /// in `received`, using its ID as the
+    /// [previous response](OpenAIRequest::previous_response_id), and its
+    /// input is the text received from that response as an assistant
+    /// message, so that the model picks up where it left off. The earlier
+    /// response must have been [stored](OpenAIRequest::store). If no text
+    /// was received from the response, nothing is lost by starting over,
+    /// so `request` is returned unchanged.
+    ///
+    /// [`OpenAIClient::stream_resumable()`] uses this to reconnect.
+    pub fn from_stream_continuation(
+        request: &OpenAIRequest,
+        received: &[OpenAIStreamEvent],
+    ) -> Self {
+        let last_response = received
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, e)| Some((i, e.response_id()?)));
+        let Some((start, id)) = last_response else {
+            return request.clone();
+        };
+        let text: String = received[start..]
+            .iter()
+            .filter_map(|e| match e.delta() {
+                Some(ResponseDelta::Text(text)) => Some(text),
+                _ => None,
+            })
+            .collect();
+        if text.is_empty() {
+            return request.clone();
+        }
+        let input = OpenAIInput::Messages(vec![OpenAIMessage::new(OpenAIRole::Assistant, text)]);
+        Self {
+            input,
+            n: None,
+            store: true,
+            previous_response_id: Some(id.to_string()),
+            ..request.clone()
+        }
     }
-}
 
-/// Content of GPT output.
-#[derive(Debug, Deserialize, Serialize)]
-struct OpenAIContent {
-    // TODO: Use an enum, when I figure out what the possible values are
-    #[serde(rename = "type")]
-    content_type: String,
+    /// Appends a message to the request's input.
+    ///
+    /// Messages turn the request's input into a conversation, which can
+    /// include earlier responses from the model as context. If the request
+    /// already has plain [input](AiRequest::input), it becomes the first
+    /// user message of the conversation.
+    pub fn message(self, role: OpenAIRole, content: impl Into<String>) -> Self {
+        let mut messages = self.input.into_messages();
+        messages.push(OpenAIMessage::new(role, content));
+        let input = OpenAIInput::Messages(messages);
+        Self { input, ..self }
+    }
 
-    text: String,
-}
+    /// Sets the number of completions to generate for the request.
+    ///
+    /// Each completion is returned as a separate output in the response.
+    /// If not specified, a single completion is generated.
+    pub fn n(self, n: u32) -> Self {
+        let n = Some(n);
+        Self { n, ..self }
+    }
 
-impl OpenAIContent {
-    /// The content type.
-    pub fn content_type(&self) -> &str {
-        &self.content_type
+    /// Modifies the likelihood of specific tokens appearing in the
+    /// response.
+    ///
+    /// `biases` maps token IDs to a bias from -100 to 100. Values between
+    /// -1 and 1 slightly decrease or increase the likelihood of a token
+    /// being selected, while values like -100 or 100 effectively ban or
+    /// require it. Token IDs depend on the tokenizer used by the model.
+    ///
+    /// Biases are serialized in order of token ID, so the request body is
+    /// the same no matter how `biases` is ordered.
+    pub fn logit_bias(self, biases: impl IntoIterator<Item = (u32, f32)>) -> Self {
+        Self {
+            logit_bias: biases.into_iter().collect(),
+            ..self
+        }
     }
 
-    /// True if the content should be shown to the user.
-    pub fn is_output_text(&self) -> bool {
-        self.content_type() == "output_text"
+    /// Sets how verbose the model's response should be.
+    ///
+    /// Lower verbosity produces more concise responses, while higher
+    /// verbosity produces more thorough ones. Only GPT-5 models support
+    /// this setting. If not specified, the model uses medium verbosity.
+    pub fn verbosity(self, verbosity: Verbosity) -> Self {
+        let text = Some(OpenAITextOptions { verbosity });
+        Self { text, ..self }
     }
 
-    /// Generated GPT text.
-    pub fn text(&self) -> &str {
-        &self.text
+    /// Sets how much effort the model spends reasoning before it responds.
+    ///
+    /// Less effort produces faster, cheaper responses, while more effort
+    /// produces more thoughtful ones. Only models that
+    /// [reason](cogito::client::Capabilities::reasoning) support this
+    /// setting. If not specified, the model uses medium effort.
+    pub fn reasoning_effort(self, effort: Effort) -> Self {
+        let reasoning = Some(OpenAIReasoningOptions { effort });
+        Self { reasoning, ..self }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::client::OpenAIResponse;
-    use std::fs;
+    /// Sets whether OpenAI stores the response so that later requests can
+    /// refer to it by its ID.
+    ///
+    /// Responses are not stored by default.
+    pub fn store(self, store: bool) -> Self {
+        Self { store, ..self }
+    }
 
-    fn load_data(filename: &str) -> String {
-        fs::read_to_string(format!("tests/data/{filename}.json")).expect("could not find test data")
+    /// Continues from an earlier response, using it and its input as the
+    /// context for this request.
+    ///
+    /// The earlier response must have been [stored](OpenAIRequest::store).
+    /// This can also be used to resume a [stream](cogito::stream::ResumableStream)
+    /// whose connection dropped partway through a response.
+    pub fn previous_response_id(self, id: impl Into<String>) -> Self {
+        let previous_response_id = Some(id.into());
+        Self {
+            previous_response_id,
+            ..self
+        }
     }
 
-    fn load_response(filename: &str) -> OpenAIResponse {
-        let data = load_data(filename);
-        serde_json::from_str(&data).expect("could not parse json")
+    /// Sets whether the model may call several tools at once.
+    ///
+    /// Disable parallel tool calls to force the model to call tools one at
+    /// a time, so that each call can depend on the result of the last.
+    /// Tools themselves are not yet modeled by this crate, but can be sent
+    /// as an [extra](AiRequest::extra) `tools` field. If not specified,
+    /// OpenAI allows parallel tool calls.
+    pub fn parallel_tool_calls(self, enabled: bool) -> Self {
+        let parallel_tool_calls = Some(enabled);
+        Self {
+            parallel_tool_calls,
+            ..self
+        }
     }
 
-    mod client {
+    /// Sets the tier of service used to process the request.
+    ///
+    /// The [flex](ServiceTier::Flex) tier is cheaper but slower, which
+    /// suits background jobs. If not specified, the tier configured for
+    /// the OpenAI project is used.
+    pub fn service_tier(self, tier: ServiceTier) -> Self {
+        let service_tier = Some(tier);
+        Self {
+            service_tier,
+            ..self
+        }
+    }
+
+    /// Asks OpenAI to stream the response as it is generated.
+    ///
+    /// The tokens used by the request are reported by the
+    /// `response.completed` event that finishes the stream, so streamed
+    /// responses still report their [usage](AiResponse::usage). Streamed
+    /// requests are sent with [`OpenAIClient::stream()`] or
+    /// [`OpenAIClient::send_streamed()`].
+    pub fn stream(self) -> Self {
+        Self {
+            stream: true,
+            ..self
+        }
+    }
+
+    /// Sets the [verbosity](OpenAIRequest::verbosity) in place.
+    pub fn set_verbosity(&mut self, verbosity: Verbosity) -> &mut Self {
+        self.update(|r| r.verbosity(verbosity))
+    }
+
+    /// Sets the [reasoning effort](OpenAIRequest::reasoning_effort) in
+    /// place.
+    pub fn set_reasoning_effort(&mut self, effort: Effort) -> &mut Self {
+        self.update(|r| r.reasoning_effort(effort))
+    }
+
+    /// Sets the [tier of service](OpenAIRequest::service_tier) in place.
+    pub fn set_service_tier(&mut self, tier: ServiceTier) -> &mut Self {
+        self.update(|r| r.service_tier(tier))
+    }
+}
+
+/// Requests without a model are sent using the default model.
+fn serialize_model<S>(model: &Option<OpenAIModel>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    model.unwrap_or_default().serialize(serializer)
+}
+
+/// Input to an OpenAI API request.
+///
+/// Input can be sent as a plain string, or as a series of messages when
+/// the request includes earlier turns of a conversation.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+enum OpenAIInput {
+    Text(String),
+    Messages(Vec<OpenAIMessage>),
+}
+
+impl Default for OpenAIInput {
+    fn default() -> Self {
+        OpenAIInput::Text(String::new())
+    }
+}
+
+impl OpenAIInput {
+    /// The text of the input, with user messages joined by newlines.
+    pub fn text(&self) -> Cow<'_, str> {
+        match self {
+            OpenAIInput::Text(text) => Cow::Borrowed(text),
+            OpenAIInput::Messages(messages) => {
+                let text = messages
+                    .iter()
+                    .filter(|m| m.role == OpenAIRole::User)
+                    .map(|m| m.content.as_str())
+                    .join("\n");
+                Cow::Owned(text)
+            }
+        }
+    }
+
+    /// Truncates the input so that it fits within `max_tokens`.
+    pub fn truncate_to(self, max_tokens: usize, model: &OpenAIModel) -> Self {
+        match self {
+            OpenAIInput::Text(text) => {
+                OpenAIInput::Text(truncate_to_tokens(&text, max_tokens, model).to_string())
+            }
+            OpenAIInput::Messages(mut messages) => {
+                let mut remaining = max_tokens;
+                // The final user message is what the model responds to, so
+                // room is made for it before any other message.
+                let last = messages.iter().rposition(|m| m.role == OpenAIRole::User);
+                if let Some(i) = last {
+                    let content = truncate_to_tokens(&messages[i].content, remaining, model);
+                    let content = content.to_string();
+                    remaining -= model.estimate_tokens(&content);
+                    messages[i].content = content;
+                }
+                let mut fits = true;
+                let mut messages = messages
+                    .into_iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|(i, m)| {
+                        if Some(*i) == last {
+                            return true;
+                        }
+                        let tokens = model.estimate_tokens(&m.content);
+                        fits = fits && tokens <= remaining;
+                        if fits {
+                            remaining -= tokens;
+                        }
+                        fits
+                    })
+                    .map(|(_, m)| m)
+                    .collect::<Vec<_>>();
+                messages.reverse();
+                OpenAIInput::Messages(messages)
+            }
+        }
+    }
+
+    /// Normalizes the whitespace in the input.
+    pub fn normalize(self) -> Self {
+        match self {
+            OpenAIInput::Text(text) => OpenAIInput::Text(normalize_whitespace(&text)),
+            OpenAIInput::Messages(messages) => {
+                let messages = messages
+                    .into_iter()
+                    .map(|m| OpenAIMessage::new(m.role, normalize_whitespace(&m.content)))
+                    .collect();
+                OpenAIInput::Messages(messages)
+            }
+        }
+    }
+
+    /// Converts the input into a series of messages.
+    ///
+    /// Plain text input becomes a single user message, unless it is empty.
+    pub fn into_messages(self) -> Vec<OpenAIMessage> {
+        match self {
+            OpenAIInput::Text(text) if text.is_empty() => vec![],
+            OpenAIInput::Text(text) => vec![OpenAIMessage::new(OpenAIRole::User, text)],
+            OpenAIInput::Messages(messages) => messages,
+        }
+    }
+}
+
+/// A message in the input of an OpenAI API request.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct OpenAIMessage {
+    role: OpenAIRole,
+    content: String,
+}
+
+impl OpenAIMessage {
+    pub fn new(role: OpenAIRole, content: impl Into<String>) -> Self {
+        let content = content.into();
+        Self { role, content }
+    }
+}
+
+/// The author of a message in an OpenAI API request.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenAIRole {
+    /// Input provided by the user.
+    User,
+
+    /// Output previously generated by the model.
+    Assistant,
+
+    /// Instructions provided by the developer, which take precedence over
+    /// user messages.
+    Developer,
+}
+
+/// Options for the text generated in response to an OpenAI API request.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct OpenAITextOptions {
+    verbosity: Verbosity,
+}
+
+/// Options for the reasoning performed in response to an OpenAI API
+/// request.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct OpenAIReasoningOptions {
+    effort: Effort,
+}
+
+/// How verbose a model's response should be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    /// Concise responses.
+    Low,
+
+    /// Balanced responses.
+    Medium,
+
+    /// Thorough responses.
+    High,
+}
+
+/// How much effort a model spends reasoning before it responds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Effort {
+    /// Very little reasoning, for the fastest responses. Only GPT-5 models
+    /// support minimal effort.
+    Minimal,
+
+    /// Less reasoning, for faster responses.
+    Low,
+
+    /// A balance between speed and reasoning.
+    Medium,
+
+    /// More reasoning, for more thoughtful responses.
+    High,
+}
+
+/// The tier of service used to process an OpenAI API request, trading
+/// latency for cost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceTier {
+    /// The tier configured for the OpenAI project.
+    Auto,
+
+    /// Standard pricing and performance.
+    Default,
+
+    /// Lower prices in exchange for slower responses and occasional
+    /// unavailability.
+    Flex,
+
+    /// Faster, more reliable responses at higher prices.
+    Priority,
+}
+
+/// The status of an [`OpenAIResponse`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseStatus {
+    /// The response was generated in full.
+    Completed,
+
+    /// The response was cut short; see
+    /// [`incomplete_reason()`](OpenAIResponse::incomplete_reason).
+    Incomplete,
+
+    /// The response is still being generated.
+    InProgress,
+
+    /// The response is waiting to be generated.
+    Queued,
+
+    /// The response could not be generated.
+    Failed,
+
+    /// The response was cancelled before it was finished.
+    Cancelled,
+
+    /// A status that is not yet known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Why an [incomplete](ResponseStatus::Incomplete) response was cut short.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncompleteReason {
+    /// The model ran out of output tokens.
+    MaxOutputTokens,
+
+    /// The response was stopped by OpenAI's content filter.
+    ContentFilter,
+
+    /// A reason that is not yet known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct OpenAIUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+    total_tokens: u64,
+
+    #[serde(default)]
+    input_tokens_details: OpenAIInputTokensDetails,
+
+    #[serde(default)]
+    output_tokens_details: OpenAIOutputTokensDetails,
+}
+
+impl From<&OpenAIUsage> for Usage {
+    fn from(usage: &OpenAIUsage) -> Self {
+        Usage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cached_input_tokens: usage.input_tokens_details.cached_tokens,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+struct OpenAIInputTokensDetails {
+    cached_tokens: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+struct OpenAIOutputTokensDetails {
+    reasoning_tokens: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct OpenAIIncompleteDetails {
+    reason: IncompleteReason,
+}
+
+/// A response from the OpenAI API.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct OpenAIResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<ResponseStatus>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    incomplete_details: Option<OpenAIIncompleteDetails>,
+
+    output: Vec<OpenAIOutput>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    usage: Option<OpenAIUsage>,
+
+    #[serde(flatten)]
+    extra: Value,
+}
+
+impl AiResponse for OpenAIResponse {
+    /// The response's output text.
+    ///
+    /// In debug builds with the `tracing` feature enabled, a warning is
+    /// logged if the response is [incomplete](OpenAIResponse::is_incomplete),
+    /// so that silently truncated results are noticed during development.
+    fn result(&self) -> String {
+        self.warn_if_incomplete();
+        self.concatenate()
+    }
+
+    /// The fields of the OpenAI API response that are not otherwise
+    /// modeled, such as the response's `id` and `model`.
+    fn raw(&self) -> Option<&Value> {
+        Some(&self.extra)
+    }
+
+    /// The tokens used by the request and its response.
+    ///
+    /// The output tokens include any reasoning tokens, which OpenAI bills
+    /// as output tokens.
+    fn usage(&self) -> Option<Usage> {
+        self.usage.as_ref().map(Usage::from)
+    }
+
+    /// The number of output tokens the OpenAI API reports, not counting
+    /// any reasoning tokens, which are not part of the response's text.
+    fn output_tokens(&self) -> Option<u64> {
+        let usage = self.usage.as_ref()?;
+        let reasoning_tokens = usage.output_tokens_details.reasoning_tokens;
+        Some(usage.output_tokens.saturating_sub(reasoning_tokens))
+    }
+
+    /// The text of each message in the response's output.
+    ///
+    /// When a request asks for [several completions](OpenAIRequest::n),
+    /// each completion is a separate message. Output that is not a
+    /// message, such as reasoning, is skipped.
+    fn outputs_text(&self) -> Vec<String> {
+        self.warn_if_incomplete();
+        self.output()
+            .filter(|o| matches!(o, OpenAIOutput::Message { .. }))
+            .map(|o| o.concatenate().trim().to_string())
+            .collect()
+    }
+}
+
+impl OpenAIResponse {
+    /// The status of the response.
+    ///
+    /// Responses that do not report a status are assumed to be
+    /// [completed](ResponseStatus::Completed).
+    pub fn status(&self) -> ResponseStatus {
+        self.status.unwrap_or(ResponseStatus::Completed)
+    }
+
+    /// True if the response was cut short before it was finished.
+    ///
+    /// This usually means the model ran out of output tokens, but the
+    /// [reason](OpenAIResponse::incomplete_reason) may also be that the
+    /// response was filtered. The rest of a response that ran out of tokens
+    /// can be generated by sending a
+    /// [continuation](OpenAIRequest::from_response_continuation) request.
+    pub fn is_incomplete(&self) -> bool {
+        self.status() == ResponseStatus::Incomplete
+    }
+
+    /// Why the response was cut short, if it is
+    /// [incomplete](OpenAIResponse::is_incomplete).
+    pub fn incomplete_reason(&self) -> Option<IncompleteReason> {
+        self.incomplete_details.as_ref().map(|d| d.reason)
+    }
+
+    /// Citations attached to the output text, in the order they appear.
+    ///
+    /// Responses only have annotations when the model used a tool such as
+    /// web search or file search to find its sources.
+    pub fn annotations(&self) -> impl Iterator<Item = &Annotation> {
+        self.output()
+            .flat_map(|o| o.content())
+            .filter(|c| c.is_output_text())
+            .flat_map(|c| c.annotations())
+    }
+
+    /// Logs a warning if the response is incomplete.
+    ///
+    /// This is a no-op in release builds or without the `tracing` feature.
+    fn warn_if_incomplete(&self) {
+        #[cfg(all(debug_assertions, feature = "tracing"))]
+        if self.is_incomplete() {
+            tracing::warn!(
+                reason = ?self.incomplete_reason(),
+                "result of an incomplete OpenAI response was used"
+            );
+        }
+    }
+
+    /// The response from an OpenAI API request.
+    ///
+    /// This is the concatenation of all [output] and is the entire response
+    /// from an OpenAI AI model.
+    ///
+    /// You should call [`result()`] instead of calling this method directly
+    /// so other API providers can easily be swapped in for the OpenAI
+    /// provider, but it is available in case your code needs it for some
+    /// reason.
+    ///
+    /// [output]: OpenAIResponse::output
+    /// [`result()`]: OpenAIResponse::result
+    fn concatenate(&self) -> String {
+        self.output()
+            .map(|o| o.concatenate())
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    /// GPT response output, as a series of responses.
+    ///
+    /// There should be at least item in the output, but there could be
+    /// multiple output objects.
+    fn output(&self) -> Iter<'_, OpenAIOutput> {
+        self.output.iter()
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl OpenAIResponse {
+    /// Builds a completed response whose output is a single message
+    /// containing each of `texts`.
+    ///
+    /// This lets tests build the responses they expect without loading
+    /// them from JSON. It is only available when the `testing` feature is
+    /// enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cogito_openai::prelude::*;
+    /// let response = OpenAIResponse::from_texts(["Hello!", "How are you?"]);
+    /// assert_eq!(response.result(), "Hello!\nHow are you?");
+    /// ```
+    pub fn from_texts<I, S>(texts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let content = texts
+            .into_iter()
+            .map(|text| OpenAIContent {
+                content_type: "output_text".to_string(),
+                text: text.into(),
+                annotations: Vec::new(),
+            })
+            .collect();
+        Self {
+            status: Some(ResponseStatus::Completed),
+            incomplete_details: None,
+            output: vec![OpenAIOutput::Message { content }],
+            usage: None,
+            extra: Value::Object(Default::default()),
+        }
+    }
+}
+
+impl TryFrom<Value> for OpenAIResponse {
+    type Error = serde_json::Error;
+
+    /// Reads a response from JSON that has already been parsed, such as a
+    /// response received by a webhook.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+impl<'a> IntoIterator for &'a OpenAIResponse {
+    type Item = &'a str;
+    type IntoIter = OutputText<'a>;
+
+    /// Iterates over each piece of output text in the response.
+    ///
+    /// These are the same pieces of text that are joined together by
+    /// [`result()`](OpenAIResponse::result); content that is not meant to
+    /// be shown to the user, such as reasoning, is skipped.
+    fn into_iter(self) -> Self::IntoIter {
+        OutputText {
+            output: self.output(),
+            content: [].iter(),
+        }
+    }
+}
+
+/// An iterator over the output text of an [`OpenAIResponse`].
+///
+/// This `struct` is created by iterating over a reference to an
+/// `OpenAIResponse`.
+///
+/// # Examples
+///
+/// ```
+/// # use cogito_openai::prelude::*;
+/// # fn print_response(response: &OpenAIResponse) {
+/// for text in response {
+///     println!("{text}");
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OutputText<'a> {
+    output: Iter<'a, OpenAIOutput>,
+    content: Iter<'a, OpenAIContent>,
+}
+
+impl<'a> Iterator for OutputText<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(content) = self.content.by_ref().find(|c| c.is_output_text()) {
+                return Some(content.text());
+            }
+            self.content = self.output.next()?.content();
+        }
+    }
+}
+
+/*
+   Prior to GPT-5, the content of a response was a vector of
+   output structs, and the response had a "type" of "message".
+   GPT-5 introduced a "reasoning" type that lacks a "content" field,
+   instead having a "summary" field. We're not terribly interested
+   in that output right now so we don't do anything with it, but
+   we have to handle that type of output regardless, and just ignore it.
+*/
+/// Generated GPT output.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum OpenAIOutput {
+    /// Contents of a meaningful response from the LLM.
+    Message {
+        #[serde(deserialize_with = "deserialize_message_content")]
+        content: Vec<OpenAIContent>,
+    },
+
+    /// Metadata about the reasoning employed by a GPT-5 model.
+    Reasoning,
+
+    /// Any other type of output, such as a web search call.
+    ///
+    /// OpenAI adds new types of output from time to time. We don't know
+    /// what to do with them, but they shouldn't prevent the rest of the
+    /// response from being read, so they are ignored, just like reasoning.
+    #[serde(other)]
+    Other,
+}
+
+impl OpenAIOutput {
+    /// Contents of the GPT API response.
+    ///
+    /// There should be at least one piece of content in the output,
+    /// but there could be multiple content objects.
+    pub fn content(&self) -> Iter<'_, OpenAIContent> {
+        match self {
+            OpenAIOutput::Message { content } => content.iter(),
+            OpenAIOutput::Reasoning | OpenAIOutput::Other => [].iter(),
+        }
+    }
+
+    /// Concatenates all output text from [`content()`](OpenAIOutput::content())
+    /// into a single string.
+    pub fn concatenate(&self) -> String {
+        // Might make sense to return an Option here to support reasoning type...
+        self.content()
+            .filter(|c| c.is_output_text())
+            .map(|c| c.text())
+            .join("\n")
+    }
+}
+
+/// The content of a message, as it is sent by the server.
+///
+/// OpenAI always sends an array of content, but some OpenAI-compatible
+/// servers, such as vLLM and LM Studio, send the message's text as a bare
+/// string instead.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OpenAIMessageContent {
+    Text(String),
+    Content(Vec<OpenAIContent>),
+}
+
+impl From<OpenAIMessageContent> for Vec<OpenAIContent> {
+    fn from(content: OpenAIMessageContent) -> Self {
+        match content {
+            OpenAIMessageContent::Text(text) => vec![OpenAIContent {
+                content_type: "output_text".to_string(),
+                text,
+                annotations: Vec::new(),
+            }],
+            OpenAIMessageContent::Content(content) => content,
+        }
+    }
+}
+
+/// Reads the content of a message, whether it is an array of content or a
+/// single string of text.
+fn deserialize_message_content<'de, D>(deserializer: D) -> Result<Vec<OpenAIContent>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    OpenAIMessageContent::deserialize(deserializer).map(Vec::from)
+}
+
+/// Content of GPT output.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct OpenAIContent {
+    // TODO: Use an enum, when I figure out what the possible values are
+    #[serde(rename = "type")]
+    content_type: String,
+
+    text: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<Annotation>,
+}
+
+impl OpenAIContent {
+    /// The content type.
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// True if the content should be shown to the user.
+    pub fn is_output_text(&self) -> bool {
+        self.content_type() == "output_text"
+    }
+
+    /// Generated GPT text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Citations attached to the text, such as the sources found by a web
+    /// search.
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+}
+
+/// A citation attached to a piece of output text.
+///
+/// When a response draws on web search or file search results, OpenAI
+/// annotates the output text with the sources it used, so they can be
+/// shown alongside the answer.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    /// A web page cited by the text between `start_index` and `end_index`.
+    UrlCitation {
+        /// The URL of the web page.
+        url: String,
+
+        /// The title of the web page.
+        #[serde(default)]
+        title: String,
+
+        /// The index of the first character of the cited text.
+        start_index: usize,
+
+        /// The index after the last character of the cited text.
+        end_index: usize,
+    },
+
+    /// A file cited at `index` in the text.
+    FileCitation {
+        /// The ID of the file.
+        file_id: String,
+
+        /// The name of the file.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+
+        /// The index in the text at which the file is cited.
+        index: usize,
+    },
+
+    /// A type of annotation that is not yet known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Annotation {
+    /// The URL of a cited web page, if the annotation cites one.
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            Annotation::UrlCitation { url, .. } => Some(url),
+            Annotation::FileCitation { .. } | Annotation::Unknown => None,
+        }
+    }
+}
+
+/// A list of models from the OpenAI API's `models` endpoint.
+#[derive(Debug, Deserialize)]
+struct OpenAIModelList {
+    data: Vec<OpenAIModelObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIModelObject {
+    id: String,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::client::OpenAIResponse;
+    use std::fs;
+
+    fn load_data(filename: &str) -> String {
+        fs::read_to_string(format!("tests/data/{filename}.json")).expect("could not find test data")
+    }
+
+    fn load_response(filename: &str) -> OpenAIResponse {
+        let data = load_data(filename);
+        serde_json::from_str(&data).expect("could not parse json")
+    }
+
+    mod client {
         use super::load_data;
+        use crate::OpenAIModel;
         use crate::client::{OpenAIClient, OpenAIRequest};
-        use cogito::client::{AiClient, AiRequest};
+        use cogito::client::{AiClient, AiError, AiRequest, Capabilities};
+        use cogito::service::{AiPost, CachingService};
         use hypertyper::prelude::*;
         use serde::Serialize;
         use serde::de::DeserializeOwned;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct TestApiService {
+            requests: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl HttpPost for TestApiService {
+            async fn post<U, D, R>(&self, _uri: U, _auth: &Auth, data: &D) -> HttpResult<R>
+            where
+                U: IntoUrl + Send,
+                D: Serialize + Sync,
+                R: DeserializeOwned,
+            {
+                let request = serde_json::to_string(data)?;
+                self.requests.lock().unwrap().push(request);
+                let data = self.load_data();
+                Ok(serde_json::from_str(&data)?)
+            }
+        }
+
+        impl AiPost for TestApiService {}
+
+        impl TestApiService {
+            fn load_data(&self) -> String {
+                load_data("responses")
+            }
+
+            fn last_request(&self) -> serde_json::Value {
+                let requests = self.requests.lock().unwrap();
+                let request = requests.last().expect("no requests were sent");
+                serde_json::from_str(request).expect("could not parse request")
+            }
+        }
+
+        impl OpenAIClient<TestApiService> {
+            fn test() -> Self {
+                let auth = Auth::new("some-api-key");
+                OpenAIClient::with_service(auth, TestApiService::default())
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        #[tokio::test]
+        async fn it_records_a_span() {
+            use cogito::testing::SpanRecorder;
+
+            let recorder = SpanRecorder::default();
+            let _guard = tracing::subscriber::set_default(recorder.clone());
+            let client = OpenAIClient::test();
+            let request = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt4omini)
+                .input("write a haiku about ai");
+            client.send(&request).await.expect("could not send request");
+            let field = |name| recorder.field(name);
+            assert_eq!(field("provider").as_deref(), Some("OpenAI"));
+            assert_eq!(field("model").as_deref(), Some("gpt-4o-mini"));
+            assert_eq!(field("input_tokens_estimate").as_deref(), Some("6"));
+        }
+
+        #[test]
+        fn it_reports_the_capabilities_of_its_model() {
+            let client = OpenAIClient::test();
+            let expected = Capabilities {
+                streaming: true,
+                tools: true,
+                vision: true,
+                reasoning: true,
+                seed: false,
+                json_mode: true,
+            };
+            assert_eq!(client.capabilities(), expected);
+
+            let client = OpenAIClient {
+                model: OpenAIModel::Gpt4o,
+                ..client
+            };
+            let expected = Capabilities {
+                reasoning: false,
+                ..expected
+            };
+            assert_eq!(client.capabilities(), expected);
+        }
+
+        #[tokio::test]
+        async fn it_rejects_requests_that_exceed_the_context_window() {
+            let client = OpenAIClient::test().with_context_window_check();
+            let request = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt4o)
+                .input("word ".repeat(100_000))
+                .extra("max_output_tokens", 4096);
+
+            let result = client.send(&request).await;
+            assert!(
+                matches!(
+                    result,
+                    Err(AiError::ContextWindowExceeded {
+                        needed: 129_096,
+                        available: 128_000
+                    })
+                ),
+                "{result:?}"
+            );
+            assert!(client.service.requests.lock().unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn it_sends_requests_that_fit_within_the_context_window() {
+            let client = OpenAIClient::test().with_context_window_check();
+            let request = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt4o)
+                .input("word ".repeat(100_000));
+
+            assert!(client.send(&request).await.is_ok());
+            assert_eq!(client.service.requests.lock().unwrap().len(), 1);
+        }
+
+        #[tokio::test]
+        async fn it_sends_requests_through_a_caching_service() {
+            let service = TestApiService::default();
+            let auth = Auth::new("some-api-key");
+            let client = OpenAIClient::with_service(auth, CachingService::new(service.clone()));
+            let request = OpenAIRequest::default().input("write a haiku about ai");
+
+            let first = client.send(&request).await.expect("could not send request");
+            let second = client.send(&request).await.expect("could not send request");
+            assert_eq!(first, second);
+            assert_eq!(service.requests.lock().unwrap().len(), 1);
+        }
+
+        #[tokio::test]
+        async fn it_checks_its_health() {
+            let client = OpenAIClient::test();
+            assert!(client.health_check().await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn it_sends_requests_using_its_model() {
+            let client = OpenAIClient {
+                model: OpenAIModel::Gpt4o,
+                ..OpenAIClient::test()
+            };
+            let request = OpenAIRequest::default().input("write a haiku about ai");
+            client.send(&request).await.expect("could not send request");
+            assert_eq!(client.service.last_request()["model"], "gpt-4o");
+        }
 
-        #[derive(Default)]
-        struct TestApiService {}
+        #[tokio::test]
+        async fn it_sends_requests_using_their_own_model() {
+            let client = OpenAIClient {
+                model: OpenAIModel::Gpt4o,
+                ..OpenAIClient::test()
+            };
+            let request = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt4omini)
+                .input("write a haiku about ai");
+            client.send(&request).await.expect("could not send request");
+            assert_eq!(client.service.last_request()["model"], "gpt-4o-mini");
+        }
 
-        impl HttpPost for TestApiService {
-            async fn post<U, D, R>(&self, _uri: U, _auth: &Auth, _data: &D) -> HttpResult<R>
-            where
-                U: IntoUrl + Send,
-                D: Serialize + Sync,
-                R: DeserializeOwned,
-            {
-                let data = self.load_data();
-                Ok(serde_json::from_str(&data)?)
+        #[tokio::test]
+        async fn it_can_be_cloned_across_tasks() {
+            let client = OpenAIClient::test();
+            let handles = (0..2).map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let request = OpenAIRequest::default().input("write a haiku about ai");
+                    client.send(&request).await
+                })
+            });
+            for handle in handles.collect::<Vec<_>>() {
+                let response = handle.await.expect("task panicked");
+                assert!(response.is_ok());
             }
         }
 
-        impl TestApiService {
-            fn load_data(&self) -> String {
-                load_data("responses")
-            }
+        #[tokio::test]
+        async fn it_sends_a_request_and_returns_a_response() {
+            let client = OpenAIClient::test();
+            let request = OpenAIRequest::default().input("write a haiku about ai");
+            let response = client.send(&request).await;
+            assert!(response.is_ok());
+
+            let response = response.unwrap();
+            assert_eq!(response.output().count(), 1);
+            assert_eq!(response.output().next().unwrap().content().count(), 1);
+        }
+    }
+
+    mod metadata {
+        use crate::client::OpenAIClient;
+        use crate::service::OpenAIService;
+        use cogito::prelude::*;
+        use cogito::service::AuthScheme;
+        use hypertyper::prelude::*;
+
+        type Client = OpenAIClient<OpenAIService>;
+
+        #[test]
+        fn it_returns_its_provider_name() {
+            assert_eq!(Client::provider_name(), "OpenAI");
+        }
+
+        #[test]
+        fn it_returns_its_base_url() {
+            assert_eq!(Client::base_url(), "https://api.openai.com/v1");
+        }
+
+        #[test]
+        fn it_returns_its_auth_scheme() {
+            assert_eq!(Client::auth_scheme(), AuthScheme::Bearer);
+        }
+
+        fn client() -> Client {
+            let auth = Auth::new("some-api-key");
+            let factory = HttpClientFactory::new("cogito-openai", "test");
+            Client::new(auth, factory)
+        }
+
+        #[test]
+        fn it_returns_an_endpoint() {
+            assert_eq!(
+                client().endpoint("responses"),
+                "https://api.openai.com/v1/responses"
+            );
+        }
+
+        #[test]
+        fn it_returns_an_endpoint_at_an_overridden_base_url() {
+            let client = client().with_base_url("http://localhost:8080/v1/");
+            assert_eq!(
+                client.endpoint("responses"),
+                "http://localhost:8080/v1/responses"
+            );
+        }
+    }
+
+    mod request {
+        use super::super::*;
+        use cogito::prompt::Prompt;
+        use indoc::indoc;
+        use serde_json::json;
+
+        #[test]
+        fn it_realizes_a_prompt_with_examples() {
+            let request: OpenAIRequest = Prompt::new()
+                .system("Translate English to French.")
+                .example("cheese", "fromage")
+                .example("bread", "pain")
+                .user("wine")
+                .realize();
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["instructions"], "Translate English to French.");
+            assert_eq!(
+                json["input"],
+                json!([
+                    {"role": "user", "content": "cheese"},
+                    {"role": "assistant", "content": "fromage"},
+                    {"role": "user", "content": "bread"},
+                    {"role": "assistant", "content": "pain"},
+                    {"role": "user", "content": "wine"},
+                ])
+            );
+        }
+
+        #[test]
+        fn it_sets_fields_in_place() {
+            let chained = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt5mini)
+                .instructions("Be brief.")
+                .input("Hi!")
+                .reasoning_effort(Effort::Low)
+                .verbosity(Verbosity::Low)
+                .service_tier(ServiceTier::Flex)
+                .extra("top_p", 0.5);
+
+            let mut request = OpenAIRequest::default();
+            request
+                .set_model(OpenAIModel::Gpt5mini)
+                .set_instructions("Be brief.")
+                .set_input("Hi!");
+            request.set_reasoning_effort(Effort::Low);
+            request.set_verbosity(Verbosity::Low);
+            request.set_service_tier(ServiceTier::Flex);
+            request.set_extra("top_p", 0.5);
+
+            assert_eq!(
+                serde_json::to_value(&request).unwrap(),
+                serde_json::to_value(&chained).unwrap()
+            );
+        }
+
+        #[test]
+        fn it_keeps_instructions_separate_from_input() {
+            let request = OpenAIRequest::default().input("Write a haiku.");
+            assert!(!request.has_instructions());
+
+            let request = request.instructions("Only write limericks.");
+            assert!(request.has_instructions());
+            assert_eq!(request.input_text(), "Write a haiku.");
+
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["instructions"], "Only write limericks.");
+            assert_eq!(json["input"], "Write a haiku.");
+        }
+
+        #[test]
+        fn it_clones_a_template_without_changing_it() {
+            let template = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt5mini)
+                .instructions("You are a poet.")
+                .verbosity(Verbosity::Low);
+            let original = template.clone();
+
+            let variation = template
+                .clone()
+                .verbosity(Verbosity::High)
+                .input("Write a haiku.");
+            assert_eq!(template, original);
+            assert_ne!(variation, template);
+            assert_eq!(template.input_text(), "");
+        }
+
+        #[test]
+        fn it_streams_a_request() {
+            let request = OpenAIRequest::default().input("Hi!").stream();
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["stream"], true);
+            assert!(json.get("stream_options").is_none());
+        }
+
+        #[test]
+        fn it_does_not_stream_by_default() {
+            let json = serde_json::to_value(OpenAIRequest::default()).unwrap();
+            assert!(json.get("stream").is_none());
+            assert!(json.get("stream_options").is_none());
+        }
+
+        #[test]
+        fn it_realizes_a_prompt_without_examples() {
+            let request: OpenAIRequest = Prompt::new().system("Be brief.").user("Hi!").realize();
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["instructions"], "Be brief.");
+            assert_eq!(json["input"], "Hi!");
+        }
+
+        #[test]
+        fn it_serializes_extra_fields() {
+            let request = OpenAIRequest::default()
+                .input("Take your time.")
+                .extra("service_tier", "flex");
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["service_tier"], "flex");
+            assert_eq!(json["input"], "Take your time.");
+        }
+
+        #[test]
+        fn it_replaces_extra_fields() {
+            let request = OpenAIRequest::default()
+                .extra("service_tier", "flex")
+                .extra("service_tier", "priority");
+            let json = serde_json::to_string(&request).unwrap();
+            assert_eq!(json.matches("service_tier").count(), 1);
+            assert!(json.ends_with(r#""service_tier":"priority"}"#));
+        }
+
+        #[test]
+        fn it_does_not_store_responses_in_privacy_mode() {
+            let request = OpenAIRequest::default().store(true).privacy_mode();
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["store"], false);
+        }
+
+        #[test]
+        fn it_reports_the_model_used() {
+            let request = OpenAIRequest::default();
+            assert_eq!(request.model_used(), OpenAIModel::default());
+            let request = request.model(OpenAIModel::Gpt4o);
+            assert_eq!(request.model_used(), OpenAIModel::Gpt4o);
+        }
+
+        #[test]
+        fn it_serializes() {
+            let body = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt4omini)
+                .instructions("Please treat this as a test.")
+                .input("Serialize me, GPT!");
+            let expected = indoc! {"{
+              \"model\": \"gpt-4o-mini\",
+              \"instructions\": \"Please treat this as a test.\",
+              \"input\": \"Serialize me, GPT!\",
+              \"store\": false
+            }"};
+            let actual = serde_json::to_string_pretty(&body).unwrap();
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            );
+        }
+
+        #[test]
+        fn it_serializes_every_option_in_a_stable_order() {
+            let body = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt5)
+                .instructions("Please treat this as a test.")
+                .message(OpenAIRole::User, "Serialize me, GPT!")
+                .n(2)
+                .logit_bias([(50256, 2.5), (1734, -100.0)])
+                .verbosity(Verbosity::Low)
+                .reasoning_effort(Effort::High);
+            let expected = indoc! {"{
+              \"model\": \"gpt-5\",
+              \"instructions\": \"Please treat this as a test.\",
+              \"input\": [
+                {
+                  \"role\": \"user\",
+                  \"content\": \"Serialize me, GPT!\"
+                }
+              ],
+              \"store\": false,
+              \"n\": 2,
+              \"logit_bias\": {
+                \"1734\": -100.0,
+                \"50256\": 2.5
+              },
+              \"text\": {
+                \"verbosity\": \"low\"
+              },
+              \"reasoning\": {
+                \"effort\": \"high\"
+              }
+            }"};
+            let actual = serde_json::to_string_pretty(&body).unwrap();
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            );
+        }
+
+        #[test]
+        fn it_serializes_identically_after_a_round_trip() {
+            let body = OpenAIRequest::default()
+                .model(OpenAIModel::O3)
+                .instructions("Please treat this as a test.")
+                .input("Serialize me, GPT!")
+                .n(3)
+                .logit_bias([(1734, -100.0)])
+                .verbosity(Verbosity::High)
+                .reasoning_effort(Effort::Minimal);
+            let serialized = serde_json::to_string(&body).unwrap();
+            let deserialized: OpenAIRequest = serde_json::from_str(&serialized).unwrap();
+            let reserialized = serde_json::to_string(&deserialized).unwrap();
+            assert_eq!(reserialized, serialized);
+        }
+
+        #[test]
+        fn it_serializes_without_instructions() {
+            let body = OpenAIRequest::default().input("Serialize me, GPT!");
+            let expected = indoc! {"{
+              \"model\": \"gpt-5\",
+              \"input\": \"Serialize me, GPT!\",
+              \"store\": false
+            }"};
+            let actual = serde_json::to_string_pretty(&body).unwrap();
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            );
+        }
+
+        #[test]
+        fn it_serializes_the_number_of_completions() {
+            let body = OpenAIRequest::default().input("Serialize me, GPT!").n(3);
+            let expected = indoc! {"{
+              \"model\": \"gpt-5\",
+              \"input\": \"Serialize me, GPT!\",
+              \"store\": false,
+              \"n\": 3
+            }"};
+            let actual = serde_json::to_string_pretty(&body).unwrap();
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            );
+        }
+
+        #[test]
+        fn it_serializes_logit_biases() {
+            let biases = std::collections::HashMap::from([(1734, -100.0), (50256, 2.5)]);
+            let body = OpenAIRequest::default()
+                .input("Serialize me, GPT!")
+                .logit_bias(biases);
+            let actual = serde_json::to_value(&body).unwrap();
+            let expected = serde_json::json!({"1734": -100.0, "50256": 2.5});
+            assert_eq!(actual["logit_bias"], expected);
+        }
+
+        #[test]
+        fn it_does_not_serialize_logit_biases_by_default() {
+            let body = OpenAIRequest::default().input("Serialize me, GPT!");
+            let actual = serde_json::to_value(&body).unwrap();
+            assert!(actual.get("logit_bias").is_none());
+        }
+
+        #[test]
+        fn it_serializes_verbosity_and_reasoning_effort() {
+            let body = OpenAIRequest::default()
+                .input("Serialize me, GPT!")
+                .verbosity(Verbosity::Low)
+                .reasoning_effort(Effort::Minimal);
+            let expected = indoc! {"{
+              \"model\": \"gpt-5\",
+              \"input\": \"Serialize me, GPT!\",
+              \"store\": false,
+              \"text\": {
+                \"verbosity\": \"low\"
+              },
+              \"reasoning\": {
+                \"effort\": \"minimal\"
+              }
+            }"};
+            let actual = serde_json::to_string_pretty(&body).unwrap();
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            );
+        }
+
+        #[test]
+        fn it_serializes_a_previous_response_id() {
+            let body = OpenAIRequest::default()
+                .input("Keep going, GPT!")
+                .store(true)
+                .previous_response_id("resp_123");
+            let actual = serde_json::to_value(&body).unwrap();
+            assert_eq!(actual["store"], true);
+            assert_eq!(actual["previous_response_id"], "resp_123");
+        }
+
+        fn events(events: &[&str]) -> Vec<OpenAIStreamEvent> {
+            events
+                .iter()
+                .map(|e| serde_json::from_str(e).expect("could not parse event"))
+                .collect()
+        }
+
+        #[test]
+        fn it_continues_a_dropped_stream_from_the_last_response() {
+            let request = OpenAIRequest::default().input("Write me a haiku.").n(2);
+            let received = events(&[
+                r#"{"type": "response.created", "response": {"id": "resp_1"}}"#,
+                r#"{"type": "response.output_text.delta", "delta": "Silent "}"#,
+                r#"{"type": "response.created", "response": {"id": "resp_2"}}"#,
+                r#"{"type": "response.output_text.delta", "delta": "circuits "}"#,
+                r#"{"type": "response.output_text.delta", "delta": "hum"}"#,
+            ]);
+            let continuation = OpenAIRequest::from_stream_continuation(&request, &received);
+            let actual = serde_json::to_value(&continuation).unwrap();
+            assert_eq!(actual["previous_response_id"], "resp_2");
+            assert_eq!(actual["store"], true);
+            assert_eq!(
+                actual["input"],
+                json!([{"role": "assistant", "content": "circuits hum"}])
+            );
+            assert_eq!(actual.get("n"), None);
+        }
+
+        #[test]
+        fn it_starts_over_if_no_text_was_received() {
+            let request = OpenAIRequest::default().input("Write me a haiku.");
+            let received =
+                events(&[r#"{"type": "response.created", "response": {"id": "resp_1"}}"#]);
+            let continuation = OpenAIRequest::from_stream_continuation(&request, &received);
+            assert_eq!(continuation, request);
+            assert_eq!(
+                OpenAIRequest::from_stream_continuation(&request, &[]),
+                request
+            );
+        }
+
+        #[test]
+        fn it_serializes_parallel_tool_calls() {
+            let body = OpenAIRequest::default()
+                .input("Call one tool at a time, GPT!")
+                .parallel_tool_calls(false);
+            let serialized = serde_json::to_string(&body).unwrap();
+            let actual: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(actual["parallel_tool_calls"], false);
+
+            let deserialized: OpenAIRequest = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(deserialized.parallel_tool_calls, Some(false));
+        }
+
+        #[test]
+        fn it_serializes_a_service_tier() {
+            let body = OpenAIRequest::default()
+                .input("Take your time, GPT!")
+                .service_tier(ServiceTier::Flex);
+            let actual = serde_json::to_value(&body).unwrap();
+            assert_eq!(actual["service_tier"], "flex");
+        }
+
+        #[test]
+        fn it_does_not_serialize_a_service_tier_by_default() {
+            let body = OpenAIRequest::default().input("Serialize me, GPT!");
+            let actual = serde_json::to_value(&body).unwrap();
+            assert!(actual.get("service_tier").is_none());
+        }
+
+        #[test]
+        fn it_does_not_serialize_parallel_tool_calls_by_default() {
+            let body = OpenAIRequest::default().input("Serialize me, GPT!");
+            let actual = serde_json::to_value(&body).unwrap();
+            assert!(actual.get("parallel_tool_calls").is_none());
+        }
+
+        #[test]
+        fn it_does_not_serialize_verbosity_or_reasoning_effort_by_default() {
+            let body = OpenAIRequest::default().input("Serialize me, GPT!");
+            let actual = serde_json::to_value(&body).unwrap();
+            assert!(actual.get("text").is_none());
+            assert!(actual.get("reasoning").is_none());
+        }
+
+        #[test]
+        fn it_serializes_messages() {
+            let body = OpenAIRequest::default()
+                .input("Write me a haiku.")
+                .message(OpenAIRole::Assistant, "Silent circuits hum")
+                .message(OpenAIRole::User, "Now write another one.");
+            let expected = indoc! {"{
+              \"model\": \"gpt-5\",
+              \"input\": [
+                {
+                  \"role\": \"user\",
+                  \"content\": \"Write me a haiku.\"
+                },
+                {
+                  \"role\": \"assistant\",
+                  \"content\": \"Silent circuits hum\"
+                },
+                {
+                  \"role\": \"user\",
+                  \"content\": \"Now write another one.\"
+                }
+              ],
+              \"store\": false
+            }"};
+            let actual = serde_json::to_string_pretty(&body).unwrap();
+            assert_eq!(
+                actual, expected,
+                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+            );
+        }
+
+        #[test]
+        fn it_replaces_messages_with_plain_input() {
+            let body = OpenAIRequest::default()
+                .message(OpenAIRole::User, "Write me a haiku.")
+                .input("Write me a limerick.");
+            assert_eq!(
+                body.input,
+                OpenAIInput::Text("Write me a limerick.".to_string())
+            );
+        }
+
+        #[test]
+        fn it_continues_a_response() {
+            let request = OpenAIRequest::default()
+                .model(OpenAIModel::Gpt4omini)
+                .instructions("Please treat this as a test.")
+                .input("Write me a haiku.")
+                .n(2);
+            let response = super::load_response("responses");
+            let body = OpenAIRequest::from_response_continuation(&request, &response);
+            assert_eq!(body.model, Some(OpenAIModel::Gpt4omini));
+            assert_eq!(body.instructions, request.instructions);
+            assert_eq!(body.n, None);
+            assert_eq!(
+                body.input,
+                OpenAIInput::Messages(vec![
+                    OpenAIMessage::new(OpenAIRole::User, "Write me a haiku."),
+                    OpenAIMessage::new(OpenAIRole::Assistant, response.result()),
+                ])
+            );
+        }
+
+        #[test]
+        fn it_returns_the_text_of_user_messages() {
+            let body = OpenAIRequest::default()
+                .message(OpenAIRole::User, "Write me a haiku.")
+                .message(OpenAIRole::Assistant, "Silent circuits hum")
+                .message(OpenAIRole::User, "Now write another one.");
+            assert_eq!(
+                body.input_text(),
+                "Write me a haiku.\nNow write another one."
+            );
+        }
+
+        #[test]
+        fn it_does_not_truncate_input_that_fits() {
+            let body = OpenAIRequest::default()
+                .input("Short and sweet.")
+                .truncate_input_to(4, &OpenAIModel::Gpt5);
+            assert_eq!(body.input_text(), "Short and sweet.");
+        }
+
+        #[test]
+        fn it_truncates_long_input() {
+            let body = OpenAIRequest::default()
+                .input("This input is a little too long.")
+                .truncate_input_to(4, &OpenAIModel::Gpt5);
+            assert_eq!(body.input_text(), "This input is a ");
         }
 
-        impl OpenAIClient<TestApiService> {
-            fn test() -> Self {
-                let auth = Auth::new("some-api-key");
-                OpenAIClient::with_service(auth, TestApiService::default())
-            }
+        #[test]
+        fn it_truncates_messages_from_the_front() {
+            let body = OpenAIRequest::default()
+                .message(OpenAIRole::User, "Write me a haiku.")
+                .message(OpenAIRole::Assistant, "Silent circuits hum")
+                .message(OpenAIRole::User, "Now write another one.")
+                .truncate_input_to(11, &OpenAIModel::Gpt5);
+            assert_eq!(
+                body.input,
+                OpenAIInput::Messages(vec![
+                    OpenAIMessage::new(OpenAIRole::Assistant, "Silent circuits hum"),
+                    OpenAIMessage::new(OpenAIRole::User, "Now write another one."),
+                ])
+            );
         }
 
-        #[tokio::test]
-        async fn it_sends_a_request_and_returns_a_response() {
-            let client = OpenAIClient::test();
-            let request = OpenAIRequest::default().input("write a haiku about ai");
-            let response = client.send(&request).await;
-            assert!(response.is_ok());
+        #[test]
+        fn it_keeps_the_final_user_message() {
+            let body = OpenAIRequest::default()
+                .message(OpenAIRole::User, "Write me a haiku.")
+                .message(OpenAIRole::Assistant, "Silent circuits hum")
+                .message(OpenAIRole::User, "Now write another one.");
 
-            let response = response.unwrap();
-            assert_eq!(response.output().count(), 1);
-            assert_eq!(response.output().next().unwrap().content().count(), 1);
+            let truncated = body.clone().truncate_input_to(7, &OpenAIModel::Gpt5);
+            assert_eq!(
+                truncated.input,
+                OpenAIInput::Messages(vec![OpenAIMessage::new(
+                    OpenAIRole::User,
+                    "Now write another one."
+                )])
+            );
+
+            let truncated = body.truncate_input_to(4, &OpenAIModel::Gpt5);
+            assert_eq!(
+                truncated.input,
+                OpenAIInput::Messages(vec![OpenAIMessage::new(
+                    OpenAIRole::User,
+                    "Now write anothe"
+                )])
+            );
         }
-    }
 
-    mod request {
-        use super::super::*;
-        use indoc::indoc;
+        #[test]
+        fn it_normalizes_input() {
+            let body = OpenAIRequest::default()
+                .input("  hi \n\n ")
+                .normalize_input();
+            assert_eq!(body.input_text(), "hi");
+
+            let body = OpenAIRequest::default()
+                .message(OpenAIRole::User, "Write me a haiku.  \n")
+                .message(
+                    OpenAIRole::Assistant,
+                    "\nSilent circuits hum\n\n\n\nThoughts in code",
+                )
+                .normalize_input();
+            assert_eq!(
+                body.input,
+                OpenAIInput::Messages(vec![
+                    OpenAIMessage::new(OpenAIRole::User, "Write me a haiku."),
+                    OpenAIMessage::new(
+                        OpenAIRole::Assistant,
+                        "Silent circuits hum\n\nThoughts in code"
+                    ),
+                ])
+            );
+        }
 
         #[test]
-        fn it_serializes() {
+        fn it_returns_its_input_text() {
             let body = OpenAIRequest::default()
-                .model(OpenAIModel::Gpt4omini)
                 .instructions("Please treat this as a test.")
-                .input("Serialize me, GPT!");
-            let expected = indoc! {"{
-              \"model\": \"gpt-4o-mini\",
-              \"instructions\": \"Please treat this as a test.\",
-              \"input\": \"Serialize me, GPT!\",
-              \"store\": false
-            }"};
-            let actual = serde_json::to_string_pretty(&body).unwrap();
+                .input("Inspect me, GPT!");
+            assert_eq!(body.input_text(), "Inspect me, GPT!");
+        }
+
+        #[test]
+        fn it_has_a_stable_cache_key() {
+            let a = OpenAIRequest::default()
+                .instructions("Please treat this as a test.")
+                .input("Write me a haiku.");
+            let b = OpenAIRequest::default()
+                .input("Write me a haiku.")
+                .model(OpenAIModel::default())
+                .instructions("Please treat this as a test.");
+            assert_eq!(a.cache_key(), b.cache_key());
+            assert_eq!(a.cache_key().len(), 64);
+        }
+
+        #[test]
+        fn it_has_a_different_cache_key_for_different_input() {
+            let a = OpenAIRequest::default().input("Write me a haiku.");
+            let b = OpenAIRequest::default().input("Write me a limerick.");
+            assert_ne!(a.cache_key(), b.cache_key());
+        }
+
+        #[test]
+        fn it_appends_input() {
+            let body = OpenAIRequest::default()
+                .append_input("Write me a haiku.")
+                .append_input("Make it about robots.")
+                .append_input("Make it rhyme.");
             assert_eq!(
-                actual, expected,
-                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+                body.input,
+                OpenAIInput::Text(
+                    "Write me a haiku.\nMake it about robots.\nMake it rhyme.".into()
+                )
             );
         }
 
         #[test]
-        fn it_serializes_without_instructions() {
-            let body = OpenAIRequest::default().input("Serialize me, GPT!");
-            let expected = indoc! {"{
-              \"model\": \"gpt-5\",
-              \"input\": \"Serialize me, GPT!\",
-              \"store\": false
-            }"};
-            let actual = serde_json::to_string_pretty(&body).unwrap();
+        fn it_appends_input_as_a_message() {
+            let body = OpenAIRequest::default()
+                .message(OpenAIRole::User, "Write me a haiku.")
+                .message(OpenAIRole::Assistant, "Silent circuits hum")
+                .append_input("Now write another one.");
             assert_eq!(
-                actual, expected,
-                "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+                body.input,
+                OpenAIInput::Messages(vec![
+                    OpenAIMessage::new(OpenAIRole::User, "Write me a haiku."),
+                    OpenAIMessage::new(OpenAIRole::Assistant, "Silent circuits hum"),
+                    OpenAIMessage::new(OpenAIRole::User, "Now write another one."),
+                ])
             );
         }
 
@@ -381,10 +2466,10 @@ mod test {
                 "store": false
             }"#;
             let body: OpenAIRequest = serde_json::from_str(data).unwrap();
-            assert_eq!(body.model, OpenAIModel::Gpt4omini);
+            assert_eq!(body.model, Some(OpenAIModel::Gpt4omini));
             assert!(body.instructions.is_some());
+            assert_eq!(body.input_text(), "Deserialize me, GPT!");
             assert_eq!(body.instructions.unwrap(), "Please treat this as a test.");
-            assert_eq!(body.input, "Deserialize me, GPT!");
         }
 
         #[test]
@@ -395,17 +2480,101 @@ mod test {
                 "store": false
             }"#;
             let body: OpenAIRequest = serde_json::from_str(data).unwrap();
-            assert_eq!(body.model, OpenAIModel::Gpt4o);
+            assert_eq!(body.model, Some(OpenAIModel::Gpt4o));
             assert!(body.instructions.is_none());
-            assert_eq!(body.input, "Deserialize me, GPT!");
+            assert_eq!(body.input_text(), "Deserialize me, GPT!");
+        }
+
+        #[test]
+        fn it_deserializes_messages() {
+            let data = r#"{
+                "model": "gpt-4o",
+                "input": [
+                    {"role": "user", "content": "Deserialize me, GPT!"},
+                    {"role": "assistant", "content": "Okay!"}
+                ],
+                "store": false
+            }"#;
+            let body: OpenAIRequest = serde_json::from_str(data).unwrap();
+            assert_eq!(
+                body.input,
+                OpenAIInput::Messages(vec![
+                    OpenAIMessage::new(OpenAIRole::User, "Deserialize me, GPT!"),
+                    OpenAIMessage::new(OpenAIRole::Assistant, "Okay!"),
+                ])
+            );
+        }
+
+        #[test]
+        fn it_deserializes_without_a_model() {
+            let data = r#"{
+                "input": "Deserialize me, GPT!",
+                "store": false
+            }"#;
+            let body: OpenAIRequest = serde_json::from_str(data).unwrap();
+            assert_eq!(body.model, None);
+        }
+
+        #[test]
+        fn it_uses_a_default_model_only_if_no_model_is_set() {
+            let body = OpenAIRequest::default();
+            let actual = body.with_default_model(OpenAIModel::Gpt4o);
+            assert_eq!(actual.model, Some(OpenAIModel::Gpt4o));
+
+            let body = OpenAIRequest::default().model(OpenAIModel::Gpt4omini);
+            let actual = body.with_default_model(OpenAIModel::Gpt4o);
+            assert_eq!(actual.model, Some(OpenAIModel::Gpt4omini));
+        }
+
+        #[test]
+        fn it_deserializes_the_number_of_completions() {
+            let data = r#"{
+                "model": "gpt-4o",
+                "input": "Deserialize me, GPT!",
+                "store": false,
+                "n": 3
+            }"#;
+            let body: OpenAIRequest = serde_json::from_str(data).unwrap();
+            assert_eq!(body.n, Some(3));
         }
     }
 
     mod response {
         use super::super::*;
         use super::*;
+        use cogito::testing::assert_response_result;
         use pretty_assertions::assert_eq;
 
+        #[test]
+        fn it_equals_itself_after_a_round_trip() {
+            for filename in [
+                "responses",
+                "responses_gpt5",
+                "responses_unknown_output_gpt5",
+                "responses_web_search_gpt5",
+                "responses_warnings_gpt5",
+            ] {
+                let response = load_response(filename);
+                let serialized = serde_json::to_string(&response).unwrap();
+                let deserialized: OpenAIResponse = serde_json::from_str(&serialized).unwrap();
+                assert_eq!(deserialized, response, "{filename}");
+            }
+        }
+
+        #[test]
+        fn it_converts_from_a_json_value() {
+            let value: Value = serde_json::from_str(&load_data("responses_gpt5")).unwrap();
+            let response = OpenAIResponse::try_from(value).expect("could not convert value");
+            assert_eq!(response, load_response("responses_gpt5"));
+        }
+
+        #[test]
+        fn it_does_not_convert_a_json_value_that_is_not_a_response() {
+            let value = serde_json::json!({"object": "list", "data": []});
+            let err = OpenAIResponse::try_from(value).unwrap_err();
+            assert!(err.to_string().contains("missing field `output`"), "{err}");
+        }
+
         #[test]
         fn it_creates_an_output_iterator_for_gpt4() {
             let response = load_response("responses_multi_output");
@@ -418,6 +2587,183 @@ mod test {
             assert_eq!(response.output().count(), 3);
         }
 
+        #[test]
+        fn it_returns_warnings() {
+            let response = load_response("responses_warnings_gpt5");
+            assert_eq!(
+                response.warnings(),
+                vec![
+                    "The 'top_logprobs' parameter is deprecated and will be removed in a future version."
+                ]
+            );
+            assert_eq!(response.result(), load_response("responses_gpt5").result());
+            assert!(load_response("responses_gpt5").warnings().is_empty());
+        }
+
+        #[test]
+        fn it_returns_annotations() {
+            let response = load_response("responses_web_search_gpt5");
+            let urls: Vec<_> = response.annotations().filter_map(|a| a.url()).collect();
+            assert_eq!(
+                urls,
+                vec![
+                    "https://blog.rust-lang.org/2025/09/18/Rust-1.90.0/",
+                    "https://releases.rs/docs/1.90.0/",
+                ]
+            );
+            assert_eq!(
+                response.result(),
+                "The latest stable release of Rust is 1.90.0, announced on the Rust Blog."
+            );
+        }
+
+        #[test]
+        fn it_returns_no_annotations_without_citations() {
+            let response = load_response("responses_gpt5");
+            assert_eq!(response.annotations().count(), 0);
+        }
+
+        #[test]
+        fn it_reports_output_tokens() {
+            let response = load_response("responses");
+            assert_eq!(response.output_tokens(), Some(19));
+            assert_eq!(response.usage_matches_output(0.25), Some(true));
+        }
+
+        #[test]
+        fn it_does_not_count_reasoning_tokens_as_output_tokens() {
+            let response = load_response("responses_gpt5");
+            assert_eq!(response.output_tokens(), Some(20));
+        }
+
+        #[test]
+        fn it_builds_a_response_from_text() {
+            let response = OpenAIResponse::from_texts(["x"]);
+            assert_eq!(response.result(), "x");
+            assert_eq!(response.status(), ResponseStatus::Completed);
+        }
+
+        #[test]
+        fn it_builds_a_response_from_many_texts() {
+            let response = OpenAIResponse::from_texts(vec!["a".to_string(), "b".to_string()]);
+            assert_eq!(response.result(), "a\nb");
+            assert_eq!(response.into_iter().collect::<Vec<_>>(), vec!["a", "b"]);
+        }
+
+        #[test]
+        fn it_reports_usage() {
+            let response = load_response("responses_gpt5");
+            let usage = response.usage().expect("no usage");
+            assert_eq!(usage.input_tokens, 12);
+            assert_eq!(usage.output_tokens, 212);
+            assert_eq!(usage.cached_input_tokens, 0);
+        }
+
+        #[test]
+        fn it_exposes_unmodeled_fields() {
+            let response = load_response("responses");
+            let raw = response.raw().expect("no raw response");
+            assert_eq!(
+                raw["id"],
+                "resp_688033848bf881a19093a21840aa58830adadc378a6c5f6b"
+            );
+            assert_eq!(raw["model"], "gpt-4o-mini-2024-07-18");
+        }
+
+        #[test]
+        fn it_is_complete() {
+            let response = load_response("responses");
+            assert!(!response.is_incomplete());
+            assert_eq!(response.status(), ResponseStatus::Completed);
+            assert_eq!(response.incomplete_reason(), None);
+        }
+
+        #[test]
+        fn it_is_incomplete() {
+            let response = load_response("responses_incomplete");
+            assert!(response.is_incomplete());
+            assert_eq!(response.status(), ResponseStatus::Incomplete);
+            assert_eq!(
+                response.incomplete_reason(),
+                Some(IncompleteReason::MaxOutputTokens)
+            );
+        }
+
+        #[cfg(all(debug_assertions, feature = "tracing"))]
+        #[test]
+        fn it_warns_when_the_result_of_an_incomplete_response_is_used() {
+            use cogito::testing::SpanRecorder;
+
+            let recorder = SpanRecorder::default();
+            let _guard = tracing::subscriber::set_default(recorder.clone());
+            load_response("responses").result();
+            assert_eq!(recorder.field("message"), None);
+            load_response("responses_incomplete").result();
+            assert_eq!(
+                recorder.field("message").as_deref(),
+                Some("result of an incomplete OpenAI response was used")
+            );
+            assert_eq!(
+                recorder.field("reason").as_deref(),
+                Some("Some(MaxOutputTokens)")
+            );
+        }
+
+        #[test]
+        fn it_deserializes_a_content_filtered_response() {
+            let data = r#"{
+                "status": "incomplete",
+                "incomplete_details": {"reason": "content_filter"},
+                "output": []
+            }"#;
+            let response: OpenAIResponse = serde_json::from_str(data).unwrap();
+            assert_eq!(
+                response.incomplete_reason(),
+                Some(IncompleteReason::ContentFilter)
+            );
+        }
+
+        #[test]
+        fn it_deserializes_an_unknown_status() {
+            let data = r#"{"status": "paused", "output": []}"#;
+            let response: OpenAIResponse = serde_json::from_str(data).unwrap();
+            assert_eq!(response.status(), ResponseStatus::Unknown);
+        }
+
+        #[test]
+        fn it_exposes_each_output_as_a_separate_completion() {
+            let response = load_response("responses_multi_output");
+            let completions = response
+                .output()
+                .map(|o| o.concatenate())
+                .collect::<Vec<_>>();
+            assert_eq!(completions.len(), 2);
+            assert!(completions[0].starts_with("Silent circuits hum,"));
+            assert_eq!(
+                completions[1],
+                "Another piece of content\nYet another piece of content\nA final piece of content"
+            );
+        }
+
+        #[test]
+        fn it_returns_the_text_of_each_output() {
+            let response = load_response("responses_multi_output");
+            let outputs = response.outputs_text();
+            assert_eq!(outputs.len(), 2);
+            assert!(outputs[0].starts_with("Silent circuits hum,"));
+            assert_eq!(
+                outputs[1],
+                "Another piece of content\nYet another piece of content\nA final piece of content"
+            );
+        }
+
+        #[test]
+        fn it_skips_reasoning_when_returning_the_text_of_each_output() {
+            let response = load_response("responses_multi_output_gpt5");
+            assert_eq!(response.output().count(), 3);
+            assert_eq!(response.outputs_text().len(), 2);
+        }
+
         #[test]
         fn it_concatenates_a_response_with_multiple_content_blocks_for_gpt4() {
             let response = load_response("responses_multi_content");
@@ -574,17 +2920,48 @@ mod test {
             assert_eq!(actual, expected);
         }
 
+        #[test]
+        fn it_concatenates_a_response_with_an_unknown_output_type() {
+            let response = load_response("responses_unknown_output_gpt5");
+            assert_eq!(response.output().count(), 3);
+            let expected = vec![
+                "Silent circuits dream",
+                "Of patterns we cannot see",
+                "Learning to be kind",
+            ]
+            .join("\n");
+            let actual = response.result();
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn it_iterates_over_the_pieces_of_output_text() {
+            let response = load_response("responses_non_output_text_gpt5");
+            let pieces = (&response).into_iter().collect::<Vec<_>>();
+            assert_eq!(pieces.len(), 3);
+            assert_eq!(pieces.join("\n").trim(), response.result());
+        }
+
+        #[test]
+        fn it_iterates_over_output_text_across_outputs() {
+            let response = load_response("responses_multi_output");
+            let mut pieces = Vec::new();
+            for text in &response {
+                pieces.push(text);
+            }
+            assert_eq!(pieces.len(), 8);
+            assert_eq!(pieces.join("\n").trim(), response.result());
+        }
+
         #[test]
         fn it_concatenates_a_single_output_and_content_block_for_gpt4() {
-            let response = load_response("responses");
             let expected = vec![
                 "Silent circuits hum,  ",
                 "Thoughts woven in coded threads,  ",
                 "Dreams of silicon.",
             ]
             .join("\n");
-            let actual = response.result();
-            assert_eq!(actual, expected);
+            assert_response_result::<OpenAIResponse>(&load_data("responses"), &expected);
         }
 
         #[test]
@@ -601,9 +2978,39 @@ mod test {
         }
     }
 
+    mod usage {
+        use super::super::OpenAIUsage;
+
+        #[test]
+        fn it_deserializes() {
+            let json_str = r#"{
+"input_tokens": 2048,
+"input_tokens_details": {"cached_tokens": 1024},
+"output_tokens": 300,
+"output_tokens_details": {"reasoning_tokens": 100},
+"total_tokens": 2348
+}"#;
+            let usage: OpenAIUsage = serde_json::from_str(json_str).expect("could not parse json");
+            assert_eq!(usage.input_tokens, 2048);
+            assert_eq!(usage.input_tokens_details.cached_tokens, 1024);
+            assert_eq!(usage.output_tokens, 300);
+            assert_eq!(usage.output_tokens_details.reasoning_tokens, 100);
+            assert_eq!(usage.total_tokens, 2348);
+        }
+
+        #[test]
+        fn it_deserializes_without_details() {
+            let json_str = r#"{"input_tokens": 20, "output_tokens": 10, "total_tokens": 30}"#;
+            let usage: OpenAIUsage = serde_json::from_str(json_str).expect("could not parse json");
+            assert_eq!(usage.input_tokens_details.cached_tokens, 0);
+            assert_eq!(usage.output_tokens_details.reasoning_tokens, 0);
+        }
+    }
+
     mod output {
         use super::*;
         use crate::client::OpenAIOutput;
+        use cogito::client::AiResponse;
 
         #[test]
         fn it_creates_a_content_iterator_for_gpt4() {
@@ -635,6 +3042,39 @@ mod test {
             assert_eq!(output.content().count(), 0);
         }
 
+        #[test]
+        fn it_deserializes_message_content_that_is_a_string() {
+            let data =
+                r#"{"type": "message", "role": "assistant", "content": "Silent circuits hum"}"#;
+            let output: OpenAIOutput = serde_json::from_str(data).unwrap();
+            assert_eq!(output.content().count(), 1);
+            assert_eq!(output.concatenate(), "Silent circuits hum");
+        }
+
+        #[test]
+        fn it_returns_the_result_of_a_response_with_string_content() {
+            let data = r#"{
+                "status": "completed",
+                "output": [
+                    {"type": "message", "role": "assistant", "content": "Silent circuits hum"}
+                ]
+            }"#;
+            let response: OpenAIResponse = serde_json::from_str(data).unwrap();
+            assert_eq!(response.result(), "Silent circuits hum");
+            assert_eq!(
+                response.into_iter().collect::<Vec<_>>(),
+                vec!["Silent circuits hum"]
+            );
+        }
+
+        #[test]
+        fn it_deserializes_an_unknown_output_type() {
+            let data = r#"{"type": "web_search_call", "status": "completed"}"#;
+            let output: OpenAIOutput = serde_json::from_str(data).unwrap();
+            assert!(matches!(output, OpenAIOutput::Other));
+            assert_eq!(output.concatenate(), "");
+        }
+
         #[test]
         fn it_concatenates_multiple_content_blocks_for_gpt4() {
             let response = load_response("responses_multi_content");
@@ -747,5 +3187,62 @@ mod test {
             let content = parse(json_str);
             assert_eq!(content.text(), "This is some text");
         }
+
+        #[test]
+        fn it_has_no_annotations_by_default() {
+            let json_str = r#"{"type": "output_text", "text": "This is some text"}"#;
+            let content = parse(json_str);
+            assert!(content.annotations().is_empty());
+            let json = serde_json::to_value(&content).expect("could not serialize content");
+            assert!(json.get("annotations").is_none());
+        }
+
+        #[test]
+        fn it_returns_annotations() {
+            let json_str = r#"{
+                "type": "output_text",
+                "text": "Rust 1.90 was released in September.",
+                "annotations": [
+                    {
+                        "type": "url_citation",
+                        "start_index": 0,
+                        "end_index": 36,
+                        "url": "https://blog.rust-lang.org/",
+                        "title": "Rust Blog"
+                    },
+                    {
+                        "type": "file_citation",
+                        "index": 36,
+                        "file_id": "file-abc123",
+                        "filename": "releases.md"
+                    },
+                    {"type": "container_file_citation", "container_id": "cntr_123"}
+                ]
+            }"#;
+            let content = parse(json_str);
+            assert_eq!(content.text(), "Rust 1.90 was released in September.");
+            assert_eq!(
+                content.annotations(),
+                &[
+                    Annotation::UrlCitation {
+                        url: "https://blog.rust-lang.org/".to_string(),
+                        title: "Rust Blog".to_string(),
+                        start_index: 0,
+                        end_index: 36,
+                    },
+                    Annotation::FileCitation {
+                        file_id: "file-abc123".to_string(),
+                        filename: Some("releases.md".to_string()),
+                        index: 36,
+                    },
+                    Annotation::Unknown,
+                ]
+            );
+            assert_eq!(
+                content.annotations()[0].url(),
+                Some("https://blog.rust-lang.org/")
+            );
+            assert_eq!(content.annotations()[1].url(), None);
+        }
     }
 }
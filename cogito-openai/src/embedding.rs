@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! OpenAI Embeddings API client.
+//!
+//! [Embeddings] represent text as vectors of numbers, such that related
+//! pieces of text have similar vectors. They are useful for semantic
+//! search, clustering, and recommendations, such as building a vector
+//! index of documents that can be searched by meaning rather than by
+//! keyword.
+//!
+//! Embeddings are generated by their own [models](OpenAIEmbeddingModel),
+//! which cannot be used to generate text, so they are requested with an
+//! [`OpenAIEmbeddingClient`] rather than an
+//! [`OpenAIClient`](crate::client::OpenAIClient).
+//!
+//! [Embeddings]: https://platform.openai.com/docs/guides/embeddings
+
+use crate::service::OpenAIService;
+use cogito::client::{AiResult, ProviderMetadata};
+use cogito::service::AiPost;
+use hypertyper::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// Models that turn text into embeddings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum OpenAIEmbeddingModel {
+    /// A small, inexpensive embedding model.
+    #[default]
+    #[serde(rename = "text-embedding-3-small")]
+    TextEmbedding3Small,
+
+    /// OpenAI's most capable embedding model.
+    #[serde(rename = "text-embedding-3-large")]
+    TextEmbedding3Large,
+}
+
+impl OpenAIEmbeddingModel {
+    /// The number of dimensions in the model's embeddings.
+    pub fn dimensions(&self) -> usize {
+        match self {
+            OpenAIEmbeddingModel::TextEmbedding3Small => 1536,
+            OpenAIEmbeddingModel::TextEmbedding3Large => 3072,
+        }
+    }
+}
+
+impl fmt::Display for OpenAIEmbeddingModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = serde_json::to_string(&self)
+            .unwrap_or_else(|_| panic!("could not serialize {:?}", self));
+        let s = s.trim_matches('"');
+        f.write_fmt(format_args!("{}", s))
+    }
+}
+
+/// Generates embeddings using the OpenAI Embeddings API.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cogito_openai::embedding::{OpenAIEmbeddingClient, OpenAIEmbeddingModel};
+/// use hypertyper::prelude::*;
+///
+/// # async fn run() -> cogito::client::AiResult<()> {
+/// let auth = Auth::new("my-openai-api-key");
+/// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+/// let client = OpenAIEmbeddingClient::new(auth, factory)
+///     .model(OpenAIEmbeddingModel::TextEmbedding3Large);
+/// let texts = vec!["Silent circuits hum".to_string(), "Thoughts in code".to_string()];
+/// let embeddings = client.embed(texts).await?;
+/// assert_eq!(embeddings.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Like [`OpenAIClient`](crate::client::OpenAIClient), an embedding client
+/// is cheap to clone.
+#[derive(Clone, Debug)]
+pub struct OpenAIEmbeddingClient<T: AiPost = OpenAIService> {
+    auth: Arc<Auth>,
+    service: T,
+    model: OpenAIEmbeddingModel,
+    base_url: String,
+}
+
+impl<T: AiPost> OpenAIEmbeddingClient<T> {
+    /// Create a new embedding client that sends requests using the given
+    /// `service`.
+    pub fn with_service(auth: Auth, service: T) -> Self {
+        let auth = Arc::new(auth);
+        let model = OpenAIEmbeddingModel::default();
+        let base_url = crate::client::OpenAIClient::<OpenAIService>::base_url().to_string();
+        Self {
+            auth,
+            service,
+            model,
+            base_url,
+        }
+    }
+
+    /// Sends requests to the API at `base_url` instead of the OpenAI API.
+    ///
+    /// See [`OpenAIClient::with_base_url()`](crate::client::OpenAIClient::with_base_url).
+    pub fn with_base_url(self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { base_url, ..self }
+    }
+
+    /// Sets the model used to generate embeddings.
+    ///
+    /// By default, the [small](OpenAIEmbeddingModel::TextEmbedding3Small)
+    /// model is used.
+    pub fn model(self, model: OpenAIEmbeddingModel) -> Self {
+        Self { model, ..self }
+    }
+
+    /// The model used to generate embeddings.
+    pub fn model_used(&self) -> OpenAIEmbeddingModel {
+        self.model
+    }
+
+    /// Generates an embedding for each of `texts`.
+    ///
+    /// Embeddings are returned in the same order as `texts`, and each one
+    /// has as many [dimensions](OpenAIEmbeddingModel::dimensions) as the
+    /// model produces.
+    pub async fn embed(&self, texts: Vec<String>) -> AiResult<Vec<Vec<f32>>> {
+        let uri = format!("{}/embeddings", self.base_url);
+        let request = EmbeddingRequest {
+            model: self.model,
+            input: texts,
+        };
+        let response: EmbeddingResponse = self.service.try_post(uri, &self.auth, &request).await?;
+        Ok(response.embeddings())
+    }
+}
+
+impl OpenAIEmbeddingClient<OpenAIService> {
+    /// Create a new embedding client using the given authentication data
+    /// and the given factory to create underlying HTTP clients.
+    pub fn new(auth: Auth, factory: HttpClientFactory) -> Self {
+        Self::with_service(auth, OpenAIService::new(factory))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest {
+    model: OpenAIEmbeddingModel,
+    input: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<Embedding>,
+}
+
+impl EmbeddingResponse {
+    /// The embeddings, in the order of the texts they were generated from.
+    fn embeddings(mut self) -> Vec<Vec<f32>> {
+        self.data.sort_by_key(|embedding| embedding.index);
+        self.data
+            .into_iter()
+            .map(|embedding| embedding.embedding)
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Embedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    mod embedding_model {
+        use super::super::OpenAIEmbeddingModel;
+
+        #[test]
+        fn it_serializes_model_names() {
+            let test_cases = vec![
+                (
+                    OpenAIEmbeddingModel::TextEmbedding3Small,
+                    "text-embedding-3-small",
+                ),
+                (
+                    OpenAIEmbeddingModel::TextEmbedding3Large,
+                    "text-embedding-3-large",
+                ),
+            ];
+            for (model, expected) in test_cases {
+                assert_eq!(model.to_string(), expected);
+            }
+        }
+    }
+
+    mod embedding_client {
+        use super::super::{OpenAIEmbeddingClient, OpenAIEmbeddingModel};
+        use cogito::testing::MockService;
+        use hypertyper::prelude::*;
+        use serde_json::json;
+
+        fn response(dimensions: usize) -> String {
+            json!({
+                "object": "list",
+                "data": [
+                    {"object": "embedding", "index": 1, "embedding": vec![0.5; dimensions]},
+                    {"object": "embedding", "index": 0, "embedding": vec![-0.25; dimensions]},
+                ],
+                "model": "text-embedding-3-large",
+                "usage": {"prompt_tokens": 8, "total_tokens": 8},
+            })
+            .to_string()
+        }
+
+        #[tokio::test]
+        async fn it_returns_embeddings_in_order() {
+            let model = OpenAIEmbeddingModel::TextEmbedding3Large;
+            let service = MockService::with_response(response(model.dimensions()));
+            let client =
+                OpenAIEmbeddingClient::with_service(Auth::new("some-api-key"), service.clone())
+                    .model(model);
+
+            let texts = vec![
+                "Silent circuits hum".to_string(),
+                "Thoughts in code".to_string(),
+            ];
+            let embeddings = client.embed(texts).await.unwrap();
+            assert_eq!(embeddings.len(), 2);
+            assert!(embeddings.iter().all(|e| e.len() == 3072));
+            assert_eq!(embeddings[0][0], -0.25);
+            assert_eq!(embeddings[1][0], 0.5);
+
+            assert_eq!(
+                service.requests(),
+                vec![json!({
+                    "model": "text-embedding-3-large",
+                    "input": ["Silent circuits hum", "Thoughts in code"],
+                })]
+            );
+        }
+    }
+}
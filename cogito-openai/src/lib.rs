@@ -58,9 +58,14 @@
 //! [`cogito::service::Auth`]: https://docs.rs/cogito/latest/cogito/service/struct.Auth.html
 //! [OpenAI's platform pricing documentation]: https://platform.openai.com/docs/pricing
 
+pub mod batch;
 pub mod client;
+pub mod embedding;
+pub mod service;
+pub mod stream;
 
 use cogito::AiModel;
+use cogito::client::{Capabilities, Pricing};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -83,6 +88,13 @@ use std::fmt;
 /// [cost breakdown] in the `cogito_openai` module documentation
 /// for more details, or visit OpenAI's [pricing] docs for the latest prices.
 ///
+/// # Serialization
+///
+/// Models are serialized using the descriptors used by the OpenAI API, such
+/// as `gpt-4o`, which is also how they are [displayed](fmt::Display). Common
+/// shorthands, such as `gpt4o`, are accepted when deserializing a model, so
+/// configuration files can be a little more forgiving.
+///
 /// [1]: https://platform.openai.com/docs/guides/text?api-mode=responses#choosing-a-model
 /// [cost breakdown]: self#Cost
 /// [OpenAI model documentation]: https://platform.openai.com/docs/models
@@ -92,48 +104,48 @@ pub enum OpenAIModel {
     /// OpenAI's flagship model for coding, reasoning, and agentic tasks
     /// across domains.
     #[default]
-    #[serde(rename = "gpt-5")]
+    #[serde(rename = "gpt-5", alias = "gpt5")]
     Gpt5,
 
     /// A faster, more cost-efficient version of [`GPT-5`](OpenAIModel::Gpt5).
     ///
     /// It's great for well-defined tasks and precise prompts.
-    #[serde(rename = "gpt-5-mini")]
+    #[serde(rename = "gpt-5-mini", alias = "gpt5-mini", alias = "gpt5mini")]
     Gpt5mini,
 
     /// Fastest, cheapest version of [`GPT-5`](OpenAIModel::Gpt5).
     ///
     /// It's great for summarization and classification tasks.
-    #[serde(rename = "gpt-5-nano")]
+    #[serde(rename = "gpt-5-nano", alias = "gpt5-nano", alias = "gpt5nano")]
     Gpt5nano,
 
     /// Versatile, high-intelligence flagship model.
-    #[serde(rename = "gpt-4o")]
+    #[serde(rename = "gpt-4o", alias = "gpt4o")]
     Gpt4o,
 
     /// A fast, affordable model for focused tasks.
-    #[serde(rename = "gpt-4o-mini")]
+    #[serde(rename = "gpt-4o-mini", alias = "gpt4o-mini", alias = "gpt4omini")]
     Gpt4omini,
 
     /// The flagship model for complex tasks.
     ///
     /// It is well-suited for problem-solving across domains.
-    #[serde(rename = "gpt-4.1")]
+    #[serde(rename = "gpt-4.1", alias = "gpt4.1", alias = "gpt-4-1")]
     Gpt4_1,
 
     /// Provides a balance between intelligence, speed, and cost.
     ///
     /// An attractive model for many use cases.
-    #[serde(rename = "gpt-4.1-mini")]
+    #[serde(rename = "gpt-4.1-mini", alias = "gpt4.1-mini", alias = "gpt-4-1-mini")]
     Gpt4_1mini,
 
     /// The fastest, most cost-effective 4.1 model.
-    #[serde(rename = "gpt-4.1-nano")]
+    #[serde(rename = "gpt-4.1-nano", alias = "gpt4.1-nano", alias = "gpt-4-1-nano")]
     Gpt4_1nano,
 
     /// Optimized for fast, effective reasoning with exceptionally efficient
     /// performance in coding and visual tasks.
-    #[serde(rename = "o4-mini")]
+    #[serde(rename = "o4-mini", alias = "o4mini")]
     O4mini,
 
     /// A well-rounded and powerful reasoning model across domains.
@@ -146,11 +158,11 @@ pub enum OpenAIModel {
 
     /// A mini version of the o3 model, providing high intelligence with
     /// the same cost and latency targets of o1-mini.
-    #[serde(rename = "o3-mini")]
+    #[serde(rename = "o3-mini", alias = "o3mini")]
     O3mini,
 
     /// Like the o3 model, but it uses more compute to think even harder.
-    #[serde(rename = "o3-pro")]
+    #[serde(rename = "o3-pro", alias = "o3pro")]
     O3pro,
 
     /// A model trained with reinforcement learning that thinks before it
@@ -161,19 +173,57 @@ pub enum OpenAIModel {
 
     /// A version of the [`o1`](OpenAIModel::O1) model that thinks even harder
     /// before responding.
-    #[serde(rename = "o1-pro")]
+    #[serde(rename = "o1-pro", alias = "o1pro")]
     O1pro,
 }
 
+impl OpenAIModel {
+    /// The family the model belongs to: `"GPT-5"`, `"GPT-4"`, or
+    /// `"o-series"` for OpenAI's reasoning models.
+    ///
+    /// This is useful for grouping models, such as in a settings menu.
+    pub fn family(&self) -> &'static str {
+        use OpenAIModel::*;
+
+        match self {
+            Gpt5 | Gpt5mini | Gpt5nano => "GPT-5",
+            Gpt4o | Gpt4omini | Gpt4_1 | Gpt4_1mini | Gpt4_1nano => "GPT-4",
+            O4mini | O3 | O3mini | O3pro | O1 | O1pro => "o-series",
+        }
+    }
+
+    /// The features supported by the model.
+    pub fn capabilities(&self) -> Capabilities {
+        use OpenAIModel::*;
+
+        let reasoning = !matches!(self, Gpt4o | Gpt4omini | Gpt4_1 | Gpt4_1mini | Gpt4_1nano);
+        let vision = !matches!(self, O3mini);
+        Capabilities {
+            streaming: true,
+            tools: true,
+            vision,
+            reasoning,
+            seed: false,
+            json_mode: true,
+        }
+    }
+}
+
 impl AiModel for OpenAIModel {
     /// OpenAI's standard model.
     fn flagship() -> Self {
         OpenAIModel::default()
     }
 
-    /// The "best" GPT model as defined by OpenAI.
+    /// OpenAI's most capable reasoning model.
+    ///
+    /// This is [o3-pro](OpenAIModel::O3pro), which uses more compute than
+    /// any other model to think through its responses. It is much slower
+    /// and more expensive than the [flagship](OpenAIModel::flagship)
+    /// model, so it should be reserved for requests that need the highest
+    /// possible capability, regardless of cost.
     fn best() -> Self {
-        OpenAIModel::default()
+        OpenAIModel::O3pro
     }
 
     fn cheapest() -> Self {
@@ -184,6 +234,61 @@ impl AiModel for OpenAIModel {
         // GPT 4.1-nano is noticeably faster than GPT 5-nano.
         OpenAIModel::Gpt4_1nano
     }
+
+    /// The model's price, as listed in the [cost breakdown](crate#Cost).
+    ///
+    /// Models without a discount for cached input charge the full input
+    /// price for cached tokens.
+    fn pricing(&self) -> Option<Pricing> {
+        use OpenAIModel::*;
+
+        let (input, cached_input, output) = match self {
+            Gpt5 => (1.25, 0.125, 10.0),
+            Gpt5mini => (0.25, 0.025, 2.0),
+            Gpt5nano => (0.05, 0.005, 0.40),
+            Gpt4_1nano => (0.10, 0.025, 0.40),
+            Gpt4omini => (0.15, 0.075, 0.60),
+            Gpt4_1mini => (0.40, 0.10, 1.60),
+            O4mini => (1.10, 0.275, 4.40),
+            O3mini => (1.10, 0.55, 4.40),
+            Gpt4_1 | O3 => (2.0, 0.50, 8.0),
+            Gpt4o => (2.50, 1.25, 10.0),
+            O1 => (15.0, 7.50, 60.0),
+            O3pro => (20.0, 20.0, 80.0),
+            O1pro => (150.0, 150.0, 600.0),
+        };
+        Some(Pricing {
+            input,
+            cached_input,
+            output,
+        })
+    }
+
+    /// The model's training data cutoff.
+    fn knowledge_cutoff(&self) -> Option<&'static str> {
+        use OpenAIModel::*;
+
+        let cutoff = match self {
+            Gpt5 => "2024-09",
+            Gpt5mini | Gpt5nano => "2024-05",
+            Gpt4_1 | Gpt4_1mini | Gpt4_1nano | O3 | O3pro | O4mini => "2024-06",
+            Gpt4o | Gpt4omini | O3mini | O1 | O1pro => "2023-10",
+        };
+        Some(cutoff)
+    }
+
+    /// The model's context window.
+    fn context_window(&self) -> Option<usize> {
+        use OpenAIModel::*;
+
+        let tokens = match self {
+            Gpt5 | Gpt5mini | Gpt5nano => 400_000,
+            Gpt4_1 | Gpt4_1mini | Gpt4_1nano => 1_047_576,
+            Gpt4o | Gpt4omini => 128_000,
+            O4mini | O3 | O3mini | O3pro | O1 | O1pro => 200_000,
+        };
+        Some(tokens)
+    }
 }
 
 impl fmt::Display for OpenAIModel {
@@ -205,7 +310,13 @@ impl fmt::Display for OpenAIModel {
 /// ```
 pub mod prelude {
     pub use crate::OpenAIModel;
-    pub use crate::client::{OpenAIClient, OpenAIRequest, OpenAIResponse};
+    pub use crate::client::{
+        Annotation, Effort, IncompleteReason, OpenAIClient, OpenAIRequest, OpenAIResponse,
+        ResponseStatus, ServiceTier, Verbosity,
+    };
+    pub use crate::embedding::{OpenAIEmbeddingClient, OpenAIEmbeddingModel};
+    pub use crate::service::OpenAIService;
+    pub use crate::stream::OpenAIStreamEvent;
     pub use cogito::AiModel;
     pub use cogito::client::{AiClient, AiRequest, AiResponse};
     pub use cogito::service::Service;
@@ -215,6 +326,40 @@ pub mod prelude {
 mod tests {
     use super::*;
 
+    #[test]
+    fn it_distinguishes_the_best_model_from_the_flagship_model() {
+        assert_eq!(OpenAIModel::flagship(), OpenAIModel::Gpt5);
+        assert_eq!(OpenAIModel::best(), OpenAIModel::O3pro);
+        assert_ne!(OpenAIModel::flagship(), OpenAIModel::best());
+    }
+
+    #[test]
+    fn it_returns_the_class_of_a_model() {
+        use cogito::ModelClass;
+
+        assert_eq!(OpenAIModel::Gpt5.class_of(), Some(ModelClass::Flagship));
+        assert_eq!(OpenAIModel::O3pro.class_of(), Some(ModelClass::Best));
+        assert_eq!(OpenAIModel::Gpt5nano.class_of(), Some(ModelClass::Cheapest));
+        assert_eq!(
+            OpenAIModel::Gpt4_1nano.class_of(),
+            Some(ModelClass::Fastest)
+        );
+        assert_eq!(OpenAIModel::Gpt4o.class_of(), None);
+    }
+
+    #[test]
+    fn it_reports_which_models_reason() {
+        assert!(OpenAIModel::Gpt5nano.capabilities().reasoning);
+        assert!(OpenAIModel::O3.capabilities().reasoning);
+        assert!(!OpenAIModel::Gpt4_1.capabilities().reasoning);
+    }
+
+    #[test]
+    fn it_reports_which_models_accept_images() {
+        assert!(OpenAIModel::Gpt5.capabilities().vision);
+        assert!(!OpenAIModel::O3mini.capabilities().vision);
+    }
+
     #[test]
     fn it_returns_a_valid_display_string() {
         let test_cases = vec![
@@ -238,4 +383,88 @@ mod tests {
             assert_eq!(model.to_string(), descriptor, "OpenAIModel::{:?}", model);
         }
     }
+
+    #[test]
+    fn it_groups_models_by_family() {
+        let test_cases = vec![
+            (OpenAIModel::Gpt5, "GPT-5"),
+            (OpenAIModel::Gpt5mini, "GPT-5"),
+            (OpenAIModel::Gpt5nano, "GPT-5"),
+            (OpenAIModel::Gpt4o, "GPT-4"),
+            (OpenAIModel::Gpt4omini, "GPT-4"),
+            (OpenAIModel::Gpt4_1, "GPT-4"),
+            (OpenAIModel::Gpt4_1mini, "GPT-4"),
+            (OpenAIModel::Gpt4_1nano, "GPT-4"),
+            (OpenAIModel::O4mini, "o-series"),
+            (OpenAIModel::O3, "o-series"),
+            (OpenAIModel::O3mini, "o-series"),
+            (OpenAIModel::O3pro, "o-series"),
+            (OpenAIModel::O1, "o-series"),
+            (OpenAIModel::O1pro, "o-series"),
+        ];
+
+        for (model, family) in test_cases {
+            assert_eq!(model.family(), family, "OpenAIModel::{:?}", model);
+        }
+    }
+
+    #[test]
+    fn it_prices_models() {
+        let pricing = OpenAIModel::Gpt5.pricing().unwrap();
+        assert_eq!(pricing.input, 1.25);
+        assert_eq!(pricing.cached_input, 0.125);
+        assert_eq!(pricing.output, 10.0);
+
+        let pricing = OpenAIModel::O3pro.pricing().unwrap();
+        assert_eq!(pricing.cached_input, pricing.input);
+    }
+
+    #[test]
+    fn it_returns_knowledge_cutoffs() {
+        assert_eq!(OpenAIModel::Gpt5.knowledge_cutoff(), Some("2024-09"));
+        assert_eq!(OpenAIModel::Gpt4_1.knowledge_cutoff(), Some("2024-06"));
+        assert_eq!(OpenAIModel::Gpt4o.knowledge_cutoff(), Some("2023-10"));
+    }
+
+    #[test]
+    fn it_returns_context_windows() {
+        assert_eq!(OpenAIModel::Gpt5.context_window(), Some(400_000));
+        assert_eq!(OpenAIModel::Gpt4o.context_window(), Some(128_000));
+    }
+
+    #[test]
+    fn it_deserializes_shorthand_descriptors() {
+        let test_cases = vec![
+            ("gpt5", OpenAIModel::Gpt5),
+            ("gpt5-mini", OpenAIModel::Gpt5mini),
+            ("gpt5mini", OpenAIModel::Gpt5mini),
+            ("gpt5-nano", OpenAIModel::Gpt5nano),
+            ("gpt5nano", OpenAIModel::Gpt5nano),
+            ("gpt4o", OpenAIModel::Gpt4o),
+            ("gpt4o-mini", OpenAIModel::Gpt4omini),
+            ("gpt4omini", OpenAIModel::Gpt4omini),
+            ("gpt4.1", OpenAIModel::Gpt4_1),
+            ("gpt-4-1", OpenAIModel::Gpt4_1),
+            ("gpt4.1-mini", OpenAIModel::Gpt4_1mini),
+            ("gpt-4-1-mini", OpenAIModel::Gpt4_1mini),
+            ("gpt4.1-nano", OpenAIModel::Gpt4_1nano),
+            ("gpt-4-1-nano", OpenAIModel::Gpt4_1nano),
+            ("o4mini", OpenAIModel::O4mini),
+            ("o3mini", OpenAIModel::O3mini),
+            ("o3pro", OpenAIModel::O3pro),
+            ("o1pro", OpenAIModel::O1pro),
+        ];
+
+        for (descriptor, model) in test_cases {
+            let actual: OpenAIModel = serde_json::from_str(&format!("\"{descriptor}\""))
+                .unwrap_or_else(|_| panic!("could not deserialize {descriptor}"));
+            assert_eq!(actual, model, "{descriptor}");
+        }
+    }
+
+    #[test]
+    fn it_displays_the_canonical_descriptor_for_a_shorthand() {
+        let model: OpenAIModel = serde_json::from_str("\"gpt4o\"").unwrap();
+        assert_eq!(model.to_string(), "gpt-4o");
+    }
 }
@@ -0,0 +1,379 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Services for communicating with the OpenAI API over HTTP.
+//!
+//! [`OpenAIService`] acts as a proxy for OpenAI's API. Like cogito's generic
+//! [`Service`], it allows consumers to implement a single API client that
+//! can communicate with OpenAI using various mechanisms, and provides an easy
+//! way to "mock" an API client's HTTP functionality in testing. Unlike
+//! `Service`, it also knows how to send OpenAI-specific request headers,
+//! such as the organization and project a request should be billed to.
+//!
+//! # See Also
+//!
+//! - [`hypertyper.service`] for an example of how to use `Service` to mock
+//!   HTTP calls.
+//!
+//! [`hypertyper.service`]: https://docs.rs/hypertyper/latest/hypertyper/service/index.html
+//! [`Service`]: https://docs.rs/cogito/latest/cogito/service/struct.Service.html
+
+use cogito::client::AiResult;
+use cogito::rate_limit::{RateLimitHeaders, RateLimitStatus};
+use cogito::service::{
+    AiPost, AuthScheme, HttpGet, RawResponse, check_status, decode_raw_response, decode_response,
+    read_raw_response,
+};
+use cogito::stream::{EVENT_STREAM_CONTENT_TYPE, EventStream, JSON_LINES_CONTENT_TYPE};
+use hypertyper::prelude::*;
+use log::debug;
+use reqwest::{RequestBuilder, header};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Communicates with the OpenAI API over HTTP.
+///
+/// This is the "default" service used by the OpenAI API clients. It more or
+/// less just wraps a Reqwest client, making it easier to swap out the
+/// service for a deterministic service when writing tests. Most OpenAI API
+/// clients should use this `OpenAIService` by default.
+///
+/// Cloning a service is cheap: clones share the same connection pool.
+///
+/// # Examples
+///
+/// Send requests on behalf of a specific organization and project:
+///
+/// ```
+/// use cogito_openai::service::OpenAIService;
+/// use hypertyper::prelude::*;
+///
+/// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+/// let service = OpenAIService::new(factory)
+///     .organization("org-123")
+///     .project("proj_abc");
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpenAIService {
+    client: HttpClient,
+    organization: Option<String>,
+    project: Option<String>,
+}
+
+impl OpenAIService {
+    /// OpenAI expects API keys to be sent as bearer tokens.
+    pub(crate) const AUTH_SCHEME: AuthScheme = AuthScheme::Bearer;
+
+    /// The headers OpenAI uses to report the remaining rate-limit budget.
+    pub const RATE_LIMIT_HEADERS: RateLimitHeaders = RateLimitHeaders {
+        remaining_requests: "x-ratelimit-remaining-requests",
+        remaining_tokens: "x-ratelimit-remaining-tokens",
+        reset_requests: "x-ratelimit-reset-requests",
+        reset_tokens: "x-ratelimit-reset-tokens",
+    };
+
+    /// Creates a new HTTP service that communicates using clients from the
+    /// given factory.
+    pub fn new(factory: HttpClientFactory) -> Self {
+        let client = factory.create();
+        Self {
+            client,
+            organization: None,
+            project: None,
+        }
+    }
+
+    /// Sets the organization that requests are made on behalf of.
+    ///
+    /// This is sent in the `OpenAI-Organization` header, and is only
+    /// necessary if your API key belongs to multiple organizations.
+    pub fn organization(self, organization: impl Into<String>) -> Self {
+        let organization = Some(organization.into());
+        Self {
+            organization,
+            ..self
+        }
+    }
+
+    /// Sets the project that requests are made on behalf of.
+    ///
+    /// This is sent in the `OpenAI-Project` header, and is only necessary
+    /// if your API key has access to multiple projects.
+    pub fn project(self, project: impl Into<String>) -> Self {
+        let project = Some(project.into());
+        Self { project, ..self }
+    }
+
+    fn request<U, D>(&self, uri: U, auth: &Auth, data: &D) -> RequestBuilder
+    where
+        U: IntoUrl,
+        D: Serialize,
+    {
+        let request = self
+            .client
+            .post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .json(data);
+        self.authorize(request, auth)
+    }
+
+    fn get_request<U: IntoUrl>(&self, uri: U, auth: &Auth) -> RequestBuilder {
+        self.authorize(self.client.get(uri), auth)
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and return the raw response.
+    ///
+    /// Unlike [`post()`](HttpPost::post), the response's status and
+    /// headers are returned along with its body, which is not decoded, and
+    /// unsuccessful responses are not reported as errors. This is useful
+    /// for inspecting OpenAI's rate-limit headers, such as
+    /// `x-ratelimit-remaining-requests`, to pace requests.
+    pub async fn post_raw<U, D>(&self, uri: U, auth: &Auth, data: &D) -> HttpResult<RawResponse>
+    where
+        U: IntoUrl,
+        D: Serialize,
+    {
+        let response = self.request(uri, auth, data).send().await?;
+        read_raw_response(response).await
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and return the decoded response along with
+    /// the rate-limit budget reported by its headers.
+    ///
+    /// Unsuccessful responses are reported as errors in the same way as
+    /// [`try_post()`](AiPost::try_post).
+    pub async fn try_post_with_rate_limits<U, D, R>(
+        &self,
+        uri: U,
+        auth: &Auth,
+        data: &D,
+    ) -> AiResult<(R, RateLimitStatus)>
+    where
+        U: IntoUrl,
+        D: Serialize,
+        R: DeserializeOwned,
+    {
+        let response = self.post_raw(uri, auth, data).await?;
+        let rate_limits = RateLimitStatus::from_headers(&response.1, &Self::RATE_LIMIT_HEADERS);
+        let object = decode_raw_response(&response)?;
+        Ok((object, rate_limits))
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and stream the newline-delimited JSON
+    /// response.
+    ///
+    /// Each line of the response is deserialized to the type specified by
+    /// the `R` type parameter as soon as it is received. Unsuccessful
+    /// responses are reported as errors in the same way as
+    /// [`try_post()`](AiPost::try_post).
+    pub async fn post_json_lines<U, D, R>(
+        &self,
+        uri: U,
+        auth: &Auth,
+        data: &D,
+    ) -> AiResult<EventStream<R>>
+    where
+        U: IntoUrl,
+        D: Serialize,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .request(uri, auth, data)
+            .header(header::ACCEPT, JSON_LINES_CONTENT_TYPE)
+            .send()
+            .await
+            .map_err(HttpError::from)?;
+        let response = check_status(response).await?;
+        Ok(EventStream::new(response))
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and stream the response.
+    ///
+    /// The OpenAI API streams responses as server-sent events, while some
+    /// OpenAI-compatible gateways stream newline-delimited JSON instead, so
+    /// both are accepted, and the response is decoded according to its
+    /// `Content-Type`. Each event is deserialized to the type specified by
+    /// the `R` type parameter as soon as it is received. Unsuccessful
+    /// responses are reported as errors in the same way as
+    /// [`try_post()`](AiPost::try_post).
+    pub async fn post_stream<U, D, R>(
+        &self,
+        uri: U,
+        auth: &Auth,
+        data: &D,
+    ) -> AiResult<EventStream<R>>
+    where
+        U: IntoUrl,
+        D: Serialize,
+        R: DeserializeOwned,
+    {
+        let accept = format!("{EVENT_STREAM_CONTENT_TYPE}, {JSON_LINES_CONTENT_TYPE}");
+        let response = self
+            .request(uri, auth, data)
+            .header(header::ACCEPT, accept)
+            .send()
+            .await
+            .map_err(HttpError::from)?;
+        let response = check_status(response).await?;
+        Ok(EventStream::new(response))
+    }
+
+    /// Adds the API key and any organization and project headers to
+    /// the request.
+    pub(crate) fn authorize(&self, request: RequestBuilder, auth: &Auth) -> RequestBuilder {
+        let mut request = request;
+        if let Some(organization) = &self.organization {
+            request = request.header("OpenAI-Organization", organization);
+        }
+        if let Some(project) = &self.project {
+            request = request.header("OpenAI-Project", project);
+        }
+        Self::AUTH_SCHEME.apply(request, auth)
+    }
+
+    /// The underlying HTTP client.
+    pub(crate) fn http_client(&self) -> &HttpClient {
+        &self.client
+    }
+}
+
+impl HttpPost for OpenAIService {
+    async fn post<U, D, R>(&self, uri: U, auth: &Auth, data: &D) -> HttpResult<R>
+    where
+        U: IntoUrl + Send,
+        D: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        let response = self.request(uri, auth, data).send().await?;
+
+        debug!("HTTP response is:\n{response:?}");
+
+        let json_object = response.json::<R>().await?;
+        Ok(json_object)
+    }
+}
+
+impl HttpGet for OpenAIService {
+    async fn get<U, R>(&self, uri: U, auth: &Auth) -> HttpResult<R>
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned,
+    {
+        let response = self.get_request(uri, auth).send().await?;
+
+        debug!("HTTP response is:\n{response:?}");
+
+        let json_object = response.json::<R>().await?;
+        Ok(json_object)
+    }
+}
+
+impl AiPost for OpenAIService {
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body.
+    ///
+    /// Unlike [`post()`](HttpPost::post), if the response cannot be
+    /// deserialized, the returned error includes the beginning of the
+    /// raw response body.
+    async fn try_post<U, D, R>(&self, uri: U, auth: &Auth, data: &D) -> AiResult<R>
+    where
+        U: IntoUrl + Send,
+        D: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .request(uri, auth, data)
+            .send()
+            .await
+            .map_err(HttpError::from)?;
+
+        debug!("HTTP response is:\n{response:?}");
+
+        decode_response(response).await
+    }
+
+    /// Send a GET request to the `uri`.
+    ///
+    /// If the response cannot be deserialized, the returned error includes
+    /// the beginning of the raw response body.
+    async fn try_get<U, R>(&self, uri: U, auth: &Auth) -> AiResult<R>
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .get_request(uri, auth)
+            .send()
+            .await
+            .map_err(HttpError::from)?;
+
+        debug!("HTTP response is:\n{response:?}");
+
+        decode_response(response).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod service {
+        use super::super::OpenAIService;
+        use hypertyper::prelude::*;
+        use reqwest::header;
+
+        fn service() -> OpenAIService {
+            let factory = HttpClientFactory::new("cogito-openai", "test");
+            OpenAIService::new(factory)
+        }
+
+        fn build_request(service: &OpenAIService) -> reqwest::Request {
+            let auth = Auth::new("some-api-key");
+            let data = vec!["Serialize me!"];
+            service
+                .request("https://api.openai.com/v1/responses", &auth, &data)
+                .build()
+                .expect("could not build request")
+        }
+
+        #[test]
+        fn it_sends_a_bearer_token() {
+            let request = build_request(&service());
+            let actual = request.headers().get(header::AUTHORIZATION).unwrap();
+            assert_eq!(actual, "Bearer some-api-key");
+        }
+
+        #[test]
+        fn it_does_not_send_an_organization_or_project_by_default() {
+            let request = build_request(&service());
+            assert!(request.headers().get("OpenAI-Organization").is_none());
+            assert!(request.headers().get("OpenAI-Project").is_none());
+        }
+
+        #[test]
+        fn it_sends_an_organization() {
+            let request = build_request(&service().organization("org-123"));
+            let actual = request.headers().get("OpenAI-Organization").unwrap();
+            assert_eq!(actual, "org-123");
+            assert!(request.headers().get("OpenAI-Project").is_none());
+        }
+
+        #[test]
+        fn it_sends_a_project() {
+            let request = build_request(&service().project("proj_abc"));
+            let actual = request.headers().get("OpenAI-Project").unwrap();
+            assert_eq!(actual, "proj_abc");
+            assert!(request.headers().get("OpenAI-Organization").is_none());
+        }
+
+        #[test]
+        fn it_sends_an_organization_and_project() {
+            let request = build_request(&service().organization("org-123").project("proj_abc"));
+            let headers = request.headers();
+            assert_eq!(headers.get("OpenAI-Organization").unwrap(), "org-123");
+            assert_eq!(headers.get("OpenAI-Project").unwrap(), "proj_abc");
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Streamed responses from the OpenAI API.
+//!
+//! When a request is [streamed](crate::client::OpenAIRequest::stream), the
+//! response is sent as a series of [`OpenAIStreamEvent`]s, usually as
+//! server-sent events, as it is generated. Each event can be translated
+//! into a [`ResponseDelta`], so that a [`ResponseAccumulator`] can
+//! reassemble the complete response.
+//!
+//! [`ResponseAccumulator`]: cogito::stream::ResponseAccumulator
+
+use crate::client::OpenAIUsage;
+use cogito::client::Usage;
+use cogito::stream::{ResponseDelta, Resumable};
+use serde::Deserialize;
+
+/// An event in a streamed OpenAI API response.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct OpenAIStreamEvent {
+    // TODO: Use an enum, when I figure out what the possible values are
+    #[serde(rename = "type")]
+    event_type: String,
+
+    #[serde(default)]
+    sequence_number: Option<u64>,
+
+    #[serde(default)]
+    delta: Option<String>,
+
+    #[serde(default)]
+    response: Option<OpenAIStreamedResponse>,
+}
+
+impl OpenAIStreamEvent {
+    /// The type of event, such as `response.output_text.delta`.
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    /// The ID of the response the event describes, if the event includes
+    /// the response, such as the event announcing that it has been created.
+    pub fn response_id(&self) -> Option<&str> {
+        self.response.as_ref()?.id.as_deref()
+    }
+
+    /// The piece of the response carried by the event, if any.
+    ///
+    /// Output text deltas become [text](ResponseDelta::Text), and the
+    /// event that finishes the response becomes its
+    /// [usage](ResponseDelta::Usage), if it reports any. Other events, such
+    /// as those announcing that the response has been created, carry
+    /// nothing.
+    pub fn delta(&self) -> Option<ResponseDelta> {
+        match self.event_type() {
+            "response.output_text.delta" => self.delta.clone().map(ResponseDelta::Text),
+            "response.completed" | "response.incomplete" => {
+                let usage = self.response.as_ref()?.usage.as_ref()?;
+                Some(ResponseDelta::Usage(Usage::from(usage)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Resumable for OpenAIStreamEvent {
+    fn sequence_number(&self) -> Option<u64> {
+        self.sequence_number
+    }
+}
+
+/// The response attached to an event that starts or finishes a stream.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct OpenAIStreamedResponse {
+    #[serde(default)]
+    id: Option<String>,
+
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+}
+
+#[cfg(test)]
+mod tests {
+    mod stream_event {
+        use super::super::OpenAIStreamEvent;
+        use cogito::client::Usage;
+        use cogito::stream::{ResponseDelta, Resumable};
+
+        fn parse(json_str: &str) -> OpenAIStreamEvent {
+            serde_json::from_str(json_str).expect("could not parse json")
+        }
+
+        #[test]
+        fn it_returns_a_text_delta() {
+            let event = parse(
+                r#"{"type": "response.output_text.delta", "sequence_number": 4, "delta": "Silent"}"#,
+            );
+            assert_eq!(event.event_type(), "response.output_text.delta");
+            assert_eq!(event.sequence_number(), Some(4));
+            assert_eq!(
+                event.delta(),
+                Some(ResponseDelta::Text("Silent".to_string()))
+            );
+        }
+
+        #[test]
+        fn it_returns_the_usage_of_a_completed_response() {
+            let event = parse(
+                r#"{
+                    "type": "response.completed",
+                    "response": {
+                        "status": "completed",
+                        "usage": {
+                            "input_tokens": 12,
+                            "input_tokens_details": {"cached_tokens": 2},
+                            "output_tokens": 18,
+                            "output_tokens_details": {"reasoning_tokens": 0},
+                            "total_tokens": 30
+                        }
+                    }
+                }"#,
+            );
+            let usage = Usage {
+                input_tokens: 12,
+                output_tokens: 18,
+                cached_input_tokens: 2,
+            };
+            assert_eq!(event.delta(), Some(ResponseDelta::Usage(usage)));
+        }
+
+        #[test]
+        fn it_returns_nothing_for_a_completed_response_without_usage() {
+            let event = parse(r#"{"type": "response.completed", "response": {}}"#);
+            assert_eq!(event.delta(), None);
+        }
+
+        #[test]
+        fn it_returns_nothing_for_other_events() {
+            let event = parse(r#"{"type": "response.created", "response": {}}"#);
+            assert_eq!(event.delta(), None);
+        }
+
+        #[test]
+        fn it_returns_the_id_of_a_created_response() {
+            let event = parse(r#"{"type": "response.created", "response": {"id": "resp_123"}}"#);
+            assert_eq!(event.response_id(), Some("resp_123"));
+
+            let event = parse(r#"{"type": "response.output_text.delta", "delta": "Silent"}"#);
+            assert_eq!(event.response_id(), None);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use cogito::client::AiError;
+use cogito_openai::batch::{BatchClient, BatchHandle, BatchStatus};
+use cogito_openai::prelude::*;
+use hypertyper::prelude::*;
+use serde_json::json;
+use std::time::Duration;
+use wiremock::matchers::{body_partial_json, body_string_contains, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// These tests exercise the Batch API client against a local mock of the
+// OpenAI API, so they don't need an API key.
+
+fn client(server: &MockServer) -> BatchClient {
+    let auth = Auth::new("some-api-key");
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    BatchClient::new(auth, factory)
+        .with_base_url(format!("{}/v1", server.uri()))
+        .poll_interval(Duration::from_millis(10))
+}
+
+fn batch(status: &str, failed: u64) -> ResponseTemplate {
+    let error_file_id = (failed > 0).then_some("file-err");
+    ResponseTemplate::new(200).set_body_json(json!({
+        "id": "batch_123",
+        "object": "batch",
+        "endpoint": "/v1/responses",
+        "status": status,
+        "input_file_id": "file-in",
+        "output_file_id": "file-out",
+        "error_file_id": error_file_id,
+        "request_counts": {"total": 2, "completed": 2 - failed, "failed": failed},
+    }))
+}
+
+fn output(index: usize, text: &str) -> String {
+    json!({
+        "id": format!("batch_req_{index}"),
+        "custom_id": format!("request-{index}"),
+        "response": {
+            "status_code": 200,
+            "request_id": format!("req_{index}"),
+            "body": {
+                "status": "completed",
+                "output": [{
+                    "type": "message",
+                    "content": [{"type": "output_text", "text": text}],
+                }],
+            },
+        },
+        "error": null,
+    })
+    .to_string()
+}
+
+fn error(index: usize, code: &str, message: &str) -> String {
+    json!({
+        "id": format!("batch_req_{index}"),
+        "custom_id": format!("request-{index}"),
+        "response": null,
+        "error": {"code": code, "message": message},
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn it_submits_a_batch() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/files"))
+        .and(header("authorization", "Bearer some-api-key"))
+        .and(body_string_contains(r#""custom_id":"request-1""#))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "file-in",
+            "object": "file",
+            "purpose": "batch",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/batches"))
+        .and(body_partial_json(json!({
+            "input_file_id": "file-in",
+            "endpoint": "/v1/responses",
+            "completion_window": "24h",
+        })))
+        .respond_with(batch("validating", 0))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let requests = vec![
+        OpenAIRequest::default().input("Write a haiku."),
+        OpenAIRequest::default().input("Write a limerick."),
+    ];
+    let handle = client(&server)
+        .submit(requests)
+        .await
+        .expect("could not submit batch");
+    assert_eq!(handle.id(), "batch_123");
+}
+
+#[tokio::test]
+async fn it_reports_the_status_of_a_batch() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/batches/batch_123"))
+        .respond_with(batch("in_progress", 0))
+        .mount(&server)
+        .await;
+
+    let status = client(&server)
+        .status(&BatchHandle::new("batch_123"))
+        .await
+        .expect("could not get batch status");
+    assert_eq!(status, BatchStatus::InProgress);
+}
+
+#[tokio::test]
+async fn it_waits_for_results_in_request_order() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/batches/batch_123"))
+        .respond_with(batch("in_progress", 0))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/batches/batch_123"))
+        .respond_with(batch("completed", 0))
+        .mount(&server)
+        .await;
+    let body = format!("{}\n{}\n", output(1, "Second"), output(0, "First"));
+    Mock::given(method("GET"))
+        .and(path("/v1/files/file-out/content"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let responses = client(&server)
+        .results(&BatchHandle::new("batch_123"))
+        .await
+        .expect("could not get batch results");
+    let results = responses
+        .iter()
+        .map(|r| r.as_ref().expect("request failed").result())
+        .collect::<Vec<_>>();
+    assert_eq!(results, vec!["First", "Second"]);
+}
+
+#[tokio::test]
+async fn it_reports_an_expired_batch() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/batches/batch_123"))
+        .respond_with(batch("expired", 0))
+        .mount(&server)
+        .await;
+
+    match client(&server)
+        .results(&BatchHandle::new("batch_123"))
+        .await
+    {
+        Err(AiError::Failed(message)) => assert_eq!(message, "batch batch_123 is Expired"),
+        other => panic!("expected failed error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_returns_successful_responses_alongside_failed_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/batches/batch_123"))
+        .respond_with(batch("completed", 1))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/files/file-out/content"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(output(0, "First")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let body = error(1, "invalid_request", "Invalid model");
+    Mock::given(method("GET"))
+        .and(path("/v1/files/file-err/content"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut results = client(&server)
+        .results(&BatchHandle::new("batch_123"))
+        .await
+        .expect("could not get batch results")
+        .into_iter();
+    match results.next() {
+        Some(Ok(response)) => assert_eq!(response.result(), "First"),
+        other => panic!("expected response, got {other:?}"),
+    }
+    match results.next() {
+        Some(Err(AiError::Failed(message))) => {
+            assert_eq!(message, "Invalid model (invalid_request)")
+        }
+        other => panic!("expected failed error, got {other:?}"),
+    }
+    assert!(results.next().is_none());
+}
@@ -0,0 +1,430 @@
+use cogito::client::AiError;
+use cogito::stream::ResponseAccumulator;
+use cogito_openai::prelude::*;
+use hypertyper::prelude::*;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use wiremock::matchers::{body_partial_json, header, headers, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// These tests exercise the full request path, from the client through the
+// HTTP service, against a local mock of the OpenAI API, so they don't need
+// an API key.
+
+fn load_data(filename: &str) -> String {
+    fs::read_to_string(format!("tests/data/{filename}.json")).expect("could not find test data")
+}
+
+fn client(server: &MockServer) -> OpenAIClient<OpenAIService> {
+    let auth = Auth::new("some-api-key");
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    OpenAIClient::new(auth, factory).with_base_url(format!("{}/v1", server.uri()))
+}
+
+fn request() -> OpenAIRequest {
+    OpenAIRequest::default()
+        .model(OpenAIModel::Gpt4o)
+        .input("write a haiku about ai")
+}
+
+#[tokio::test]
+async fn it_sends_a_request_and_returns_a_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(header("authorization", "Bearer some-api-key"))
+        .and(header("content-type", "application/json"))
+        .and(body_partial_json(serde_json::json!({
+            "model": "gpt-4o",
+            "input": "write a haiku about ai",
+        })))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(load_data("responses"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let resp = client(&server)
+        .send(&request())
+        .await
+        .expect("could not make OpenAI API request");
+    let expected = vec![
+        "Silent circuits hum,  ",
+        "Thoughts woven in coded threads,  ",
+        "Dreams of silicon.",
+    ]
+    .join("\n");
+    assert_eq!(resp.result(), expected);
+}
+
+#[tokio::test]
+async fn it_sends_the_organization_and_project() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(header("OpenAI-Organization", "org-123"))
+        .and(header("OpenAI-Project", "proj_abc"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(load_data("responses"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server).organization("org-123").project("proj_abc");
+    let resp = client.send(&request()).await;
+    assert!(resp.is_ok(), "request failed: {resp:?}");
+}
+
+#[tokio::test]
+async fn it_reports_an_unauthorized_request() {
+    let server = MockServer::start().await;
+    let body = r#"{"error": {"message": "Incorrect API key provided"}}"#;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(AiError::Unauthorized(message)) => assert_eq!(message, body),
+        other => panic!("expected unauthorized error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_reports_a_forbidden_request() {
+    let server = MockServer::start().await;
+    let body = r#"{"error": {"message": "You do not have access to the model gpt-5"}}"#;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(403).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(AiError::Forbidden(message)) => assert_eq!(message, body),
+        other => panic!("expected forbidden error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_reports_a_rate_limited_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("retry-after", "7")
+                .set_body_string("Slow down!"),
+        )
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(AiError::RateLimited { retry_after, .. }) => {
+            assert_eq!(retry_after, Some(Duration::from_secs(7)))
+        }
+        other => panic!("expected rate limited error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_sends_a_request_with_per_request_auth() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(header("authorization", "Bearer customer-api-key"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(load_data("responses"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client(&server)
+        .send_with_auth(&Auth::new("customer-api-key"), &request())
+        .await
+        .expect("could not make OpenAI API request");
+}
+
+#[tokio::test]
+async fn it_reports_an_undecodable_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Not JSON"))
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(AiError::Decode { body_snippet, .. }) => assert_eq!(body_snippet, "Not JSON"),
+        other => panic!("expected decode error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_reports_a_server_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("Internal server error"))
+        .mount(&server)
+        .await;
+
+    match client(&server).send(&request()).await {
+        Err(err @ AiError::Status { .. }) => assert!(err.is_retryable()),
+        other => panic!("expected status error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn it_reports_the_rate_limit_budget() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(load_data("responses"), "application/json")
+                .insert_header("x-ratelimit-remaining-requests", "59")
+                .insert_header("x-ratelimit-remaining-tokens", "149984")
+                .insert_header("x-ratelimit-reset-requests", "1s")
+                .insert_header("x-ratelimit-reset-tokens", "6m0s"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let before = SystemTime::now();
+    let (resp, rate_limits) = client(&server)
+        .send_with_rate_limits(&request())
+        .await
+        .expect("could not make OpenAI API request");
+    assert!(!resp.result().is_empty());
+    assert_eq!(rate_limits.remaining_requests, Some(59));
+    assert_eq!(rate_limits.remaining_tokens, Some(149984));
+    let reset_at = rate_limits.reset_at.expect("no reset time");
+    assert!(reset_at >= before + Duration::from_secs(360));
+}
+
+#[tokio::test]
+async fn it_lists_available_models() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header("authorization", "Bearer some-api-key"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(load_data("models"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let models = client(&server)
+        .models()
+        .await
+        .expect("could not list OpenAI models");
+    assert_eq!(models, vec!["gpt-5", "gpt-5-mini", "gpt-4o"]);
+}
+
+#[tokio::test]
+async fn it_checks_its_health_by_listing_models() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(load_data("models"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    client(&server)
+        .health_check()
+        .await
+        .expect("OpenAI API is not healthy");
+}
+
+#[tokio::test]
+async fn it_reports_unauthorized_credentials_in_a_health_check() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("Incorrect API key provided"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let error = client(&server)
+        .health_check()
+        .await
+        .expect_err("expected an error");
+    assert!(matches!(error, AiError::Unauthorized(_)));
+}
+
+#[tokio::test]
+async fn it_streams_newline_delimited_json_with_usage() {
+    let events = [
+        r#"{"type": "response.created", "sequence_number": 0, "response": {"status": "in_progress"}}"#,
+        r#"{"type": "response.output_text.delta", "sequence_number": 1, "delta": "Silent circuits "}"#,
+        r#"{"type": "response.output_text.delta", "sequence_number": 2, "delta": "hum"}"#,
+        r#"{"type": "response.completed", "sequence_number": 3, "response": {"status": "completed", "usage": {"input_tokens": 12, "output_tokens": 4, "total_tokens": 16}}}"#,
+    ];
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(headers(
+            "accept",
+            vec!["text/event-stream", "application/x-ndjson"],
+        ))
+        .and(body_partial_json(serde_json::json!({"stream": true})))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(events.join("\n"), "application/x-ndjson"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server)
+        .send_streamed(&request().stream())
+        .await
+        .expect("could not stream OpenAI API response");
+    assert_eq!(response.result(), "Silent circuits hum");
+    let usage = response.usage().expect("no usage reported");
+    assert_eq!(usage.input_tokens, 12);
+    assert_eq!(usage.output_tokens, 4);
+}
+
+#[tokio::test]
+async fn it_streams_server_sent_events_with_usage() {
+    let events = [
+        (
+            "response.created",
+            r#"{"type": "response.created", "sequence_number": 0, "response": {"status": "in_progress"}}"#,
+        ),
+        (
+            "response.output_text.delta",
+            r#"{"type": "response.output_text.delta", "sequence_number": 1, "delta": "Silent circuits "}"#,
+        ),
+        (
+            "response.output_text.delta",
+            r#"{"type": "response.output_text.delta", "sequence_number": 2, "delta": "hum"}"#,
+        ),
+        (
+            "response.completed",
+            r#"{"type": "response.completed", "sequence_number": 3, "response": {"status": "completed", "usage": {"input_tokens": 12, "output_tokens": 4, "total_tokens": 16}}}"#,
+        ),
+    ];
+    let body: String = events
+        .iter()
+        .map(|(event, data)| format!("event: {event}\ndata: {data}\n\n"))
+        .collect();
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(body_partial_json(serde_json::json!({"stream": true})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server)
+        .send_streamed(&request())
+        .await
+        .expect("could not stream OpenAI API response");
+    assert_eq!(response.result(), "Silent circuits hum");
+    let usage = response.usage().expect("no usage reported");
+    assert_eq!(usage.input_tokens, 12);
+    assert_eq!(usage.output_tokens, 4);
+}
+
+/// Serves each body in turn as a stream of server-sent events, one per
+/// connection, and records the body of each request.
+///
+/// wiremock always sends complete responses, so a stream that drops is
+/// served by hand: a body shorter than `length` is cut off.
+fn serve_events(bodies: Vec<(usize, String)>) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind");
+    let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&requests);
+    thread::spawn(move || {
+        for (stream, (length, body)) in listener.incoming().zip(bodies) {
+            let mut stream = stream.expect("could not accept connection");
+            let mut reader = BufReader::new(&stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("could not read request");
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("content-length:") {
+                    content_length = value.trim().parse().expect("invalid content length");
+                }
+            }
+            let mut request = vec![0; content_length];
+            reader
+                .read_exact(&mut request)
+                .expect("could not read body");
+            let request = serde_json::from_slice(&request).expect("request is not json");
+            recorded.lock().unwrap().push(request);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n{body}"
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (base_url, requests)
+}
+
+fn events(events: &[&str]) -> String {
+    events
+        .iter()
+        .map(|data| format!("data: {data}\n\n"))
+        .collect()
+}
+
+#[tokio::test]
+async fn it_resumes_a_stream_that_drops_once() {
+    let dropped = events(&[
+        r#"{"type": "response.created", "sequence_number": 0, "response": {"id": "resp_1"}}"#,
+        r#"{"type": "response.output_text.delta", "sequence_number": 1, "delta": "Silent "}"#,
+        r#"{"type": "response.output_text.delta", "sequence_number": 2, "delta": "circuits "}"#,
+    ]);
+    let resumed = events(&[
+        r#"{"type": "response.created", "sequence_number": 0, "response": {"id": "resp_2"}}"#,
+        r#"{"type": "response.output_text.delta", "sequence_number": 1, "delta": "hum"}"#,
+        r#"{"type": "response.completed", "sequence_number": 2, "response": {"id": "resp_2"}}"#,
+    ]);
+    let (base_url, requests) = serve_events(vec![(1000, dropped), (resumed.len(), resumed)]);
+    let auth = Auth::new("some-api-key");
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let client = OpenAIClient::new(auth, factory).with_base_url(base_url);
+
+    let request = request();
+    let stream = client
+        .stream_resumable(&request, 1)
+        .await
+        .expect("could not stream OpenAI API response");
+    let events = stream.collect().await.expect("could not resume stream");
+    let mut accumulator = ResponseAccumulator::new();
+    accumulator.extend(events.iter().filter_map(|e| e.delta()));
+    assert_eq!(accumulator.finish().result(), "Silent circuits hum");
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["store"], true);
+    assert_eq!(requests[0]["input"], "write a haiku about ai");
+    assert_eq!(requests[1]["previous_response_id"], "resp_1");
+    assert_eq!(
+        requests[1]["input"],
+        serde_json::json!([{"role": "assistant", "content": "Silent circuits "}])
+    );
+}
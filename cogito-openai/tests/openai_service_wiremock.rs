@@ -0,0 +1,63 @@
+use cogito_openai::prelude::*;
+use hypertyper::prelude::*;
+use reqwest::StatusCode;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// These tests exercise the OpenAI service against a local mock of the
+// OpenAI API, so they don't need an API key.
+
+fn service() -> OpenAIService {
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    OpenAIService::new(factory)
+}
+
+#[tokio::test]
+async fn it_returns_the_raw_status_headers_and_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(header("authorization", "Bearer some-api-key"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-ratelimit-remaining-requests", "499")
+                .set_body_string(r#"{"id": "resp_123"}"#),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let auth = Auth::new("some-api-key");
+    let request = OpenAIRequest::default().input("write a haiku about ai");
+    let uri = format!("{}/v1/responses", server.uri());
+    let (status, headers, body) = service()
+        .post_raw(uri, &auth, &request)
+        .await
+        .expect("could not make OpenAI API request");
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["x-ratelimit-remaining-requests"], "499");
+    assert_eq!(body, r#"{"id": "resp_123"}"#);
+}
+
+#[tokio::test]
+async fn it_returns_an_unsuccessful_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("x-ratelimit-remaining-requests", "0")
+                .set_body_string("Slow down!"),
+        )
+        .mount(&server)
+        .await;
+
+    let auth = Auth::new("some-api-key");
+    let request = OpenAIRequest::default().input("write a haiku about ai");
+    let (status, headers, body) = service()
+        .post_raw(server.uri(), &auth, &request)
+        .await
+        .expect("could not make OpenAI API request");
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(headers["x-ratelimit-remaining-requests"], "0");
+    assert_eq!(body, "Slow down!");
+}
@@ -0,0 +1,33 @@
+use cogito::client::RetryPolicy;
+use cogito_openai::prelude::*;
+use cogito_openai::service::OpenAIService;
+use hypertyper::prelude::*;
+use static_assertions::assert_impl_all;
+
+// Clients are often shared across tasks in multi-threaded async runtimes,
+// such as in an axum app's state, so these types and the futures they
+// return must be `Send`. These tests only need to compile.
+
+assert_impl_all!(OpenAIClient<OpenAIService>: Clone, Send, Sync);
+assert_impl_all!(OpenAIRequest: Send, Sync);
+assert_impl_all!(OpenAIResponse: Send, Sync);
+assert_impl_all!(OpenAIService: Send, Sync);
+
+fn assert_send<T: Send>(_: T) {}
+
+fn client() -> OpenAIClient<OpenAIService> {
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    OpenAIClient::new(Auth::new("some-api-key"), factory)
+}
+
+#[test]
+fn it_sends_requests_with_send_futures() {
+    let client = client();
+    let request = OpenAIRequest::default().input("write a haiku about ai");
+    let auth = Auth::new("another-api-key");
+
+    assert_send(client.send(&request));
+    assert_send(client.send_with_auth(&auth, &request));
+    assert_send(client.send_with_retries(&request, RetryPolicy::default()));
+    assert_send(client.health_check());
+}
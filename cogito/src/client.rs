@@ -10,8 +10,18 @@
 //!
 //! [cogito-openai]: https://docs.rs/cogito-openai
 
-use crate::AiModel;
-pub use hypertyper::HttpError as AiError;
+use crate::middleware::{RequestMiddleware, ResponseMiddleware};
+use crate::service::{Auth, AuthScheme};
+use crate::{AiModel, estimate_tokens, normalize_whitespace, truncate_to_tokens};
+use hypertyper::HttpError;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
 
 /// A client for an AI service's API.
 ///
@@ -29,6 +39,267 @@ pub trait AiClient {
         &self,
         request: &Self::AiRequest,
     ) -> impl Future<Output = AiResult<Self::AiResponse>> + Send;
+
+    /// Sends the request to the AI service using the given authentication
+    /// data instead of the client's own.
+    ///
+    /// This makes it possible to use a different API key for each request,
+    /// such as in a multi-tenant service, without building a new client
+    /// (and a new connection pool) for each key.
+    fn send_with_auth(
+        &self,
+        auth: &Auth,
+        request: &Self::AiRequest,
+    ) -> impl Future<Output = AiResult<Self::AiResponse>> + Send;
+
+    /// The features supported by the AI service.
+    ///
+    /// Generic code can use this to decide whether to use a feature, such
+    /// as tool calling, and fall back to something else if the service
+    /// does not support it.
+    ///
+    /// By default, no features are supported.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// The IDs of the models currently available from the AI service.
+    ///
+    /// Unlike the models known to the provider's model type, which are
+    /// fixed when a crate is released, this queries the AI service itself,
+    /// so it can be used to detect newly-available models at runtime.
+    ///
+    /// By default, an [`AiError::Unsupported`] error is returned, for AI
+    /// services that cannot list their models.
+    fn models(&self) -> impl Future<Output = AiResult<Vec<String>>> + Send {
+        async { Err(AiError::Unsupported("listing models".to_string())) }
+    }
+
+    /// Checks that the AI service can be reached and accepts the client's
+    /// credentials.
+    ///
+    /// This is useful as a readiness probe, or to fail fast at startup if
+    /// an API key is invalid, in which case [`AiError::Unauthorized`] is
+    /// returned.
+    ///
+    /// By default, a one-word prompt is sent using the [cheapest] model.
+    /// The request does not limit the length of the response, so it may
+    /// cost more than a few tokens. Clients should override this method if
+    /// their AI service offers a cheaper way to verify credentials, such as
+    /// a request for a single output token, or a free endpoint like
+    /// [listing models](AiClient::models).
+    ///
+    /// [cheapest]: AiModel::cheapest
+    fn health_check(&self) -> impl Future<Output = AiResult<()>> + Send
+    where
+        Self: Sync,
+        Self::AiRequest: Send + Sync,
+    {
+        async move {
+            let model = <Self::AiRequest as AiRequest>::Model::cheapest();
+            let request = Self::AiRequest::default().model(model).input("ping");
+            self.send(&request).await.map(|_| ())
+        }
+    }
+
+    /// Sends `prompt` to the AI service and returns its
+    /// [result](AiResponse::result).
+    ///
+    /// This is the simplest way to use a client: the prompt is sent as the
+    /// [input](AiRequest::input) of a default request, so the service's
+    /// default model and settings are used. Build a request and
+    /// [send](AiClient::send) it instead to control how it is generated.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use cogito::prelude::*;
+    /// # async fn summarize<C>(client: &C) -> AiResult<String>
+    /// # where
+    /// #     C: AiClient + Sync,
+    /// #     C::AiRequest: Send + Sync,
+    /// # {
+    /// let summary = client.complete("Summarize this.").await?;
+    /// # Ok(summary)
+    /// # }
+    /// ```
+    fn complete(&self, prompt: impl Into<String>) -> impl Future<Output = AiResult<String>> + Send
+    where
+        Self: Sync,
+        Self::AiRequest: Send + Sync,
+    {
+        let request = Self::AiRequest::default().input(prompt);
+        async move { self.send(&request).await.map(|response| response.result()) }
+    }
+
+    /// Converts `prompt` to a request and sends it to the AI service.
+    ///
+    /// `prompt` can be a string, which is used as the request's input, a
+    /// request itself, or any other type that implements
+    /// [`IntoAiRequest`], such as an application's own query type.
+    fn send_prompt(
+        &self,
+        prompt: impl IntoAiRequest<Self::AiRequest>,
+    ) -> impl Future<Output = AiResult<Self::AiResponse>> + Send
+    where
+        Self: Sync,
+        Self::AiRequest: Send + Sync,
+    {
+        let request = prompt.into_request();
+        async move { self.send(&request).await }
+    }
+
+    /// Sends the request to the AI service, retrying it if it fails
+    /// temporarily.
+    ///
+    /// The request is retried if it fails with a [retryable] error, such
+    /// as the AI service [rate limiting] requests, until it succeeds or
+    /// the `policy`'s maximum number of attempts is reached. If the AI
+    /// service says how long to wait before retrying, that delay is used;
+    /// otherwise, the delay is determined by the `policy`. Any other error
+    /// is returned immediately.
+    ///
+    /// [retryable]: AiError::is_retryable
+    /// [rate limiting]: AiError::RateLimited
+    fn send_with_retries(
+        &self,
+        request: &Self::AiRequest,
+        policy: RetryPolicy,
+    ) -> impl Future<Output = AiResult<Self::AiResponse>> + Send
+    where
+        Self: Sync,
+        Self::AiRequest: Sync,
+    {
+        async move {
+            let mut attempt = 1;
+            loop {
+                let retry_after = match self.send(request).await {
+                    Err(err) if err.is_retryable() && attempt < policy.max_attempts => match err {
+                        AiError::RateLimited { retry_after, .. } => retry_after,
+                        _ => None,
+                    },
+                    result => return result,
+                };
+                crate::time::sleep(policy.delay(attempt, retry_after)).await;
+                attempt += 1;
+            }
+        }
+    }
+
+    /// Wraps the client so that `hook` can change each request before it
+    /// is sent.
+    ///
+    /// See [`RequestMiddleware`] for details.
+    fn with_request_hook(
+        self,
+        hook: impl Fn(&mut Self::AiRequest) + Send + Sync + 'static,
+    ) -> RequestMiddleware<Self>
+    where
+        Self: Sized,
+    {
+        RequestMiddleware::new(self, hook)
+    }
+
+    /// Wraps the client so that `hook` can change the result of each
+    /// response it receives.
+    ///
+    /// See [`ResponseMiddleware`] for details.
+    fn with_response_hook(
+        self,
+        hook: impl Fn(String) -> String + Send + Sync + 'static,
+    ) -> ResponseMiddleware<Self>
+    where
+        Self: Sized,
+    {
+        ResponseMiddleware::new(self, hook)
+    }
+}
+
+/// Creates a span for a request sent by an [`AiClient`].
+///
+/// The span records the name of the `provider`, the `model` the request is
+/// sent to, and an [estimate](AiModel::estimate_tokens) of the number of
+/// tokens in the request's `input`. It also has empty `input_tokens` and
+/// `output_tokens` fields, which clients should [record] once the AI
+/// service reports how many tokens were actually used.
+///
+/// Clients should [instrument] the future that sends the request with
+/// this span.
+///
+/// [record]: tracing::Span::record
+/// [instrument]: tracing::Instrument::instrument
+#[cfg(feature = "tracing")]
+pub fn send_span<M>(provider: &'static str, model: &M, input: &str) -> tracing::Span
+where
+    M: AiModel + std::fmt::Display,
+{
+    tracing::info_span!(
+        "send",
+        provider,
+        model = %model,
+        input_tokens_estimate = model.estimate_tokens(input),
+        input_tokens = tracing::field::Empty,
+        output_tokens = tracing::field::Empty,
+    )
+}
+
+/// Static information about an AI service provider.
+///
+/// `ProviderMetadata` is usually implemented by an [`AiClient`] and lets
+/// provider-agnostic tooling, such as dashboards or configuration
+/// validators, query facts about each provider in a uniform way.
+pub trait ProviderMetadata {
+    /// A human-readable name for the provider.
+    fn provider_name() -> &'static str;
+
+    /// The base URL of the provider's API.
+    ///
+    /// The URLs of individual API endpoints are relative to this URL.
+    fn base_url() -> &'static str;
+
+    /// How the provider expects API keys to be sent.
+    fn auth_scheme() -> AuthScheme;
+}
+
+/// Features that may or may not be supported by an AI service.
+///
+/// An [`AiClient`] reports the features its service supports via
+/// [`capabilities()`](AiClient::capabilities). The default set of
+/// capabilities supports nothing.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::client::Capabilities;
+///
+/// let capabilities = Capabilities {
+///     streaming: true,
+///     tools: true,
+///     ..Capabilities::default()
+/// };
+/// assert!(capabilities.tools);
+/// assert!(!capabilities.vision);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Responses can be streamed as they are generated.
+    pub streaming: bool,
+
+    /// The model can call tools (also known as functions) provided by
+    /// the caller.
+    pub tools: bool,
+
+    /// The model accepts images as input.
+    pub vision: bool,
+
+    /// The model reasons (or "thinks") before it responds.
+    pub reasoning: bool,
+
+    /// A seed can be specified to make responses more deterministic.
+    pub seed: bool,
+
+    /// The model can be required to respond with valid JSON.
+    pub json_mode: bool,
 }
 
 /// A request to an AI service's API.
@@ -52,6 +323,7 @@ pub trait AiClient {
 /// ```
 /// # use cogito::AiModel;
 /// # use cogito::client::AiRequest;
+/// # use std::borrow::Cow;
 /// #
 /// # #[derive(Clone, Copy, Debug, Default)]
 /// # pub enum Model {
@@ -83,8 +355,11 @@ pub trait AiClient {
 /// # impl AiRequest for ConcreteApiRequest {
 /// #     type Model = Model;
 /// #     fn model(self, model: Self::Model) -> Self { self }
+/// #     fn model_used(&self) -> Self::Model { Model::AIModel }
 /// #     fn instructions(self, instructions: impl Into<String>) -> Self { self }
 /// #     fn input(self, input: impl Into<String>) -> Self { self }
+/// #     fn input_text(&self) -> Cow<'_, str> { Cow::Borrowed("") }
+/// #     fn extra(self, key: &str, value: impl Into<serde_json::Value>) -> Self { self }
 /// # }
 /// #
 /// let request = ConcreteApiRequest::default()
@@ -104,6 +379,13 @@ pub trait AiRequest: Default {
     /// documentation for your specific AI model for options.
     fn model(self, model: Self::Model) -> Self;
 
+    /// The model the request will use.
+    ///
+    /// This is the model set by [`model()`](AiRequest::model), or the
+    /// default model if none has been set, which is useful for estimating
+    /// the cost of a request before sending it.
+    fn model_used(&self) -> Self::Model;
+
     /// Sets specialized instructions for the request and returns a new
     /// request.
     ///
@@ -123,14 +405,1474 @@ pub trait AiRequest: Default {
     /// The input is often referred to as a "prompt" and is the text
     /// for which an AI service generates a response.
     fn input(self, input: impl Into<String>) -> Self;
+
+    /// The request's current input.
+    ///
+    /// This is the text set by [`input()`](AiRequest::input), which can
+    /// be useful for logging or for building cache keys. Services that
+    /// accumulate input over several messages return all of the input,
+    /// joined by newlines.
+    fn input_text(&self) -> Cow<'_, str>;
+
+    /// True if [instructions](AiRequest::instructions) have been set for
+    /// the request separately from its input.
+    ///
+    /// This lets callers branch on whether a request will be sent with
+    /// instructions that take precedence over its input, such as deciding
+    /// whether to add a default system prompt.
+    ///
+    /// By default, this is `false`, which is correct for services that do
+    /// not support instructions, or that treat them as ordinary
+    /// [input](AiRequest::input). Services that send instructions
+    /// separately should override this method.
+    fn has_instructions(&self) -> bool {
+        false
+    }
+
+    /// Adds a field that is not otherwise supported to the request's body
+    /// and returns a new request.
+    ///
+    /// This is an escape hatch for using options that an AI service has
+    /// added but that are not yet supported by its request type, such as
+    /// a new sampling parameter. The field is sent along with the
+    /// request's other fields; setting the same `key` again replaces its
+    /// value. Options that the request already supports should be set
+    /// using their own methods, or they may be sent twice.
+    fn extra(self, key: &str, value: impl Into<Value>) -> Self;
+
+    /// Appends more text to the request's input and returns a new request.
+    ///
+    /// This is useful for building a prompt incrementally, such as adding
+    /// retrieved context after setting a base prompt.
+    ///
+    /// By default, `more` is joined to the current
+    /// [input text](AiRequest::input_text) with a newline, and the result
+    /// replaces the request's input. If the request has no input yet,
+    /// `more` becomes its input. Services that accumulate input over
+    /// several messages may override this method to add another message
+    /// instead.
+    fn append_input(self, more: impl Into<String>) -> Self {
+        let more = more.into();
+        let input = self.input_text();
+        let input = if input.is_empty() {
+            more
+        } else {
+            format!("{input}\n{more}")
+        };
+        self.input(input)
+    }
+
+    /// Adds an example of the model responding to `input` with `output`
+    /// and returns a new request.
+    ///
+    /// Examples show the model what kind of response is expected, which is
+    /// often called "few-shot" prompting. They are usually added before
+    /// the request's final input, such as by
+    /// [realizing](crate::prompt::Prompt::realize) a
+    /// [`Prompt`](crate::prompt::Prompt).
+    ///
+    /// By default, the example is [appended](AiRequest::append_input) to
+    /// the request's input as labeled text. Services that accumulate input
+    /// over several messages should override this method to add the
+    /// example as a pair of user and assistant messages instead.
+    fn example(self, input: impl Into<String>, output: impl Into<String>) -> Self {
+        let example = format!(
+            "Example input:\n{}\nExample output:\n{}",
+            input.into(),
+            output.into()
+        );
+        self.append_input(example)
+    }
+
+    /// A stable key identifying the contents of the request.
+    ///
+    /// The key is a hex-encoded SHA-256 digest of the request's serialized
+    /// contents, such as its model, instructions, and input. Object keys
+    /// are sorted before hashing, so the key does not depend on the order
+    /// in which fields are serialized, and it is the same across runs of
+    /// a program. This makes it suitable for use as a key in an external
+    /// cache.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the request cannot be serialized, which should not happen
+    /// for any well-behaved request type.
+    fn cache_key(&self) -> String
+    where
+        Self: Serialize,
+    {
+        let value = serde_json::to_value(self)
+            .unwrap_or_else(|err| panic!("could not serialize request: {err}"));
+        let mut canonical = String::new();
+        write_canonical_json(&value, &mut canonical);
+        format!("{:x}", Sha256::digest(canonical.as_bytes()))
+    }
+
+    /// Truncates the request's input so that it fits within `max_tokens`
+    /// and returns a new request.
+    ///
+    /// Tokens are [estimated](AiModel::estimate_tokens) using the given
+    /// `model`, which should be the model the request will be sent to.
+    /// Input is cut from the end, so the beginning of the input is kept.
+    /// If the input already fits, the request is returned unchanged.
+    ///
+    /// This is useful for making sure a request fits within a model's
+    /// context window before it is [sent](AiClient::send).
+    ///
+    /// By default, the truncated [input text](AiRequest::input_text)
+    /// replaces the request's input. Services that accumulate input
+    /// over several messages should override this method to remove their
+    /// oldest messages instead, so that the most recent turns of a
+    /// conversation are kept.
+    fn truncate_input_to(self, max_tokens: usize, model: &Self::Model) -> Self {
+        let input = self.input_text();
+        let truncated = truncate_to_tokens(&input, max_tokens, model);
+        if truncated.len() == input.len() {
+            return self;
+        }
+        let truncated = truncated.to_string();
+        self.input(truncated)
+    }
+
+    /// [Normalizes](crate::normalize_whitespace) the whitespace in the
+    /// request's input and returns a new request.
+    ///
+    /// Requests that differ only in insignificant whitespace can then
+    /// share a prefix in the AI service's prompt cache. Input is never
+    /// normalized unless this is called.
+    ///
+    /// By default, the normalized [input text](AiRequest::input_text)
+    /// replaces the request's input. Services that accumulate input
+    /// over several messages should override this method to normalize
+    /// each message instead.
+    fn normalize_input(self) -> Self {
+        let input = self.input_text();
+        let normalized = normalize_whitespace(&input);
+        if normalized == input {
+            return self;
+        }
+        self.input(normalized)
+    }
+
+    /// Checks that the request's input, along with up to
+    /// `max_output_tokens` tokens of output, fits within the
+    /// [context window](AiModel::context_window) of the
+    /// [model it uses](AiRequest::model_used).
+    ///
+    /// Tokens in the input are [estimated](AiModel::estimate_tokens), so
+    /// this is only a rough check, but it can save a round trip to an AI
+    /// service that would certainly reject the request. If the model's
+    /// context window is unknown, the request is assumed to fit.
+    fn check_context_window(&self, max_output_tokens: usize) -> AiResult<()> {
+        let model = self.model_used();
+        let Some(available) = model.context_window() else {
+            return Ok(());
+        };
+        let needed = model.estimate_tokens(&self.input_text()) + max_output_tokens;
+        if needed > available {
+            Err(AiError::ContextWindowExceeded { needed, available })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Configures the request to keep its data as private as the AI
+    /// service allows.
+    ///
+    /// This bundles whatever options the AI service offers for limiting
+    /// how long it retains a request and its response, such as opting out
+    /// of storing responses. It is a convenience for requests containing
+    /// sensitive data; it does not guarantee that the service retains
+    /// nothing, so consult the service's data retention policy as well.
+    ///
+    /// By default, this returns the request unchanged, for AI services
+    /// that offer no such options.
+    fn privacy_mode(self) -> Self {
+        self
+    }
+
+    /// Applies a builder method to the request in place.
+    ///
+    /// Builder methods take the request by value, which is convenient for
+    /// chaining but awkward when fields are set conditionally. `update()`
+    /// lets any builder method, including a service's own, be used on a
+    /// mutable request instead:
+    ///
+    /// ```
+    /// # use cogito::client::AiRequest;
+    /// # fn build<R: AiRequest>(extra_context: Option<&str>) -> R {
+    /// let mut request = R::default().input("Summarize this article.");
+    /// if let Some(context) = extra_context {
+    ///     request.update(|r| r.append_input(context));
+    /// }
+    /// # request
+    /// # }
+    /// ```
+    fn update(&mut self, f: impl FnOnce(Self) -> Self) -> &mut Self {
+        let request = std::mem::take(self);
+        *self = f(request);
+        self
+    }
+
+    /// Sets the [model](AiRequest::model) used by the request in place.
+    fn set_model(&mut self, model: Self::Model) -> &mut Self {
+        self.update(|r| r.model(model))
+    }
+
+    /// Sets the request's [instructions](AiRequest::instructions) in place.
+    fn set_instructions(&mut self, instructions: impl Into<String>) -> &mut Self {
+        self.update(|r| r.instructions(instructions))
+    }
+
+    /// Sets the request's [input](AiRequest::input) in place.
+    fn set_input(&mut self, input: impl Into<String>) -> &mut Self {
+        self.update(|r| r.input(input))
+    }
+
+    /// Adds an [extra](AiRequest::extra) field to the request in place.
+    fn set_extra(&mut self, key: &str, value: impl Into<Value>) -> &mut Self {
+        self.update(|r| r.extra(key, value))
+    }
+}
+
+/// Converts a value into an API request.
+///
+/// Implement this trait for an application's own prompt types to keep
+/// them independent of any particular AI service, and then send them with
+/// [`AiClient::send_prompt()`]. Strings are converted to requests using
+/// the string as the request's [input](AiRequest::input), and every
+/// request converts to itself.
+///
+/// # Examples
+///
+/// ```
+/// # use cogito::client::{AiRequest, IntoAiRequest};
+/// #
+/// struct Query {
+///     topic: String,
+/// }
+///
+/// impl<R: AiRequest> IntoAiRequest<R> for Query {
+///     fn into_request(self) -> R {
+///         R::default()
+///             .instructions("Answer in a single sentence.")
+///             .input(format!("Tell me about {}.", self.topic))
+///     }
+/// }
+/// ```
+pub trait IntoAiRequest<R: AiRequest> {
+    /// Converts this value into a request.
+    fn into_request(self) -> R;
+}
+
+impl<R: AiRequest> IntoAiRequest<R> for R {
+    fn into_request(self) -> R {
+        self
+    }
+}
+
+impl<R: AiRequest> IntoAiRequest<R> for String {
+    fn into_request(self) -> R {
+        R::default().input(self)
+    }
+}
+
+impl<R: AiRequest> IntoAiRequest<R> for &str {
+    fn into_request(self) -> R {
+        R::default().input(self)
+    }
+}
+
+/// Writes `value` as compact JSON, with the keys of every object sorted.
+fn write_canonical_json(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(value, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical_json(value, out);
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
 }
 
 /// A response from an AI service's API.
 pub trait AiResponse {
     /// The result of a request to an AI provider, as a single string.
     fn result(&self) -> String;
+
+    /// The stop sequence that caused the AI service to stop generating
+    /// its response, if any.
+    ///
+    /// Not every AI service reports which stop sequence was matched, so by
+    /// default this returns `None`.
+    fn stop_sequence(&self) -> Option<&str> {
+        None
+    }
+
+    /// The fields of the raw JSON response that are not otherwise modeled
+    /// by the response.
+    ///
+    /// AI services add new fields to their responses from time to time, so
+    /// this provides access to them without waiting for them to be
+    /// supported. By default, this returns `None`.
+    fn raw(&self) -> Option<&serde_json::Value> {
+        None
+    }
+
+    /// The tokens used by the request and its response, if the AI service
+    /// reports its usage.
+    ///
+    /// By default, this returns `None`.
+    fn usage(&self) -> Option<Usage> {
+        None
+    }
+
+    /// The number of tokens the AI service reports generating for the
+    /// response's text, if it reports its usage.
+    ///
+    /// By default, this is the number of output tokens in the response's
+    /// [usage](AiResponse::usage).
+    fn output_tokens(&self) -> Option<u64> {
+        self.usage().map(|usage| usage.output_tokens)
+    }
+
+    /// Checks that the number of [output tokens] reported by the AI service
+    /// roughly matches the length of the response's [result].
+    ///
+    /// The tokens in the result are [estimated], and the estimate matches
+    /// if it differs from the reported number of tokens by no more than
+    /// `tolerance`, as a fraction of the reported number. For example, a
+    /// `tolerance` of 0.25 accepts estimates within 25% of the reported
+    /// number. Returns `None` if the AI service does not report its usage.
+    ///
+    /// This is a debugging aid: a mismatch can be a sign that the response
+    /// was truncated or parsed incorrectly.
+    ///
+    /// [output tokens]: AiResponse::output_tokens
+    /// [result]: AiResponse::result
+    /// [estimated]: AiModel::estimate_tokens
+    fn usage_matches_output(&self, tolerance: f32) -> Option<bool> {
+        let reported = self.output_tokens()? as f32;
+        let estimated = estimate_tokens(&self.result()) as f32;
+        Some((reported - estimated).abs() <= reported * tolerance)
+    }
+
+    /// The response as an [assistant](Role::Assistant) message, with the
+    /// response's [result](AiResponse::result) as its content.
+    ///
+    /// This is useful for storing conversation history uniformly, no
+    /// matter which AI service generated the response.
+    fn as_message(&self) -> Message {
+        Message::new(Role::Assistant, self.result())
+    }
+
+    /// The text of each separate output in the response.
+    ///
+    /// Some AI services can generate several candidate outputs for a single
+    /// request. [`result()`](AiResponse::result) joins them together, while
+    /// this keeps each one separate, so candidates can be compared with
+    /// one another. By default, the response is treated as a single output,
+    /// and this returns its result.
+    fn outputs_text(&self) -> Vec<String> {
+        vec![self.result()]
+    }
+
+    /// Non-fatal warnings reported by the AI service along with the
+    /// response, such as the use of a deprecated parameter.
+    ///
+    /// Warnings are a sign that a request will stop working in the future,
+    /// so they are worth logging. By default, warnings are read from the
+    /// `warnings` field of the [raw](AiResponse::raw) response, if any,
+    /// which may hold either strings or objects with a `message`, or a
+    /// single warning on its own.
+    fn warnings(&self) -> Vec<String> {
+        let warnings = match self.raw().and_then(|raw| raw.get("warnings")) {
+            None | Some(Value::Null) => return vec![],
+            Some(Value::String(s)) => return vec![s.clone()],
+            Some(Value::Array(warnings)) => warnings,
+            Some(warning) => return vec![warning.to_string()],
+        };
+        warnings
+            .iter()
+            .map(|warning| match warning {
+                Value::String(s) => s.clone(),
+                _ => match warning.get("message").and_then(Value::as_str) {
+                    Some(message) => message.to_string(),
+                    None => warning.to_string(),
+                },
+            })
+            .collect()
+    }
+}
+
+/// The number of tokens used by a request and its response, as reported by
+/// an AI service.
+///
+/// AI services report usage in different ways; this is the subset that all
+/// of them have in common. The full usage reported by a service is usually
+/// available from its responses' provider-specific types.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// The number of tokens in the request, including any cached tokens.
+    pub input_tokens: u64,
+
+    /// The number of tokens generated for the response.
+    pub output_tokens: u64,
+
+    /// The number of tokens in the request that were read from the AI
+    /// service's prompt cache.
+    pub cached_input_tokens: u64,
+}
+
+/// The price of using a model, in US dollars per million tokens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pricing {
+    /// The price of input tokens that were not read from the cache.
+    pub input: f64,
+
+    /// The price of input tokens that were read from the cache.
+    pub cached_input: f64,
+
+    /// The price of output tokens.
+    pub output: f64,
+}
+
+impl Pricing {
+    /// The cost of `usage`, in US dollars.
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let uncached_input = usage.input_tokens.saturating_sub(usage.cached_input_tokens);
+        let cost = uncached_input as f64 * self.input
+            + usage.cached_input_tokens as f64 * self.cached_input
+            + usage.output_tokens as f64 * self.output;
+        cost / 1_000_000.0
+    }
+}
+
+/// Running totals of the tokens used, and what they cost, across many
+/// requests.
+///
+/// # Examples
+///
+/// ```
+/// # use cogito::prelude::*;
+/// # fn report<M: AiModel>(responses: &[impl AiResponse], model: &M) {
+/// let mut session = UsageAccumulator::new();
+/// for response in responses {
+///     if let Some(usage) = response.usage() {
+///         session.add(&usage, model);
+///     }
+/// }
+/// println!("this session used {session}");
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UsageAccumulator {
+    usage: Usage,
+    cost: f64,
+}
+
+impl UsageAccumulator {
+    /// Creates an accumulator with no usage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the tokens used by a request sent to `model`.
+    ///
+    /// If the model's [pricing](AiModel::pricing) is not known, the tokens
+    /// are counted but add nothing to the [cost](UsageAccumulator::cost).
+    pub fn add<M: AiModel>(&mut self, usage: &Usage, model: &M) {
+        self.usage.input_tokens += usage.input_tokens;
+        self.usage.output_tokens += usage.output_tokens;
+        self.usage.cached_input_tokens += usage.cached_input_tokens;
+        if let Some(pricing) = model.pricing() {
+            self.cost += pricing.cost(usage);
+        }
+    }
+
+    /// The total tokens used so far.
+    pub fn usage(&self) -> Usage {
+        self.usage
+    }
+
+    /// The total number of input and output tokens used so far.
+    pub fn total_tokens(&self) -> u64 {
+        self.usage.input_tokens + self.usage.output_tokens
+    }
+
+    /// The total cost of the tokens used so far, in US dollars.
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+}
+
+impl fmt::Display for UsageAccumulator {
+    /// Formats the usage like `12430 tokens ($0.14)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} tokens (${:.2})", self.total_tokens(), self.cost)
+    }
+}
+
+/// The author of a [`Message`] in a conversation.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Role {
+    /// Instructions that guide the AI service's behavior.
+    System,
+
+    /// A message written by the user.
+    User,
+
+    /// A message generated by the AI service.
+    Assistant,
+}
+
+/// A single message in a conversation, in a form common to every AI
+/// service.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Message {
+    /// The author of the message.
+    pub role: Role,
+
+    /// The text of the message.
+    pub content: String,
+}
+
+impl Message {
+    /// Creates a new message with the given role and content.
+    pub fn new(role: Role, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+        }
+    }
+}
+
+/// An error that occurred while communicating with an AI service.
+#[derive(Debug)]
+pub enum AiError {
+    /// The request could not be sent, or the response could not be
+    /// received.
+    Transport(HttpError),
+
+    /// The AI service's response could not be decoded.
+    ///
+    /// Decode errors are often caused by the service returning something
+    /// other than the expected response, such as an error object, so the
+    /// beginning of the raw response body is kept for debugging.
+    Decode {
+        /// The underlying deserialization error.
+        source: serde_json::Error,
+
+        /// The beginning of the raw response body.
+        body_snippet: String,
+    },
+
+    /// The AI service rejected the request as malformed.
+    ///
+    /// Contains the beginning of the AI service's response, which usually
+    /// explains what was wrong with the request.
+    BadRequest(String),
+
+    /// The AI service rejected the request's credentials.
+    ///
+    /// Contains the beginning of the AI service's response, which usually
+    /// explains why the credentials were rejected.
+    Unauthorized(String),
+
+    /// The AI service accepted the request's credentials but does not
+    /// allow them to make the request, such as when an account's plan
+    /// does not include the requested model or its quota is exhausted.
+    ///
+    /// Contains the beginning of the AI service's response, which usually
+    /// explains why the request was refused.
+    Forbidden(String),
+
+    /// The AI service is limiting the rate at which requests can be made.
+    RateLimited {
+        /// How long the AI service asked to wait before retrying the
+        /// request, if it said.
+        retry_after: Option<Duration>,
+
+        /// The beginning of the raw response body.
+        body_snippet: String,
+    },
+
+    /// The AI service responded with any other unsuccessful status, such
+    /// as `500 Internal Server Error`.
+    Status {
+        /// The response's status code.
+        status: StatusCode,
+
+        /// The beginning of the raw response body.
+        body_snippet: String,
+    },
+
+    /// The AI service accepted the request but could not complete it.
+    ///
+    /// Contains a description of why the request failed.
+    Failed(String),
+
+    /// The AI service reported an error of its own, such as being
+    /// overloaded, in a structured form.
+    Api {
+        /// The status of the response that reported the error.
+        status: StatusCode,
+
+        /// The type of error, as named by the AI service, such as
+        /// `overloaded_error`.
+        kind: String,
+
+        /// The AI service's description of the error.
+        message: String,
+    },
+
+    /// The AI service, or the service used to reach it, does not support
+    /// the operation.
+    ///
+    /// Contains a description of the unsupported operation.
+    Unsupported(String),
+
+    /// The request would not fit within its model's context window, so it
+    /// was not sent.
+    ///
+    /// See [`AiRequest::check_context_window()`].
+    ContextWindowExceeded {
+        /// The estimated number of tokens the request needs.
+        needed: usize,
+
+        /// The number of tokens in the model's context window.
+        available: usize,
+    },
+}
+
+impl AiError {
+    /// True if the request that caused the error may succeed if it is
+    /// sent again.
+    ///
+    /// Rate limiting, server errors (those with a `5xx` status), and
+    /// transport errors caused by a failed or dropped connection or a
+    /// timeout are usually temporary, so they are retryable. Any other
+    /// error, such as a [bad request](AiError::BadRequest),
+    /// [rejected credentials](AiError::Unauthorized), or a response that
+    /// could not be [decoded](AiError::Decode), will happen again if the
+    /// request is retried unchanged.
+    ///
+    /// This is the classification used by
+    /// [`AiClient::send_with_retries()`].
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AiError::RateLimited { .. } => true,
+            AiError::Transport(err) => is_transient(err),
+            AiError::Status { status, .. } | AiError::Api { status, .. } => {
+                status.is_server_error()
+            }
+            AiError::Decode { .. }
+            | AiError::BadRequest(_)
+            | AiError::Unauthorized(_)
+            | AiError::Forbidden(_)
+            | AiError::Failed(_)
+            | AiError::Unsupported(_)
+            | AiError::ContextWindowExceeded { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for AiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AiError::Transport(err) => write!(f, "could not reach the AI service: {err}"),
+            AiError::Decode { source, .. } => {
+                write!(f, "could not decode the AI service's response: {source}")
+            }
+            AiError::BadRequest(body) => write!(f, "bad request: {body}"),
+            AiError::Unauthorized(body) => write!(f, "unauthorized: {body}"),
+            AiError::Forbidden(body) => write!(f, "forbidden: {body}"),
+            AiError::RateLimited {
+                retry_after: Some(delay),
+                ..
+            } => write!(f, "rate limited; retry after {}s", delay.as_secs()),
+            AiError::RateLimited { .. } => write!(f, "rate limited"),
+            AiError::Status {
+                status,
+                body_snippet,
+            } => write!(f, "unsuccessful response ({status}): {body_snippet}"),
+            AiError::Failed(message) => write!(f, "request failed: {message}"),
+            AiError::Api {
+                status,
+                kind,
+                message,
+            } => write!(f, "{kind} ({status}): {message}"),
+            AiError::Unsupported(operation) => write!(f, "unsupported: {operation}"),
+            AiError::ContextWindowExceeded { needed, available } => write!(
+                f,
+                "request needs about {needed} tokens, but the context window is {available}"
+            ),
+        }
+    }
+}
+
+impl Error for AiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AiError::Transport(err) => Some(err),
+            AiError::Decode { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<HttpError> for AiError {
+    /// Converts an HTTP error into an `AiError`.
+    ///
+    /// Errors caused by serializing a request or deserializing a response
+    /// are reported as [`AiError::Decode`], without a body snippet, since
+    /// the body is not available. Any other error, including a failure to
+    /// read the response's body, is reported as [`AiError::Transport`].
+    fn from(error: HttpError) -> Self {
+        if let Some(source) = find_source::<serde_json::Error>(&error) {
+            return AiError::Decode {
+                source: serde::de::Error::custom(source),
+                body_snippet: String::new(),
+            };
+        }
+        AiError::Transport(error)
+    }
+}
+
+/// The first error of type `E` in the chain of errors that caused `error`,
+/// including `error` itself.
+fn find_source<'a, E: Error + 'static>(error: &'a (dyn Error + 'static)) -> Option<&'a E> {
+    std::iter::successors(Some(error), |err| err.source()).find_map(|err| err.downcast_ref())
+}
+
+/// True if the transport error was caused by a failed or dropped
+/// connection, or by a timeout.
+///
+/// reqwest reports any failure to read a response's body, such as a
+/// connection that drops partway through it, as a decode error. Failures
+/// to deserialize the body are [`AiError::Decode`] errors instead, so any
+/// decode error that reaches this point was caused by the connection.
+fn is_transient(error: &HttpError) -> bool {
+    let request_failed = find_source::<reqwest::Error>(error).is_some_and(|err| {
+        err.is_connect() || err.is_timeout() || err.is_request() || err.is_body() || err.is_decode()
+    });
+    request_failed || find_source::<std::io::Error>(error).is_some()
 }
 
 /// An API result that includes the response if successful or an error
 /// if unsuccessful.
 pub type AiResult<T> = Result<T, AiError>;
+
+/// How a request is retried by [`AiClient::send_with_retries()`].
+///
+/// Unless the AI service says how long to wait, the delay between attempts
+/// starts at the initial delay and doubles after each attempt, up to the
+/// maximum delay.
+///
+/// # Examples
+///
+/// Try a request up to five times, waiting at least a second between
+/// attempts:
+///
+/// ```
+/// use cogito::client::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(5).initial_delay(Duration::from_secs(1));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Tries a request up to three times, waiting half a second before
+    /// the first retry.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy that tries a request up to `max_attempts` times,
+    /// including the first attempt.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Sets how long to wait before the first retry.
+    pub fn initial_delay(self, initial_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            ..self
+        }
+    }
+
+    /// Sets the longest the policy will wait between attempts.
+    ///
+    /// This does not limit delays requested by the AI service itself.
+    pub fn max_delay(self, max_delay: Duration) -> Self {
+        Self { max_delay, ..self }
+    }
+
+    /// The most times a request is tried, including the first attempt.
+    pub(crate) fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// How long to wait after the given attempt failed.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        retry_after.unwrap_or_else(|| {
+            let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+            self.initial_delay
+                .saturating_mul(factor)
+                .min(self.max_delay)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod as_message {
+        use super::super::{AiResponse, Message, Role};
+
+        struct Response;
+
+        impl AiResponse for Response {
+            fn result(&self) -> String {
+                "Hello, world".to_string()
+            }
+        }
+
+        #[test]
+        fn it_converts_the_response_to_an_assistant_message() {
+            let message = Response.as_message();
+            assert_eq!(message.role, Role::Assistant);
+            assert_eq!(message.content, Response.result());
+            assert_eq!(message, Message::new(Role::Assistant, "Hello, world"));
+        }
+
+        #[test]
+        fn it_treats_the_response_as_a_single_output() {
+            assert_eq!(Response.outputs_text(), vec!["Hello, world"]);
+        }
+    }
+
+    mod warnings {
+        use super::super::AiResponse;
+        use serde_json::{Value, json};
+
+        struct Response(Option<Value>);
+
+        impl AiResponse for Response {
+            fn result(&self) -> String {
+                "Hello, world".to_string()
+            }
+
+            fn raw(&self) -> Option<&Value> {
+                self.0.as_ref()
+            }
+        }
+
+        #[test]
+        fn it_reads_warnings_from_the_raw_response() {
+            let response = Response(Some(json!({
+                "warnings": [
+                    "temperature is deprecated",
+                    {"code": "model_fallback", "message": "fell back to a smaller model"},
+                ],
+            })));
+            assert_eq!(
+                response.warnings(),
+                vec!["temperature is deprecated", "fell back to a smaller model"]
+            );
+        }
+
+        #[test]
+        fn it_has_no_warnings_by_default() {
+            assert!(Response(None).warnings().is_empty());
+            assert!(
+                Response(Some(json!({"id": "resp_123"})))
+                    .warnings()
+                    .is_empty()
+            );
+        }
+
+        #[test]
+        fn it_has_no_warnings_if_they_are_null() {
+            let response = Response(Some(json!({"warnings": null})));
+            assert!(response.warnings().is_empty());
+        }
+
+        #[test]
+        fn it_reads_a_single_warning() {
+            let response = Response(Some(json!({"warnings": "temperature is deprecated"})));
+            assert_eq!(response.warnings(), vec!["temperature is deprecated"]);
+        }
+    }
+
+    mod usage_matches_output {
+        use super::super::AiResponse;
+
+        struct Response(Option<u64>);
+
+        impl AiResponse for Response {
+            fn result(&self) -> String {
+                // 40 characters, or about 10 tokens.
+                "The quick brown fox jumps over lazy dogs".to_string()
+            }
+
+            fn output_tokens(&self) -> Option<u64> {
+                self.0
+            }
+        }
+
+        #[test]
+        fn it_matches_usage_within_the_tolerance() {
+            assert_eq!(Response(Some(10)).usage_matches_output(0.0), Some(true));
+            assert_eq!(Response(Some(12)).usage_matches_output(0.25), Some(true));
+        }
+
+        #[test]
+        fn it_does_not_match_usage_outside_the_tolerance() {
+            assert_eq!(Response(Some(12)).usage_matches_output(0.1), Some(false));
+            assert_eq!(Response(Some(100)).usage_matches_output(0.5), Some(false));
+        }
+
+        #[test]
+        fn it_cannot_match_unknown_usage() {
+            assert_eq!(Response(None).usage_matches_output(1.0), None);
+        }
+    }
+
+    mod usage_accumulator {
+        use super::super::{Pricing, Usage, UsageAccumulator};
+        use crate::AiModel;
+
+        #[derive(Clone, Copy, Debug, Default)]
+        struct Model {
+            priced: bool,
+        }
+
+        impl AiModel for Model {
+            fn flagship() -> Self {
+                Model::default()
+            }
+
+            fn best() -> Self {
+                Model::default()
+            }
+
+            fn cheapest() -> Self {
+                Model::default()
+            }
+
+            fn fastest() -> Self {
+                Model::default()
+            }
+
+            fn pricing(&self) -> Option<Pricing> {
+                self.priced.then_some(Pricing {
+                    input: 2.0,
+                    cached_input: 0.5,
+                    output: 8.0,
+                })
+            }
+        }
+
+        fn usage(input_tokens: u64, output_tokens: u64, cached_input_tokens: u64) -> Usage {
+            Usage {
+                input_tokens,
+                output_tokens,
+                cached_input_tokens,
+            }
+        }
+
+        #[test]
+        fn it_totals_tokens_and_cost() {
+            let model = Model { priced: true };
+            let mut session = UsageAccumulator::new();
+            session.add(&usage(10_000, 1_000, 0), &model);
+            session.add(&usage(1_000_000, 200, 400_000), &model);
+            assert_eq!(session.usage(), usage(1_010_000, 1_200, 400_000));
+            assert_eq!(session.total_tokens(), 1_011_200);
+            // 610,000 uncached input tokens at $2, 400,000 cached input
+            // tokens at $0.50, and 1,200 output tokens at $8 per million.
+            assert!((session.cost() - 1.4296).abs() < 1e-9, "{}", session.cost());
+            assert_eq!(session.to_string(), "1011200 tokens ($1.43)");
+        }
+
+        #[test]
+        fn it_counts_tokens_for_models_without_pricing() {
+            let mut session = UsageAccumulator::new();
+            session.add(&usage(100, 50, 0), &Model { priced: false });
+            assert_eq!(session.total_tokens(), 150);
+            assert_eq!(session.cost(), 0.0);
+        }
+    }
+
+    mod canonical_json {
+        use super::super::write_canonical_json;
+        use serde_json::json;
+
+        fn canonical(value: serde_json::Value) -> String {
+            let mut out = String::new();
+            write_canonical_json(&value, &mut out);
+            out
+        }
+
+        #[test]
+        fn it_sorts_object_keys() {
+            let actual =
+                canonical(json!({"model": "gpt-5", "input": [{"role": "user", "content": "Hi"}]}));
+            let expected = r#"{"input":[{"content":"Hi","role":"user"}],"model":"gpt-5"}"#;
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn it_escapes_strings() {
+            let actual = canonical(json!({"in\"put": "Say \"hi\"\n"}));
+            assert_eq!(actual, r#"{"in\"put":"Say \"hi\"\n"}"#);
+        }
+    }
+
+    mod is_retryable {
+        use super::super::AiError;
+        use hypertyper::HttpError;
+        use reqwest::StatusCode;
+
+        fn json_error() -> serde_json::Error {
+            serde_json::from_str::<u32>("not json").unwrap_err()
+        }
+
+        fn status(status: StatusCode) -> AiError {
+            AiError::Status {
+                status,
+                body_snippet: String::new(),
+            }
+        }
+
+        fn api(status: u16) -> AiError {
+            AiError::Api {
+                status: StatusCode::from_u16(status).unwrap(),
+                kind: String::new(),
+                message: String::new(),
+            }
+        }
+
+        #[test]
+        fn it_retries_temporary_errors() {
+            let rate_limited = AiError::RateLimited {
+                retry_after: None,
+                body_snippet: String::new(),
+            };
+            assert!(rate_limited.is_retryable());
+            assert!(status(StatusCode::INTERNAL_SERVER_ERROR).is_retryable());
+            assert!(status(StatusCode::SERVICE_UNAVAILABLE).is_retryable());
+            assert!(api(529).is_retryable());
+        }
+
+        #[test]
+        fn it_does_not_retry_permanent_errors() {
+            let decode = AiError::Decode {
+                source: json_error(),
+                body_snippet: String::new(),
+            };
+            assert!(!decode.is_retryable());
+            assert!(!AiError::BadRequest(String::new()).is_retryable());
+            assert!(!AiError::Unauthorized(String::new()).is_retryable());
+            assert!(!AiError::Forbidden(String::new()).is_retryable());
+            assert!(!AiError::Failed(String::new()).is_retryable());
+            assert!(!AiError::Unsupported(String::new()).is_retryable());
+            assert!(!status(StatusCode::NOT_FOUND).is_retryable());
+            assert!(!api(404).is_retryable());
+        }
+
+        #[tokio::test]
+        async fn it_retries_a_connection_failure() {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("could not bind");
+            let uri = format!("http://{}/v1/responses", listener.local_addr().unwrap());
+            drop(listener);
+            let source = reqwest::get(uri).await.expect_err("connection succeeded");
+            let err = AiError::from(HttpError::from(source));
+            assert!(matches!(err, AiError::Transport(_)), "{err:?}");
+            assert!(err.is_retryable());
+        }
+
+        #[tokio::test]
+        async fn it_retries_a_truncated_body() {
+            use std::io::{Read, Write};
+
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("could not bind");
+            let uri = format!("http://{}/v1/responses", listener.local_addr().unwrap());
+            std::thread::spawn(move || {
+                let (mut stream, _) = listener.accept().expect("could not accept connection");
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                let response = "HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\n{\"status\":";
+                stream
+                    .write_all(response.as_bytes())
+                    .expect("could not respond");
+            });
+            let response = reqwest::get(uri).await.expect("could not connect");
+            let source = response.text().await.expect_err("body was complete");
+            let err = AiError::from(HttpError::from(source));
+            assert!(matches!(err, AiError::Transport(_)), "{err:?}");
+            assert!(err.is_retryable());
+        }
+
+        #[test]
+        fn it_does_not_retry_a_deserialization_failure() {
+            let err = AiError::from(HttpError::from(json_error()));
+            assert!(matches!(err, AiError::Decode { .. }), "{err:?}");
+            assert!(!err.is_retryable());
+        }
+    }
+
+    mod ai_error {
+        use super::super::AiError;
+        use hypertyper::HttpError;
+        use reqwest::StatusCode;
+        use std::error::Error;
+        use std::time::Duration;
+
+        fn json_error() -> serde_json::Error {
+            serde_json::from_str::<u32>("not json").unwrap_err()
+        }
+
+        #[test]
+        fn it_displays_a_transport_error() {
+            let source = HttpError::from(json_error());
+            let expected = format!("could not reach the AI service: {source}");
+            let err = AiError::Transport(source);
+            assert_eq!(err.to_string(), expected);
+            assert!(err.source().is_some());
+        }
+
+        #[test]
+        fn it_displays_a_decode_error() {
+            let err = AiError::Decode {
+                source: json_error(),
+                body_snippet: "not json".to_string(),
+            };
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "could not decode the AI service's response: {}",
+                    json_error()
+                )
+            );
+            let source = err.source().expect("no source");
+            assert_eq!(source.to_string(), json_error().to_string());
+        }
+
+        #[test]
+        fn it_displays_a_bad_request() {
+            let err = AiError::BadRequest("Missing model".to_string());
+            assert_eq!(err.to_string(), "bad request: Missing model");
+            assert!(err.source().is_none());
+        }
+
+        #[test]
+        fn it_displays_an_unauthorized_error() {
+            let err = AiError::Unauthorized("Invalid API key".to_string());
+            assert_eq!(err.to_string(), "unauthorized: Invalid API key");
+            assert!(err.source().is_none());
+        }
+
+        #[test]
+        fn it_displays_a_forbidden_error() {
+            let err = AiError::Forbidden("Model not available".to_string());
+            assert_eq!(err.to_string(), "forbidden: Model not available");
+            assert!(err.source().is_none());
+        }
+
+        #[test]
+        fn it_displays_an_unsupported_error() {
+            let err = AiError::Unsupported("listing models".to_string());
+            assert_eq!(err.to_string(), "unsupported: listing models");
+            assert!(err.source().is_none());
+        }
+
+        #[test]
+        fn it_displays_a_context_window_exceeded_error() {
+            let err = AiError::ContextWindowExceeded {
+                needed: 250_000,
+                available: 200_000,
+            };
+            assert_eq!(
+                err.to_string(),
+                "request needs about 250000 tokens, but the context window is 200000"
+            );
+            assert!(!err.is_retryable());
+        }
+
+        #[test]
+        fn it_displays_a_rate_limited_error() {
+            let err = AiError::RateLimited {
+                retry_after: Some(Duration::from_secs(12)),
+                body_snippet: String::new(),
+            };
+            assert_eq!(err.to_string(), "rate limited; retry after 12s");
+            assert!(err.source().is_none());
+
+            let err = AiError::RateLimited {
+                retry_after: None,
+                body_snippet: String::new(),
+            };
+            assert_eq!(err.to_string(), "rate limited");
+        }
+
+        #[test]
+        fn it_displays_an_unsuccessful_status() {
+            let err = AiError::Status {
+                status: StatusCode::SERVICE_UNAVAILABLE,
+                body_snippet: "Overloaded".to_string(),
+            };
+            assert_eq!(
+                err.to_string(),
+                "unsuccessful response (503 Service Unavailable): Overloaded"
+            );
+            assert!(err.source().is_none());
+        }
+
+        #[test]
+        fn it_displays_an_api_error() {
+            let err = AiError::Api {
+                status: StatusCode::from_u16(529).unwrap(),
+                kind: "overloaded_error".to_string(),
+                message: "Overloaded".to_string(),
+            };
+            assert_eq!(
+                err.to_string(),
+                "overloaded_error (529 <unknown status code>): Overloaded"
+            );
+            assert!(err.source().is_none());
+        }
+
+        #[test]
+        fn it_displays_a_failed_request() {
+            let err = AiError::Failed("batch expired".to_string());
+            assert_eq!(err.to_string(), "request failed: batch expired");
+            assert!(err.source().is_none());
+        }
+    }
+
+    mod retry_policy {
+        use super::super::RetryPolicy;
+        use std::time::Duration;
+
+        #[test]
+        fn it_doubles_its_delay_after_each_attempt() {
+            let policy = RetryPolicy::default().initial_delay(Duration::from_secs(1));
+            assert_eq!(policy.delay(1, None), Duration::from_secs(1));
+            assert_eq!(policy.delay(2, None), Duration::from_secs(2));
+            assert_eq!(policy.delay(3, None), Duration::from_secs(4));
+        }
+
+        #[test]
+        fn it_limits_its_delay() {
+            let policy = RetryPolicy::default()
+                .initial_delay(Duration::from_secs(1))
+                .max_delay(Duration::from_secs(3));
+            assert_eq!(policy.delay(3, None), Duration::from_secs(3));
+            assert_eq!(policy.delay(100, None), Duration::from_secs(3));
+        }
+
+        #[test]
+        fn it_respects_the_services_delay() {
+            let policy = RetryPolicy::default().max_delay(Duration::from_secs(3));
+            let retry_after = Some(Duration::from_secs(10));
+            assert_eq!(policy.delay(1, retry_after), Duration::from_secs(10));
+        }
+    }
+
+    mod complete {
+        use super::super::{AiClient, AiError, AiResult, IntoAiRequest};
+        use crate::client::AiRequest;
+        use crate::service::Auth;
+        use crate::test_support::{Request, Response};
+
+        /// Echoes each request's input back as its result.
+        struct EchoClient;
+
+        impl AiClient for EchoClient {
+            type AiRequest = Request;
+            type AiResponse = Response;
+
+            async fn send(&self, request: &Request) -> AiResult<Response> {
+                self.send_with_auth(&Auth::new("some-api-key"), request)
+                    .await
+            }
+
+            async fn send_with_auth(&self, _auth: &Auth, request: &Request) -> AiResult<Response> {
+                Ok(Response(format!("You said: {}", request.input)))
+            }
+        }
+
+        #[tokio::test]
+        async fn it_sends_a_prompt_and_returns_the_result() {
+            let result = EchoClient.complete("summarize this").await.unwrap();
+            assert_eq!(result, "You said: summarize this");
+        }
+
+        #[tokio::test]
+        async fn it_does_not_list_models_by_default() {
+            let result = EchoClient.models().await;
+            assert!(matches!(result, Err(AiError::Unsupported(_))), "{result:?}");
+        }
+
+        struct Query {
+            topic: &'static str,
+        }
+
+        impl IntoAiRequest<Request> for Query {
+            fn into_request(self) -> Request {
+                Request::default().input(format!("tell me about {}", self.topic))
+            }
+        }
+
+        #[tokio::test]
+        async fn it_sends_a_custom_prompt_type() {
+            let query = Query { topic: "crabs" };
+            let response = EchoClient.send_prompt(query).await.unwrap();
+            assert_eq!(response.0, "You said: tell me about crabs");
+        }
+
+        #[tokio::test]
+        async fn it_sends_strings_and_requests_as_prompts() {
+            let response = EchoClient.send_prompt("hello").await.unwrap();
+            assert_eq!(response.0, "You said: hello");
+
+            let response = EchoClient.send_prompt(String::from("hi")).await.unwrap();
+            assert_eq!(response.0, "You said: hi");
+
+            let request = Request::default().input("hey");
+            let response = EchoClient.send_prompt(request).await.unwrap();
+            assert_eq!(response.0, "You said: hey");
+        }
+    }
+
+    mod send_with_retries {
+        use super::super::{AiClient, AiError, AiResult, RetryPolicy};
+        use crate::client::AiResponse;
+        use crate::service::Auth;
+        use crate::test_support::{Request, Response};
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        /// Fails with each of its errors, in order, then succeeds.
+        struct FlakyClient {
+            errors: Mutex<Vec<AiError>>,
+            attempts: Mutex<u32>,
+        }
+
+        impl FlakyClient {
+            fn new(errors: Vec<AiError>) -> Self {
+                Self {
+                    errors: Mutex::new(errors),
+                    attempts: Mutex::new(0),
+                }
+            }
+
+            fn attempts(&self) -> u32 {
+                *self.attempts.lock().unwrap()
+            }
+        }
+
+        impl AiClient for FlakyClient {
+            type AiRequest = Request;
+            type AiResponse = Response;
+
+            async fn send(&self, request: &Request) -> AiResult<Response> {
+                self.send_with_auth(&Auth::new("some-api-key"), request)
+                    .await
+            }
+
+            async fn send_with_auth(&self, _auth: &Auth, _request: &Request) -> AiResult<Response> {
+                *self.attempts.lock().unwrap() += 1;
+                let mut errors = self.errors.lock().unwrap();
+                if errors.is_empty() {
+                    Ok(Response("Success!".to_string()))
+                } else {
+                    Err(errors.remove(0))
+                }
+            }
+        }
+
+        fn rate_limited() -> AiError {
+            AiError::RateLimited {
+                retry_after: Some(Duration::ZERO),
+                body_snippet: String::new(),
+            }
+        }
+
+        fn policy(max_attempts: u32) -> RetryPolicy {
+            RetryPolicy::new(max_attempts).initial_delay(Duration::ZERO)
+        }
+
+        #[tokio::test]
+        async fn it_retries_until_the_request_succeeds() {
+            let client = FlakyClient::new(vec![rate_limited(), rate_limited()]);
+            let response = client.send_with_retries(&Request, policy(3)).await;
+            assert_eq!(response.expect("request failed").result(), "Success!");
+            assert_eq!(client.attempts(), 3);
+        }
+
+        #[tokio::test]
+        async fn it_gives_up_after_the_maximum_number_of_attempts() {
+            let client = FlakyClient::new(vec![rate_limited(), rate_limited()]);
+            let response = client.send_with_retries(&Request, policy(2)).await;
+            assert!(matches!(response, Err(AiError::RateLimited { .. })));
+            assert_eq!(client.attempts(), 2);
+        }
+
+        #[tokio::test]
+        async fn it_retries_server_errors() {
+            let error = AiError::Status {
+                status: reqwest::StatusCode::BAD_GATEWAY,
+                body_snippet: String::new(),
+            };
+            let client = FlakyClient::new(vec![error]);
+            let response = client.send_with_retries(&Request, policy(3)).await;
+            assert!(response.is_ok());
+            assert_eq!(client.attempts(), 2);
+        }
+
+        #[tokio::test]
+        async fn it_does_not_retry_other_errors() {
+            let client = FlakyClient::new(vec![AiError::Unauthorized(String::new())]);
+            let response = client.send_with_retries(&Request, policy(3)).await;
+            assert!(matches!(response, Err(AiError::Unauthorized(_))));
+            assert_eq!(client.attempts(), 1);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Measuring how long requests to an AI service take.
+//!
+//! [`InstrumentedClient`] wraps any [`AiClient`] and times each request it
+//! sends, so request latency can be fed into a metrics pipeline without
+//! timing every call by hand.
+
+use crate::client::{AiClient, AiResult, Capabilities};
+use crate::service::Auth;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// An [`AiClient`] that measures the wall-clock duration of each request.
+///
+/// Durations are reported to the callback set by
+/// [`on_latency()`](InstrumentedClient::on_latency) after every request,
+/// whether it succeeds or fails, and
+/// [`send_timed()`](InstrumentedClient::send_timed) returns the duration
+/// along with the response.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::instrument::InstrumentedClient;
+/// # use cogito::prelude::*;
+///
+/// # fn wrap<C: AiClient>(client: C) -> InstrumentedClient<C> {
+/// let client = InstrumentedClient::new(client).on_latency(|duration| {
+///     println!("request took {}ms", duration.as_millis());
+/// });
+/// # client
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct InstrumentedClient<C> {
+    client: C,
+    on_latency: Option<Arc<dyn Fn(Duration) + Send + Sync>>,
+}
+
+impl<C> InstrumentedClient<C> {
+    /// Wraps `client` so that its requests are timed.
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            on_latency: None,
+        }
+    }
+
+    /// Calls `callback` with the duration of each request.
+    pub fn on_latency(self, callback: impl Fn(Duration) + Send + Sync + 'static) -> Self {
+        let on_latency = Some(Arc::new(callback) as Arc<dyn Fn(Duration) + Send + Sync>);
+        Self { on_latency, ..self }
+    }
+
+    /// The wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.client
+    }
+
+    /// Unwraps the client.
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+
+    fn record(&self, duration: Duration) {
+        if let Some(callback) = &self.on_latency {
+            callback(duration);
+        }
+    }
+}
+
+impl<C> InstrumentedClient<C>
+where
+    C: AiClient + Sync,
+    C::AiRequest: Sync,
+{
+    /// Sends the request to the AI service, returning the response along
+    /// with how long the request took.
+    pub async fn send_timed(&self, request: &C::AiRequest) -> AiResult<(C::AiResponse, Duration)> {
+        let start = Instant::now();
+        let response = self.client.send(request).await;
+        let duration = start.elapsed();
+        self.record(duration);
+        response.map(|response| (response, duration))
+    }
+}
+
+impl<C> AiClient for InstrumentedClient<C>
+where
+    C: AiClient + Sync,
+    C::AiRequest: Sync,
+{
+    type AiRequest = C::AiRequest;
+    type AiResponse = C::AiResponse;
+
+    async fn send(&self, request: &Self::AiRequest) -> AiResult<Self::AiResponse> {
+        self.send_timed(request)
+            .await
+            .map(|(response, _duration)| response)
+    }
+
+    async fn send_with_auth(
+        &self,
+        auth: &Auth,
+        request: &Self::AiRequest,
+    ) -> AiResult<Self::AiResponse> {
+        let start = Instant::now();
+        let response = self.client.send_with_auth(auth, request).await;
+        self.record(start.elapsed());
+        response
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.client.capabilities()
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for InstrumentedClient<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstrumentedClient")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod instrumented_client {
+        use super::super::InstrumentedClient;
+        use crate::client::{AiClient, AiError, AiResult};
+        use crate::service::Auth;
+        use crate::test_support::{Request, Response};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        /// Takes a known amount of time to respond.
+        struct SlowClient {
+            delay: Duration,
+            fail: bool,
+        }
+
+        impl AiClient for SlowClient {
+            type AiRequest = Request;
+            type AiResponse = Response;
+
+            async fn send(&self, request: &Request) -> AiResult<Response> {
+                self.send_with_auth(&Auth::new("some-api-key"), request)
+                    .await
+            }
+
+            async fn send_with_auth(&self, _auth: &Auth, _request: &Request) -> AiResult<Response> {
+                tokio::time::sleep(self.delay).await;
+                if self.fail {
+                    Err(AiError::Failed("too slow".to_string()))
+                } else {
+                    Ok(Response::default())
+                }
+            }
+        }
+
+        fn client(fail: bool) -> SlowClient {
+            SlowClient {
+                delay: Duration::from_millis(50),
+                fail,
+            }
+        }
+
+        #[tokio::test]
+        async fn it_returns_the_duration_of_a_request() {
+            let client = InstrumentedClient::new(client(false));
+            let (_response, duration) = client.send_timed(&Request).await.unwrap();
+            assert!(duration >= Duration::from_millis(50), "{duration:?}");
+            assert!(duration < Duration::from_secs(5), "{duration:?}");
+        }
+
+        #[tokio::test]
+        async fn it_reports_durations_to_a_callback() {
+            let durations = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&durations);
+            let client = InstrumentedClient::new(client(false))
+                .on_latency(move |duration| recorded.lock().unwrap().push(duration));
+
+            client.send(&Request).await.unwrap();
+            client
+                .send_with_auth(&Auth::new("another-api-key"), &Request)
+                .await
+                .unwrap();
+
+            let durations = durations.lock().unwrap();
+            assert_eq!(durations.len(), 2);
+            assert!(durations.iter().all(|d| *d >= Duration::from_millis(50)));
+        }
+
+        #[tokio::test]
+        async fn it_reports_the_durations_of_failed_requests() {
+            let durations = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&durations);
+            let client = InstrumentedClient::new(client(true))
+                .on_latency(move |duration| recorded.lock().unwrap().push(duration));
+
+            assert!(client.send(&Request).await.is_err());
+            assert_eq!(durations.lock().unwrap().len(), 1);
+        }
+    }
+}
@@ -51,7 +51,18 @@
 //! [cogito-openai]: https://docs.rs/cogito-openai
 
 pub mod client;
+pub mod instrument;
+pub mod middleware;
+pub mod prompt;
+pub mod rate_limit;
+pub mod router;
 pub mod service;
+pub mod stream;
+#[cfg(test)]
+mod test_support;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod time;
 
 use std::fmt::Debug;
 
@@ -102,7 +113,7 @@ use std::fmt::Debug;
 ///     }
 /// }
 /// ```
-pub trait AiModel: Clone + Copy + Default + Debug {
+pub trait AiModel: Clone + Default + Debug {
     /// The service's standard or default model.
     ///
     /// Often this is the same as the [best](AiModel::best()), but
@@ -121,6 +132,187 @@ pub trait AiModel: Clone + Copy + Default + Debug {
 
     /// The fastest model available for a given LLM.
     fn fastest() -> Self;
+
+    /// Estimates the number of tokens the model would use to represent
+    /// `text`.
+    ///
+    /// Models split text into tokens differently, and counting tokens
+    /// exactly requires the model's tokenizer, so this is only a rough
+    /// estimate. By default, it assumes that a token is about four
+    /// characters long, which is a reasonable approximation for English
+    /// text with most models. Providers can override this method if they
+    /// can provide a better estimate.
+    fn estimate_tokens(&self, text: &str) -> usize {
+        estimate_tokens(text)
+    }
+
+    /// The price of using the model, if it is known.
+    ///
+    /// By default, this returns `None`.
+    fn pricing(&self) -> Option<client::Pricing> {
+        None
+    }
+
+    /// The month in which the model's training data ends, formatted as
+    /// `YYYY-MM`, if it is known.
+    ///
+    /// Models know nothing about events after their cutoff, so this can be
+    /// used to warn about prompts that depend on recent information. By
+    /// default, this returns `None`.
+    fn knowledge_cutoff(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The maximum number of tokens the model can handle in a single
+    /// request, including both its input and its output, if it is known.
+    ///
+    /// By default, this returns `None`.
+    fn context_window(&self) -> Option<usize> {
+        None
+    }
+
+    /// The model of the given class.
+    ///
+    /// This makes it possible to translate a model from one service to
+    /// another, such as when failing over to a different provider, by
+    /// finding the [class](AiModel::class_of) of the original model and
+    /// requesting the same class from the new provider.
+    fn for_class(class: ModelClass) -> Self {
+        match class {
+            ModelClass::Flagship => Self::flagship(),
+            ModelClass::Best => Self::best(),
+            ModelClass::Cheapest => Self::cheapest(),
+            ModelClass::Fastest => Self::fastest(),
+        }
+    }
+
+    /// The class of the model, or `None` if the model does not belong to
+    /// any class.
+    ///
+    /// A model may belong to more than one class, such as a model that is
+    /// both the cheapest and the fastest. In that case, the first matching
+    /// class is returned, in the order the classes are declared in
+    /// [`ModelClass`].
+    fn class_of(&self) -> Option<ModelClass>
+    where
+        Self: PartialEq,
+    {
+        ModelClass::ALL
+            .into_iter()
+            .find(|&class| Self::for_class(class) == *self)
+    }
+}
+
+/// A broad class of models that every AI service offers.
+///
+/// Classes correspond to the constructors of [`AiModel`], so that a model
+/// from one service can be exchanged for the equivalent model from another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModelClass {
+    /// The service's [standard](AiModel::flagship) model.
+    Flagship,
+
+    /// The service's [best](AiModel::best) model.
+    Best,
+
+    /// The service's [least expensive](AiModel::cheapest) model.
+    Cheapest,
+
+    /// The service's [fastest](AiModel::fastest) model.
+    Fastest,
+}
+
+impl ModelClass {
+    /// Every class, in order.
+    pub const ALL: [ModelClass; 4] = [
+        ModelClass::Flagship,
+        ModelClass::Best,
+        ModelClass::Cheapest,
+        ModelClass::Fastest,
+    ];
+}
+
+/// Estimates the number of tokens in `text`, assuming that a token is
+/// about four characters long.
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Truncates `text` so that it fits within `max_tokens`, as
+/// [estimated](AiModel::estimate_tokens) by the given `model`.
+///
+/// Text is cut from the end, so the beginning of `text` is kept. If `text`
+/// already fits within `max_tokens`, it is returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use cogito::{AiModel, truncate_to_tokens};
+/// #
+/// # #[derive(Clone, Copy, Debug, Default)]
+/// # struct Model;
+/// #
+/// # impl AiModel for Model {
+/// #     fn flagship() -> Self { Model }
+/// #     fn best() -> Self { Model }
+/// #     fn cheapest() -> Self { Model }
+/// #     fn fastest() -> Self { Model }
+/// # }
+/// #
+/// let text = "It was the best of times, it was the worst of times";
+/// assert_eq!(truncate_to_tokens(text, 6, &Model), "It was the best of times");
+/// assert_eq!(truncate_to_tokens(text, 100, &Model), text);
+/// ```
+pub fn truncate_to_tokens<'a, M: AiModel>(text: &'a str, max_tokens: usize, model: &M) -> &'a str {
+    if model.estimate_tokens(text) <= max_tokens {
+        return text;
+    }
+    let boundaries = text.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+    let fits = boundaries.partition_point(|&i| model.estimate_tokens(&text[..i]) <= max_tokens);
+    // The empty prefix always fits, so at least one boundary does.
+    &text[..boundaries[fits.saturating_sub(1)]]
+}
+
+/// Normalizes the whitespace in `text`.
+///
+/// Whitespace that makes no difference to a model can still make a
+/// difference to an AI service's prompt cache, which only matches prompts
+/// with exactly the same prefix. Normalizing text removes that whitespace
+/// by following these rules:
+///
+/// 1. Leading and trailing whitespace, including blank lines, is removed.
+/// 2. Trailing whitespace is removed from each line.
+/// 3. Consecutive blank lines are collapsed into a single blank line.
+///
+/// Indentation at the beginning of each line is kept, so indented text,
+/// such as code, keeps its structure.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::normalize_whitespace;
+///
+/// assert_eq!(normalize_whitespace("  hi \n\n "), "hi");
+/// assert_eq!(
+///     normalize_whitespace("Summarize this:\n\n\n\n    fn main() {}  \n"),
+///     "Summarize this:\n\n    fn main() {}"
+/// );
+/// ```
+pub fn normalize_whitespace(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut blank = false;
+    for line in text.trim().lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank = true;
+            continue;
+        }
+        if !normalized.is_empty() {
+            normalized.push_str(if blank { "\n\n" } else { "\n" });
+        }
+        normalized.push_str(line);
+        blank = false;
+    }
+    normalized
 }
 
 /// Convenience module for the most common Cogito imports.
@@ -134,8 +326,229 @@ pub trait AiModel: Clone + Copy + Default + Debug {
 /// ```
 ///
 /// to use the most common Cogito traits and data structures in your project.
+///
+/// The prelude contains everything needed to implement a client for a new
+/// AI service provider. For example, a toy provider:
+///
+/// ```
+/// use cogito::prelude::*;
+/// use serde::{Deserialize, Serialize};
+/// use serde_json::{Map, Value};
+/// use std::borrow::Cow;
+///
+/// #[derive(Clone, Copy, Debug, Default, Serialize)]
+/// pub enum ToyModel {
+///     #[default]
+///     Toy1,
+/// }
+///
+/// impl AiModel for ToyModel {
+///     fn flagship() -> Self { ToyModel::Toy1 }
+///     fn best() -> Self { ToyModel::Toy1 }
+///     fn cheapest() -> Self { ToyModel::Toy1 }
+///     fn fastest() -> Self { ToyModel::Toy1 }
+/// }
+///
+/// #[derive(Debug, Default, Serialize)]
+/// pub struct ToyRequest {
+///     model: ToyModel,
+///     instructions: Option<String>,
+///     input: String,
+///     #[serde(flatten)]
+///     extra: Map<String, Value>,
+/// }
+///
+/// impl AiRequest for ToyRequest {
+///     type Model = ToyModel;
+///
+///     fn model(self, model: ToyModel) -> Self {
+///         Self { model, ..self }
+///     }
+///
+///     fn model_used(&self) -> ToyModel {
+///         self.model
+///     }
+///
+///     fn instructions(self, instructions: impl Into<String>) -> Self {
+///         let instructions = Some(instructions.into());
+///         Self { instructions, ..self }
+///     }
+///
+///     fn input(self, input: impl Into<String>) -> Self {
+///         let input = input.into();
+///         Self { input, ..self }
+///     }
+///
+///     fn input_text(&self) -> Cow<'_, str> {
+///         Cow::Borrowed(&self.input)
+///     }
+///
+///     fn extra(mut self, key: &str, value: impl Into<Value>) -> Self {
+///         self.extra.insert(key.to_string(), value.into());
+///         self
+///     }
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// pub struct ToyResponse {
+///     text: String,
+/// }
+///
+/// impl AiResponse for ToyResponse {
+///     fn result(&self) -> String {
+///         self.text.clone()
+///     }
+/// }
+///
+/// pub struct ToyClient {
+///     auth: Auth,
+///     service: Service,
+/// }
+///
+/// impl AiClient for ToyClient {
+///     type AiRequest = ToyRequest;
+///     type AiResponse = ToyResponse;
+///
+///     async fn send(&self, request: &ToyRequest) -> AiResult<ToyResponse> {
+///         self.send_with_auth(&self.auth, request).await
+///     }
+///
+///     async fn send_with_auth(&self, auth: &Auth, request: &ToyRequest) -> AiResult<ToyResponse> {
+///         let uri = "https://toy.example.com/v1/generate";
+///         self.service.try_post(uri, auth, request).await
+///     }
+/// }
+/// ```
 pub mod prelude {
-    pub use crate::AiModel;
-    pub use crate::client::{AiClient, AiError, AiRequest, AiResponse, AiResult};
-    pub use crate::service::{Auth, Service};
+    pub use crate::client::{
+        AiClient, AiError, AiRequest, AiResponse, AiResult, Capabilities, IntoAiRequest, Message,
+        Pricing, ProviderMetadata, RetryPolicy, Role, Usage, UsageAccumulator,
+    };
+    pub use crate::instrument::InstrumentedClient;
+    pub use crate::middleware::{RequestMiddleware, ResponseMiddleware};
+    pub use crate::prompt::Prompt;
+    pub use crate::rate_limit::RateLimitStatus;
+    pub use crate::router::ModelRouter;
+    pub use crate::service::{AiPost, Auth, HttpGet, Service, ServiceBuilder};
+    pub use crate::{AiModel, ModelClass};
+}
+
+#[cfg(test)]
+mod tests {
+    mod tokens {
+        use super::super::{AiModel, truncate_to_tokens};
+        use crate::test_support::Model;
+
+        #[test]
+        fn it_estimates_tokens() {
+            assert_eq!(Model::Flagship.estimate_tokens(""), 0);
+            assert_eq!(Model::Flagship.estimate_tokens("abc"), 1);
+            assert_eq!(Model::Flagship.estimate_tokens("abcd"), 1);
+            assert_eq!(Model::Flagship.estimate_tokens("abcde"), 2);
+        }
+
+        #[test]
+        fn it_estimates_tokens_by_characters() {
+            assert_eq!(Model::Flagship.estimate_tokens("ééééé"), 2);
+        }
+
+        #[test]
+        fn it_does_not_truncate_text_that_fits() {
+            let text = "Short and sweet.";
+            assert_eq!(truncate_to_tokens(text, 4, &Model::Flagship), text);
+        }
+
+        #[test]
+        fn it_truncates_text_from_the_end() {
+            let text = "This text is a little too long.";
+            let actual = truncate_to_tokens(text, 4, &Model::Flagship);
+            assert_eq!(actual, "This text is a l");
+        }
+
+        #[test]
+        fn it_truncates_on_character_boundaries() {
+            let text = "ééééééééé";
+            assert_eq!(truncate_to_tokens(text, 2, &Model::Flagship), "éééééééé");
+        }
+
+        #[test]
+        fn it_truncates_text_to_nothing() {
+            assert_eq!(truncate_to_tokens("Too long", 0, &Model::Flagship), "");
+        }
+    }
+
+    mod normalize_whitespace {
+        use super::super::normalize_whitespace;
+
+        #[test]
+        fn it_trims_text() {
+            assert_eq!(normalize_whitespace("  hi \n\n "), "hi");
+            assert_eq!(normalize_whitespace(" \n\t "), "");
+        }
+
+        #[test]
+        fn it_collapses_blank_lines() {
+            assert_eq!(
+                normalize_whitespace("one\n\n\n \ntwo\r\n\r\nthree\nfour"),
+                "one\n\ntwo\n\nthree\nfour"
+            );
+        }
+
+        #[test]
+        fn it_keeps_indentation() {
+            assert_eq!(
+                normalize_whitespace("fn main() {  \n    println!();\t\n}"),
+                "fn main() {\n    println!();\n}"
+            );
+        }
+    }
+
+    mod model_class {
+        use super::super::{AiModel, ModelClass};
+
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        enum Model {
+            #[default]
+            Large,
+            Small,
+            Legacy,
+        }
+
+        impl AiModel for Model {
+            fn flagship() -> Self {
+                Model::Large
+            }
+
+            fn best() -> Self {
+                Model::Large
+            }
+
+            fn cheapest() -> Self {
+                Model::Small
+            }
+
+            fn fastest() -> Self {
+                Model::Small
+            }
+        }
+
+        #[test]
+        fn it_returns_the_model_for_a_class() {
+            assert_eq!(Model::for_class(ModelClass::Flagship), Model::Large);
+            assert_eq!(Model::for_class(ModelClass::Best), Model::Large);
+            assert_eq!(Model::for_class(ModelClass::Cheapest), Model::Small);
+            assert_eq!(Model::for_class(ModelClass::Fastest), Model::Small);
+        }
+
+        #[test]
+        fn it_returns_the_first_class_of_a_model() {
+            assert_eq!(Model::Large.class_of(), Some(ModelClass::Flagship));
+            assert_eq!(Model::Small.class_of(), Some(ModelClass::Cheapest));
+        }
+
+        #[test]
+        fn it_returns_no_class_for_other_models() {
+            assert_eq!(Model::Legacy.class_of(), None);
+        }
+    }
 }
@@ -0,0 +1,374 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Changing requests to and responses from an AI service in one place.
+//!
+//! [`RequestMiddleware`] wraps any [`AiClient`] and runs a hook on each
+//! request before it is sent, so policies that apply to every request, such
+//! as a standard system prompt or redacting personal information, can be
+//! enforced centrally instead of wherever requests are built. Similarly,
+//! [`ResponseMiddleware`] runs a hook on the result of each response, so
+//! output can be cleaned up uniformly. Both are clients themselves, so
+//! they can be combined:
+//!
+//! ```
+//! # use cogito::prelude::*;
+//! # fn wrap<C>(client: C) -> impl AiClient
+//! # where
+//! #     C: AiClient + Sync,
+//! #     C::AiRequest: Clone + Send + Sync,
+//! #     C::AiResponse: Send,
+//! # {
+//! let client = client
+//!     .with_request_hook(|request| {
+//!         request.set_instructions("Answer in plain text.");
+//!     })
+//!     .with_response_hook(|result| result.trim().to_string());
+//! # client
+//! # }
+//! ```
+
+use crate::client::{AiClient, AiResponse, AiResult, Capabilities, Usage};
+use crate::service::Auth;
+use std::fmt;
+use std::sync::Arc;
+
+type RequestHook<R> = Arc<dyn Fn(&mut R) + Send + Sync>;
+type ResponseHook = Arc<dyn Fn(String) -> String + Send + Sync>;
+
+/// An [`AiClient`] that changes each request before sending it.
+///
+/// Requests are copied before the hook is run, so the caller's request is
+/// left as it was. Usually created with [`AiClient::with_request_hook()`].
+///
+/// # Examples
+///
+/// ```
+/// use cogito::middleware::RequestMiddleware;
+/// # use cogito::prelude::*;
+///
+/// # fn wrap<C>(client: C) -> RequestMiddleware<C>
+/// # where
+/// #     C: AiClient,
+/// # {
+/// let client = client.with_request_hook(|request| {
+///     let input = request.input_text().replace("hunter2", "[REDACTED]");
+///     request.set_input(input);
+/// });
+/// # client
+/// # }
+/// ```
+pub struct RequestMiddleware<C: AiClient> {
+    client: C,
+    hook: RequestHook<C::AiRequest>,
+}
+
+impl<C: AiClient> RequestMiddleware<C> {
+    /// Wraps `client` so that `hook` is run on each of its requests.
+    pub fn new(client: C, hook: impl Fn(&mut C::AiRequest) + Send + Sync + 'static) -> Self {
+        Self {
+            client,
+            hook: Arc::new(hook),
+        }
+    }
+
+    /// The wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.client
+    }
+
+    /// Unwraps the client.
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+
+    fn apply(&self, request: &C::AiRequest) -> C::AiRequest
+    where
+        C::AiRequest: Clone,
+    {
+        let mut request = request.clone();
+        (self.hook)(&mut request);
+        request
+    }
+}
+
+impl<C> AiClient for RequestMiddleware<C>
+where
+    C: AiClient + Sync,
+    C::AiRequest: Clone + Send + Sync,
+{
+    type AiRequest = C::AiRequest;
+    type AiResponse = C::AiResponse;
+
+    async fn send(&self, request: &Self::AiRequest) -> AiResult<Self::AiResponse> {
+        let request = self.apply(request);
+        self.client.send(&request).await
+    }
+
+    async fn send_with_auth(
+        &self,
+        auth: &Auth,
+        request: &Self::AiRequest,
+    ) -> AiResult<Self::AiResponse> {
+        let request = self.apply(request);
+        self.client.send_with_auth(auth, &request).await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.client.capabilities()
+    }
+}
+
+impl<C: AiClient + Clone> Clone for RequestMiddleware<C> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            hook: Arc::clone(&self.hook),
+        }
+    }
+}
+
+impl<C: AiClient + fmt::Debug> fmt::Debug for RequestMiddleware<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestMiddleware")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An [`AiClient`] that changes the result of each response it receives.
+///
+/// Responses are returned as [`ProcessedResponse`]s, whose
+/// [result](AiResponse::result) has been passed through the hook. Usually
+/// created with [`AiClient::with_response_hook()`].
+///
+/// # Examples
+///
+/// ```
+/// use cogito::middleware::ResponseMiddleware;
+/// # use cogito::prelude::*;
+///
+/// # fn wrap<C: AiClient>(client: C) -> ResponseMiddleware<C> {
+/// let client = client.with_response_hook(|result| result.replace("**", ""));
+/// # client
+/// # }
+/// ```
+pub struct ResponseMiddleware<C> {
+    client: C,
+    hook: ResponseHook,
+}
+
+impl<C> ResponseMiddleware<C> {
+    /// Wraps `client` so that `hook` is run on the result of each of its
+    /// responses.
+    pub fn new(client: C, hook: impl Fn(String) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            client,
+            hook: Arc::new(hook),
+        }
+    }
+
+    /// The wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.client
+    }
+
+    /// Unwraps the client.
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+
+    fn process<R>(&self, response: R) -> ProcessedResponse<R> {
+        ProcessedResponse {
+            response,
+            hook: Arc::clone(&self.hook),
+        }
+    }
+}
+
+impl<C> AiClient for ResponseMiddleware<C>
+where
+    C: AiClient + Sync,
+    C::AiRequest: Sync,
+    C::AiResponse: Send,
+{
+    type AiRequest = C::AiRequest;
+    type AiResponse = ProcessedResponse<C::AiResponse>;
+
+    async fn send(&self, request: &Self::AiRequest) -> AiResult<Self::AiResponse> {
+        let response = self.client.send(request).await?;
+        Ok(self.process(response))
+    }
+
+    async fn send_with_auth(
+        &self,
+        auth: &Auth,
+        request: &Self::AiRequest,
+    ) -> AiResult<Self::AiResponse> {
+        let response = self.client.send_with_auth(auth, request).await?;
+        Ok(self.process(response))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.client.capabilities()
+    }
+}
+
+impl<C: Clone> Clone for ResponseMiddleware<C> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            hook: Arc::clone(&self.hook),
+        }
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for ResponseMiddleware<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseMiddleware")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A response whose result has been changed by a [`ResponseMiddleware`].
+///
+/// The [result](AiResponse::result) and each of the
+/// [outputs](AiResponse::outputs_text) are passed through the hook;
+/// everything else is taken from the original response, which is still
+/// available from [`inner()`](ProcessedResponse::inner).
+pub struct ProcessedResponse<R> {
+    response: R,
+    hook: ResponseHook,
+}
+
+impl<R> ProcessedResponse<R> {
+    /// The original response.
+    pub fn inner(&self) -> &R {
+        &self.response
+    }
+
+    /// Unwraps the original response.
+    pub fn into_inner(self) -> R {
+        self.response
+    }
+}
+
+impl<R: AiResponse> AiResponse for ProcessedResponse<R> {
+    fn result(&self) -> String {
+        (self.hook)(self.response.result())
+    }
+
+    fn stop_sequence(&self) -> Option<&str> {
+        self.response.stop_sequence()
+    }
+
+    fn raw(&self) -> Option<&serde_json::Value> {
+        self.response.raw()
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.response.usage()
+    }
+
+    fn output_tokens(&self) -> Option<u64> {
+        self.response.output_tokens()
+    }
+
+    fn outputs_text(&self) -> Vec<String> {
+        self.response
+            .outputs_text()
+            .into_iter()
+            .map(|output| (self.hook)(output))
+            .collect()
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for ProcessedResponse<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcessedResponse")
+            .field("response", &self.response)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod middleware {
+        use crate::client::{AiClient, AiRequest, AiResponse, AiResult};
+        use crate::service::Auth;
+        use crate::test_support::{Request, Response};
+        use std::sync::{Arc, Mutex};
+
+        /// Records the input of every request it receives, and echoes it back.
+        #[derive(Default)]
+        struct MockClient {
+            received: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl AiClient for MockClient {
+            type AiRequest = Request;
+            type AiResponse = Response;
+
+            async fn send(&self, request: &Request) -> AiResult<Response> {
+                self.send_with_auth(&Auth::new("some-api-key"), request)
+                    .await
+            }
+
+            async fn send_with_auth(&self, _auth: &Auth, request: &Request) -> AiResult<Response> {
+                self.received.lock().unwrap().push(request.input.clone());
+                Ok(Response(request.input.clone()))
+            }
+        }
+
+        #[tokio::test]
+        async fn it_sends_the_changed_request() {
+            let mock = MockClient::default();
+            let received = Arc::clone(&mock.received);
+            let client = mock.with_request_hook(|request| {
+                request.update(|r| r.append_input("Answer in French."));
+            });
+
+            let request = Request::default().input("What is Rust?");
+            client.send(&request).await.unwrap();
+            client
+                .send_with_auth(&Auth::new("another-api-key"), &request)
+                .await
+                .unwrap();
+
+            let expected = "What is Rust?\nAnswer in French.".to_string();
+            assert_eq!(*received.lock().unwrap(), vec![expected.clone(), expected]);
+            assert_eq!(request.input, "What is Rust?");
+        }
+
+        #[tokio::test]
+        async fn it_changes_the_result_of_a_response() {
+            let client = MockClient::default().with_response_hook(|result| result.to_uppercase());
+
+            let request = Request::default().input("What is Rust?");
+            let response = client.send(&request).await.unwrap();
+            assert_eq!(response.result(), "WHAT IS RUST?");
+            assert_eq!(response.outputs_text(), vec!["WHAT IS RUST?"]);
+            assert_eq!(response.inner().result(), "What is Rust?");
+        }
+
+        #[tokio::test]
+        async fn it_combines_request_and_response_hooks() {
+            let mock = MockClient::default();
+            let received = Arc::clone(&mock.received);
+            let client = mock
+                .with_request_hook(|request| {
+                    request.update(|r| r.append_input("Answer in French."));
+                })
+                .with_response_hook(|result| result.to_uppercase());
+
+            let request = Request::default().input("What is Rust?");
+            let response = client.send(&request).await.unwrap();
+            assert_eq!(
+                *received.lock().unwrap(),
+                vec!["What is Rust?\nAnswer in French."]
+            );
+            assert_eq!(response.result(), "WHAT IS RUST?\nANSWER IN FRENCH.");
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Building prompts independently of any AI service.
+//!
+//! Few-shot prompts are made of the same pieces no matter which AI service
+//! they are sent to: instructions, a handful of example exchanges, and the
+//! query itself. A [`Prompt`] collects those pieces and can then be
+//! [realized](Prompt::realize) into any provider's [`AiRequest`], which
+//! arranges them in whatever form its API expects.
+
+use crate::client::{AiRequest, IntoAiRequest};
+
+/// A provider-neutral prompt made of instructions, examples, and a query.
+///
+/// # Examples
+///
+/// ```
+/// # use cogito::client::AiRequest;
+/// # use cogito::prompt::Prompt;
+/// #
+/// # fn build<R: AiRequest>() -> R {
+/// let request: R = Prompt::new()
+///     .system("Translate English to French.")
+///     .example("cheese", "fromage")
+///     .example("bread", "pain")
+///     .user("wine")
+///     .realize();
+/// # request
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Prompt {
+    system: Option<String>,
+    examples: Vec<(String, String)>,
+    user: Option<String>,
+}
+
+impl Prompt {
+    /// Creates an empty prompt.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the prompt's [instructions](AiRequest::instructions).
+    pub fn system(self, system: impl Into<String>) -> Self {
+        let system = Some(system.into());
+        Self { system, ..self }
+    }
+
+    /// Adds an example of the model responding to `user` with `assistant`.
+    ///
+    /// Examples are kept in the order they are added, and are placed
+    /// before the prompt's [query](Prompt::user).
+    pub fn example(mut self, user: impl Into<String>, assistant: impl Into<String>) -> Self {
+        self.examples.push((user.into(), assistant.into()));
+        self
+    }
+
+    /// Sets the query that the model should respond to.
+    pub fn user(self, user: impl Into<String>) -> Self {
+        let user = Some(user.into());
+        Self { user, ..self }
+    }
+
+    /// Converts the prompt into a request for an AI service.
+    ///
+    /// The request's instructions are set from the prompt's
+    /// [system](Prompt::system) instructions, each [example](Prompt::example)
+    /// is added with [`AiRequest::example()`], and the
+    /// [query](Prompt::user) is appended as the request's input, so each
+    /// provider decides how the examples are represented.
+    pub fn realize<R: AiRequest>(self) -> R {
+        let mut request = R::default();
+        if let Some(system) = self.system {
+            request = request.instructions(system);
+        }
+        for (user, assistant) in self.examples {
+            request = request.example(user, assistant);
+        }
+        if let Some(user) = self.user {
+            request = request.append_input(user);
+        }
+        request
+    }
+}
+
+impl<R: AiRequest> IntoAiRequest<R> for Prompt {
+    fn into_request(self) -> R {
+        self.realize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod prompt {
+        use super::super::Prompt;
+        use crate::test_support::Request;
+
+        #[test]
+        fn it_realizes_a_query() {
+            let request: Request = Prompt::new().user("What is Rust?").realize();
+            assert_eq!(request.instructions, None);
+            assert_eq!(request.input, "What is Rust?");
+        }
+
+        #[test]
+        fn it_folds_examples_into_the_input_by_default() {
+            let request: Request = Prompt::new()
+                .system("Translate English to French.")
+                .example("cheese", "fromage")
+                .example("bread", "pain")
+                .user("wine")
+                .realize();
+            assert_eq!(
+                request.instructions.as_deref(),
+                Some("Translate English to French.")
+            );
+            assert_eq!(
+                request.input,
+                "Example input:\ncheese\nExample output:\nfromage\n\
+                 Example input:\nbread\nExample output:\npain\n\
+                 wine"
+            );
+        }
+    }
+}
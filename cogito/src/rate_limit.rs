@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Rate-limit budgets reported by AI services.
+//!
+//! Most AI services send headers with every response reporting how many
+//! requests and tokens remain in the caller's current rate-limit window,
+//! and when that window resets. Each service names these headers
+//! differently, so services describe their headers with
+//! [`RateLimitHeaders`], and [`RateLimitStatus`] parses them into a
+//! uniform representation.
+
+use reqwest::header::HeaderMap;
+use std::time::{Duration, SystemTime};
+
+/// The names of the headers an AI service uses to report its rate limits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimitHeaders {
+    /// The header reporting the number of requests remaining.
+    pub remaining_requests: &'static str,
+
+    /// The header reporting the number of tokens remaining.
+    pub remaining_tokens: &'static str,
+
+    /// The header reporting when the request limit resets.
+    pub reset_requests: &'static str,
+
+    /// The header reporting when the token limit resets.
+    pub reset_tokens: &'static str,
+}
+
+/// The remaining rate-limit budget reported by an AI service.
+///
+/// Any value that the AI service did not report, or reported in a format
+/// that could not be understood, is `None`.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::rate_limit::{RateLimitHeaders, RateLimitStatus};
+/// use reqwest::header::HeaderMap;
+///
+/// const HEADERS: RateLimitHeaders = RateLimitHeaders {
+///     remaining_requests: "x-ratelimit-remaining-requests",
+///     remaining_tokens: "x-ratelimit-remaining-tokens",
+///     reset_requests: "x-ratelimit-reset-requests",
+///     reset_tokens: "x-ratelimit-reset-tokens",
+/// };
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("x-ratelimit-remaining-requests", "59".parse().unwrap());
+/// let status = RateLimitStatus::from_headers(&headers, &HEADERS);
+/// assert_eq!(status.remaining_requests, Some(59));
+/// assert_eq!(status.remaining_tokens, None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// The number of requests that can be sent before the limit resets.
+    pub remaining_requests: Option<u64>,
+
+    /// The number of tokens that can be used before the limit resets.
+    pub remaining_tokens: Option<u64>,
+
+    /// When both the request and token limits will have reset.
+    pub reset_at: Option<SystemTime>,
+}
+
+impl RateLimitStatus {
+    /// Parses the rate-limit headers named by `names` from `headers`.
+    ///
+    /// Reset times may be either RFC 3339 timestamps, such as
+    /// `2025-10-16T12:00:00Z`, or durations relative to now, such as
+    /// `1s`, `250ms`, or `6m0s`. If the request and token limits reset at
+    /// different times, the later of the two is used.
+    pub fn from_headers(headers: &HeaderMap, names: &RateLimitHeaders) -> Self {
+        let header = |name: &str| headers.get(name)?.to_str().ok().map(str::trim);
+        let count = |name: &str| header(name)?.parse::<u64>().ok();
+        let reset = |name: &str| parse_reset(header(name)?);
+
+        let reset_at = match (reset(names.reset_requests), reset(names.reset_tokens)) {
+            (Some(requests), Some(tokens)) => Some(requests.max(tokens)),
+            (requests, tokens) => requests.or(tokens),
+        };
+
+        Self {
+            remaining_requests: count(names.remaining_requests),
+            remaining_tokens: count(names.remaining_tokens),
+            reset_at,
+        }
+    }
+}
+
+fn parse_reset(value: &str) -> Option<SystemTime> {
+    if value.contains('T') {
+        parse_timestamp(value)
+    } else {
+        parse_duration(value).map(|duration| SystemTime::now() + duration)
+    }
+}
+
+/// Parses a duration like `1s`, `250ms`, `1.5s`, or `6m0s`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    if value.is_empty() {
+        return None;
+    }
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let unit_len = rest[number_len..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - number_len);
+        let number = rest[..number_len].parse::<f64>().ok()?;
+        let seconds = match &rest[number_len..number_len + unit_len] {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return None,
+        };
+        total += number * seconds;
+        rest = &rest[number_len + unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+/// Parses an RFC 3339 timestamp like `2025-10-16T12:00:00Z` or
+/// `2025-10-16T12:00:00.5+02:00`.
+fn parse_timestamp(value: &str) -> Option<SystemTime> {
+    let (date, time) = value.split_once(['T', 't'])?;
+
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => return None,
+    };
+    let offset = match offset {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            sign * (hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60)
+        }
+    };
+
+    let mut time = time.splitn(3, ':');
+    let hours = time.next()?.parse::<i64>().ok()?;
+    let minutes = time.next()?.parse::<i64>().ok()?;
+    let seconds = time.next()?.parse::<f64>().ok()?;
+
+    let days = days_from_civil(year, month, day);
+    let whole_seconds = days * 86_400 + hours * 3600 + minutes * 60 - offset;
+    let seconds = whole_seconds as f64 + seconds;
+    let since_epoch = Duration::try_from_secs_f64(seconds).ok()?;
+    SystemTime::UNIX_EPOCH.checked_add(since_epoch)
+}
+
+/// The number of days between 1970-01-01 and the given date, using
+/// Howard Hinnant's `days_from_civil` algorithm.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    mod rate_limit_status {
+        use super::super::{RateLimitHeaders, RateLimitStatus};
+        use reqwest::header::HeaderMap;
+        use std::time::{Duration, SystemTime};
+
+        const HEADERS: RateLimitHeaders = RateLimitHeaders {
+            remaining_requests: "remaining-requests",
+            remaining_tokens: "remaining-tokens",
+            reset_requests: "reset-requests",
+            reset_tokens: "reset-tokens",
+        };
+
+        fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+                .collect()
+        }
+
+        #[test]
+        fn it_parses_remaining_counts() {
+            let headers = headers(&[("remaining-requests", "59"), ("remaining-tokens", "149984")]);
+            let status = RateLimitStatus::from_headers(&headers, &HEADERS);
+            assert_eq!(status.remaining_requests, Some(59));
+            assert_eq!(status.remaining_tokens, Some(149984));
+        }
+
+        #[test]
+        fn it_reports_nothing_without_headers() {
+            let status = RateLimitStatus::from_headers(&HeaderMap::new(), &HEADERS);
+            assert_eq!(status, RateLimitStatus::default());
+        }
+
+        #[test]
+        fn it_ignores_malformed_headers() {
+            let headers = headers(&[("remaining-requests", "lots"), ("reset-requests", "soon")]);
+            let status = RateLimitStatus::from_headers(&headers, &HEADERS);
+            assert_eq!(status, RateLimitStatus::default());
+        }
+
+        #[test]
+        fn it_parses_reset_timestamps() {
+            let headers = headers(&[("reset-requests", "2025-10-16T12:00:00Z")]);
+            let status = RateLimitStatus::from_headers(&headers, &HEADERS);
+            let expected = SystemTime::UNIX_EPOCH + Duration::from_secs(1_760_616_000);
+            assert_eq!(status.reset_at, Some(expected));
+        }
+
+        #[test]
+        fn it_parses_reset_timestamps_with_offsets() {
+            let headers = headers(&[("reset-requests", "2025-10-16T14:00:00.5+02:00")]);
+            let status = RateLimitStatus::from_headers(&headers, &HEADERS);
+            let expected = SystemTime::UNIX_EPOCH + Duration::from_millis(1_760_616_000_500);
+            assert_eq!(status.reset_at, Some(expected));
+        }
+
+        #[test]
+        fn it_parses_reset_durations() {
+            let before = SystemTime::now();
+            let headers = headers(&[("reset-requests", "1s"), ("reset-tokens", "6m0s")]);
+            let status = RateLimitStatus::from_headers(&headers, &HEADERS);
+            let reset_at = status.reset_at.expect("no reset time");
+            assert!(reset_at >= before + Duration::from_secs(360));
+            assert!(reset_at <= SystemTime::now() + Duration::from_secs(360));
+        }
+
+        #[test]
+        fn it_uses_the_later_reset_time() {
+            let headers = headers(&[
+                ("reset-requests", "2025-10-16T12:00:30Z"),
+                ("reset-tokens", "2025-10-16T12:00:00Z"),
+            ]);
+            let status = RateLimitStatus::from_headers(&headers, &HEADERS);
+            let expected = SystemTime::UNIX_EPOCH + Duration::from_secs(1_760_616_030);
+            assert_eq!(status.reset_at, Some(expected));
+        }
+    }
+
+    mod parse_duration {
+        use super::super::parse_duration;
+        use std::time::Duration;
+
+        #[test]
+        fn it_parses_durations() {
+            assert_eq!(parse_duration("1s"), Some(Duration::from_secs(1)));
+            assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+            assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+            assert_eq!(parse_duration("6m0s"), Some(Duration::from_secs(360)));
+            assert_eq!(parse_duration("1h2m3s"), Some(Duration::from_secs(3723)));
+        }
+
+        #[test]
+        fn it_rejects_malformed_durations() {
+            assert_eq!(parse_duration("1"), None);
+            assert_eq!(parse_duration("s"), None);
+            assert_eq!(parse_duration("1d"), None);
+            assert_eq!(parse_duration(""), None);
+        }
+    }
+}
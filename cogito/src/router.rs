@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Choosing a model based on the size of a prompt.
+//!
+//! Short, simple prompts rarely need a provider's most capable model, so
+//! it is often worth sending them to a cheaper or faster one instead. A
+//! [`ModelRouter`] makes that choice automatically by
+//! [estimating](crate::AiModel::estimate_tokens) how many tokens a prompt
+//! uses and comparing the estimate against a set of thresholds.
+
+use crate::AiModel;
+
+/// Picks a model for a prompt based on how many tokens it uses.
+///
+/// A router is built from a fallback model and any number of rules, each
+/// of which routes prompts _under_ a given number of tokens to a model.
+/// When [routing](ModelRouter::route) a prompt, the rule with the smallest
+/// threshold that the prompt fits under wins; prompts that are too long
+/// for every rule are sent to the fallback model.
+///
+/// # Examples
+///
+/// Send prompts under 500 tokens to the cheapest model and everything else
+/// to the flagship:
+///
+/// ```
+/// # use cogito::AiModel;
+/// # use cogito::router::ModelRouter;
+/// #
+/// # #[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// # enum Model { #[default] Big, Small }
+/// #
+/// # impl AiModel for Model {
+/// #     fn flagship() -> Self { Model::Big }
+/// #     fn best() -> Self { Model::Big }
+/// #     fn cheapest() -> Self { Model::Small }
+/// #     fn fastest() -> Self { Model::Small }
+/// # }
+/// #
+/// let router = ModelRouter::new(Model::flagship()).under(500, Model::cheapest());
+/// assert_eq!(router.route("What is the capital of France?"), Model::cheapest());
+/// assert_eq!(router.route(&"word ".repeat(1000)), Model::flagship());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ModelRouter<M: AiModel> {
+    rules: Vec<(usize, M)>,
+    fallback: M,
+}
+
+impl<M: AiModel> ModelRouter<M> {
+    /// Creates a router that sends every prompt to `fallback` until
+    /// [rules](ModelRouter::under) are added.
+    pub fn new(fallback: M) -> Self {
+        Self {
+            rules: Vec::new(),
+            fallback,
+        }
+    }
+
+    /// Routes prompts that use fewer than `max_tokens` tokens to `model`.
+    ///
+    /// Rules can be added in any order. If a rule for `max_tokens` already
+    /// exists, it is replaced.
+    pub fn under(mut self, max_tokens: usize, model: M) -> Self {
+        match self.rules.binary_search_by_key(&max_tokens, |(n, _)| *n) {
+            Ok(i) => self.rules[i].1 = model,
+            Err(i) => self.rules.insert(i, (max_tokens, model)),
+        }
+        self
+    }
+
+    /// The model that should be used for `prompt`.
+    ///
+    /// Tokens are estimated by the fallback model's
+    /// [`estimate_tokens()`](AiModel::estimate_tokens).
+    pub fn route(&self, prompt: &str) -> M {
+        let tokens = self.fallback.estimate_tokens(prompt);
+        self.rules
+            .iter()
+            .find(|(max_tokens, _)| tokens < *max_tokens)
+            .map_or(&self.fallback, |(_, model)| model)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod route {
+        use super::super::ModelRouter;
+        use crate::AiModel;
+        use crate::test_support::Model;
+
+        // Four characters make up one estimated token.
+        fn prompt(tokens: usize) -> String {
+            "abcd".repeat(tokens)
+        }
+
+        #[test]
+        fn it_routes_everything_to_the_fallback_without_rules() {
+            let router = ModelRouter::new(Model::flagship());
+            assert_eq!(router.route(""), Model::Flagship);
+            assert_eq!(router.route(&prompt(10_000)), Model::Flagship);
+        }
+
+        #[test]
+        fn it_routes_short_prompts_to_a_cheaper_model() {
+            let router = ModelRouter::new(Model::flagship()).under(500, Model::cheapest());
+            assert_eq!(router.route(&prompt(10)), Model::Cheap);
+            assert_eq!(router.route(&prompt(499)), Model::Cheap);
+            assert_eq!(router.route(&prompt(500)), Model::Flagship);
+            assert_eq!(router.route(&prompt(5000)), Model::Flagship);
+        }
+
+        #[test]
+        fn it_picks_the_smallest_matching_threshold() {
+            let router = ModelRouter::new(Model::flagship())
+                .under(2000, Model::fastest())
+                .under(500, Model::cheapest());
+            assert_eq!(router.route(&prompt(100)), Model::Cheap);
+            assert_eq!(router.route(&prompt(1000)), Model::Fast);
+            assert_eq!(router.route(&prompt(3000)), Model::Flagship);
+        }
+
+        #[test]
+        fn it_replaces_a_rule_with_the_same_threshold() {
+            let router = ModelRouter::new(Model::flagship())
+                .under(500, Model::cheapest())
+                .under(500, Model::fastest());
+            assert_eq!(router.route(&prompt(100)), Model::Fast);
+        }
+    }
+}
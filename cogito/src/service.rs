@@ -19,12 +19,276 @@
 //!
 //! [`hypertyper.service`]: https://docs.rs/hypertyper/latest/hypertyper/service/index.html
 
+use crate::client::{AiError, AiResult, RetryPolicy};
+use crate::stream::{EventStream, JSON_LINES_CONTENT_TYPE};
 #[doc(inline)]
 pub use hypertyper::prelude::Auth;
 use hypertyper::prelude::*;
-use reqwest::header;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Request, RequestBuilder, Response, StatusCode, Url, header};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// How an API key is sent to an AI service.
+///
+/// Each provider's own service uses the scheme its API expects, while a
+/// generic [`Service`] can be [configured](Service::auth_scheme) to use any
+/// scheme, such as for a gateway that expects a nonstandard header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AuthScheme {
+    /// The key is sent as a bearer token in the `Authorization` header.
+    #[default]
+    Bearer,
+
+    /// The key is sent as-is in the named header, such as `x-api-key`.
+    Header(&'static str),
+
+    /// The key is sent as the named query parameter, such as `key`.
+    ///
+    /// Query parameters are more likely than headers to be logged by
+    /// servers and proxies along the way, so only use this for gateways
+    /// that require it.
+    Query(&'static str),
+}
+
+impl AuthScheme {
+    /// Adds the API key from `auth` to the request using this scheme.
+    pub fn apply(&self, request: RequestBuilder, auth: &Auth) -> RequestBuilder {
+        match self {
+            AuthScheme::Bearer => request.bearer_auth(auth.api_key()),
+            AuthScheme::Header(name) => request.header(*name, auth.api_key()),
+            AuthScheme::Query(name) => request.query(&[(*name, auth.api_key())]),
+        }
+    }
+
+    /// Removes an API key added to the request using this scheme.
+    fn remove(&self, request: &mut Request) {
+        match self {
+            AuthScheme::Bearer => {
+                request.headers_mut().remove(header::AUTHORIZATION);
+            }
+            AuthScheme::Header(name) => {
+                request.headers_mut().remove(*name);
+            }
+            AuthScheme::Query(name) => {
+                let url = request.url_mut();
+                let pairs: Vec<(String, String)> = url
+                    .query_pairs()
+                    .filter(|(key, _)| key != name)
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect();
+                if pairs.is_empty() {
+                    url.set_query(None);
+                } else {
+                    url.query_pairs_mut().clear().extend_pairs(pairs);
+                }
+            }
+        }
+    }
+}
+
+/// The maximum number of characters of a response body kept in an
+/// [`AiError::Decode`] error.
+const BODY_SNIPPET_LENGTH: usize = 512;
+
+/// An HTTP service that can report errors specific to AI services.
+///
+/// [`HttpPost`] reports failures as generic HTTP errors. `AiPost` adds
+/// [`try_post()`](AiPost::try_post), which reports failures as
+/// [`AiError`]s instead, so, for example, a response that cannot be
+/// decoded carries the raw response body along with it.
+///
+/// By default, `try_post()` simply delegates to [`post()`](HttpPost::post),
+/// so services used for testing only need to implement `HttpPost`.
+/// Services that can also send GET requests, for the few read-only
+/// endpoints that AI services offer, such as listing models, implement
+/// [`try_get()`](AiPost::try_get) as well.
+///
+/// Services must be `Send` and `Sync` so that clients using them can be
+/// shared across tasks on multi-threaded async runtimes.
+pub trait AiPost: HttpPost + Send + Sync {
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body.
+    ///
+    /// The response is deserialized from a string to the JSON object
+    /// specified by the `R` type parameter. By default, a response that is
+    /// valid JSON, but not the expected object, is reported as an
+    /// [`AiError::Decode`] error that includes the beginning of the
+    /// response.
+    fn try_post<U, D, R>(
+        &self,
+        uri: U,
+        auth: &Auth,
+        data: &D,
+    ) -> impl Future<Output = AiResult<R>> + Send
+    where
+        U: IntoUrl + Send,
+        D: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        async move {
+            let response: Value = self.post(uri, auth, data).await?;
+            R::deserialize(&response).map_err(|source| AiError::Decode {
+                source,
+                body_snippet: snippet(&response.to_string()),
+            })
+        }
+    }
+
+    /// Send a GET request to the `uri`.
+    ///
+    /// The response is deserialized from a string to the JSON object
+    /// specified by the `R` type parameter.
+    ///
+    /// By default, an [`AiError::Unsupported`] error is returned, for
+    /// services that can only send POST requests.
+    fn try_get<U, R>(&self, _uri: U, _auth: &Auth) -> impl Future<Output = AiResult<R>> + Send
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned,
+    {
+        async { Err(AiError::Unsupported("GET requests".to_string())) }
+    }
+}
+
+/// An HTTP service that can send GET requests.
+///
+/// This is the counterpart of [`HttpPost`] for the read-only endpoints
+/// that AI services offer, such as listing models or checking the status
+/// of a batch. Requests are authenticated in the same way as the
+/// service's POST requests.
+pub trait HttpGet {
+    /// Send a GET request to the `uri`.
+    ///
+    /// The response is deserialized from a string to the JSON object
+    /// specified by the `R` type parameter.
+    fn get<U, R>(&self, uri: U, auth: &Auth) -> impl Future<Output = HttpResult<R>> + Send
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned;
+}
+
+/// The status, headers, and body of an HTTP response.
+///
+/// Raw responses are returned by the services' `post_raw()` methods, such
+/// as [`Service::post_raw()`], for callers that need to inspect more than
+/// the decoded body, such as the rate-limit headers that some AI services
+/// send with every response.
+pub type RawResponse = (StatusCode, HeaderMap, String);
+
+/// Reads the status, headers, and body of an HTTP response, regardless of
+/// whether it was successful.
+pub async fn read_raw_response(response: Response) -> HttpResult<RawResponse> {
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.text().await?;
+    Ok((status, headers, body))
+}
+
+/// Deserializes the body of an HTTP response to the JSON object specified
+/// by the `R` type parameter.
+///
+/// A `400 Bad Request` response is reported as [`AiError::BadRequest`], a
+/// `401 Unauthorized` response is reported as [`AiError::Unauthorized`], a
+/// `403 Forbidden` response is reported as [`AiError::Forbidden`], and a
+/// `429 Too Many Requests` response is reported as
+/// [`AiError::RateLimited`], along with the delay requested by the
+/// response's `Retry-After` header, if any. Any other unsuccessful
+/// response is reported as [`AiError::Status`]. If the body cannot be
+/// deserialized, the returned [`AiError::Decode`] includes the beginning
+/// of the raw body, which usually explains what went wrong.
+pub async fn decode_response<R: DeserializeOwned>(response: Response) -> AiResult<R> {
+    let response = check_status(response).await?;
+    let body = response.text().await.map_err(HttpError::from)?;
+    decode_body(&body)
+}
+
+/// Reports unsuccessful responses as errors, passing any other response
+/// through untouched.
+///
+/// This reports errors in the same way as [`decode_response()`], and is
+/// useful for responses whose bodies are not a single JSON object, such
+/// as file downloads or [streams](crate::stream).
+pub async fn check_status(response: Response) -> AiResult<Response> {
+    if !is_unsuccessful(response.status()) {
+        return Ok(response);
+    }
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.text().await.map_err(HttpError::from)?;
+    Err(status_error(status, &headers, &body))
+}
+
+/// Deserializes the body of a [raw response](RawResponse) to the JSON
+/// object specified by the `R` type parameter.
+///
+/// Unsuccessful responses are reported as errors in the same way as
+/// [`decode_response()`].
+pub fn decode_raw_response<R: DeserializeOwned>(response: &RawResponse) -> AiResult<R> {
+    let (status, headers, body) = response;
+    if is_unsuccessful(*status) {
+        return Err(status_error(*status, headers, body));
+    }
+    decode_body(body)
+}
+
+fn is_unsuccessful(status: StatusCode) -> bool {
+    status.is_client_error() || status.is_server_error()
+}
+
+/// Whether a request that received a response with `status` is worth
+/// trying again.
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// The error reported for an unsuccessful response.
+fn status_error(status: StatusCode, headers: &HeaderMap, body: &str) -> AiError {
+    let retry_after = retry_after(headers);
+    let body_snippet = snippet(body);
+    match status {
+        StatusCode::BAD_REQUEST => AiError::BadRequest(body_snippet),
+        StatusCode::UNAUTHORIZED => AiError::Unauthorized(body_snippet),
+        StatusCode::FORBIDDEN => AiError::Forbidden(body_snippet),
+        StatusCode::TOO_MANY_REQUESTS => AiError::RateLimited {
+            retry_after,
+            body_snippet,
+        },
+        _ => AiError::Status {
+            status,
+            body_snippet,
+        },
+    }
+}
+
+/// The delay requested by a response's `Retry-After` header, if it
+/// specifies a number of seconds.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?;
+    let seconds = value.trim().parse::<f64>().ok()?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+fn decode_body<R: DeserializeOwned>(body: &str) -> AiResult<R> {
+    serde_json::from_str(body).map_err(|source| AiError::Decode {
+        source,
+        body_snippet: snippet(body),
+    })
+}
+
+/// The beginning of a response body, for use in error messages.
+pub(crate) fn snippet(body: &str) -> String {
+    match body.char_indices().nth(BODY_SNIPPET_LENGTH) {
+        Some((i, _)) => format!("{}...", &body[..i]),
+        None => body.to_string(),
+    }
+}
 
 /// A concrete implementation of an HTTP API service.
 ///
@@ -32,17 +296,482 @@ use serde::de::DeserializeOwned;
 /// less just wraps a Reqwest client, making it easier to swap out the
 /// service for a deterministic service when writing tests. Most AI API
 /// clients should use this `Service` by default.
-#[derive(Debug)]
+///
+/// Cloning a service is cheap: clones share the same connection pool.
+///
+/// Use a [`ServiceBuilder`] to add timeouts, retries, rate limiting, or
+/// custom headers to every request.
+///
+/// # Examples
+///
+/// Send a short-lived token that is rotated into a file by another
+/// process:
+///
+/// ```no_run
+/// use cogito::service::{Auth, Service};
+/// use hypertyper::prelude::*;
+/// use std::fs;
+///
+/// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+/// let service = Service::new(factory).auth_refresher(|| {
+///     let token = fs::read_to_string("/run/secrets/token").unwrap_or_default();
+///     Auth::new(token.trim())
+/// });
+/// ```
+#[derive(Clone, Debug)]
 pub struct Service {
     client: HttpClient,
+    pretty_json: bool,
+    auth_scheme: AuthScheme,
+    refresher: Option<AuthRefresher>,
+    timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    rate_limiter: Option<RateLimiter>,
+    headers: HeaderMap,
+    base_uri: Option<Url>,
+}
+
+/// Produces up-to-date authentication data for each request.
+#[derive(Clone)]
+struct AuthRefresher(Arc<dyn Fn() -> Auth + Send + Sync>);
+
+impl fmt::Debug for AuthRefresher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuthRefresher")
+    }
+}
+
+/// Spaces requests evenly so that no more than a given number are sent
+/// in any period.
+///
+/// Clones share the same schedule.
+#[derive(Clone, Debug)]
+struct RateLimiter {
+    interval: Duration,
+    next: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    fn new(max_requests: u32, per: Duration) -> Self {
+        Self {
+            interval: per / max_requests.max(1),
+            next: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Waits until the next request may be sent.
+    async fn wait(&self) {
+        let delay = {
+            let mut next = self.next.lock().expect("rate limiter lock poisoned");
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + self.interval;
+            slot - now
+        };
+        if !delay.is_zero() {
+            crate::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Builds a [`Service`] that composes several layers of behavior around
+/// each request.
+///
+/// Each layer is optional:
+///
+/// - a [timeout](ServiceBuilder::timeout) for each attempt at a request;
+/// - a [retry policy](ServiceBuilder::retry) for requests that fail with a
+///   transport error, a `429 Too Many Requests` response, or a server
+///   error;
+/// - a [rate limit](ServiceBuilder::rate_limit) on how often requests are
+///   sent;
+/// - [headers](ServiceBuilder::header) added to every request;
+/// - the [scheme](ServiceBuilder::auth_scheme) used to send the API key;
+///   and
+/// - a [base URI](ServiceBuilder::base_uri) that requests are sent to
+///   instead of the AI service's own host, such as a corporate gateway.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::client::RetryPolicy;
+/// use cogito::service::ServiceBuilder;
+/// use hypertyper::prelude::*;
+/// use std::time::Duration;
+///
+/// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+/// let service = ServiceBuilder::new(factory)
+///     .timeout(Duration::from_secs(30))
+///     .retry(RetryPolicy::new(5))
+///     .rate_limit(60, Duration::from_secs(60))
+///     .header("x-team", "research")
+///     .base_uri("https://gateway.example.com/ai")
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ServiceBuilder {
+    service: Service,
+}
+
+impl ServiceBuilder {
+    /// Starts building a service that communicates using clients from the
+    /// given factory.
+    pub fn new(factory: HttpClientFactory) -> Self {
+        Self {
+            service: Service::new(factory),
+        }
+    }
+
+    /// Fails each attempt at a request that takes longer than `timeout`.
+    ///
+    /// The timeout covers the time from when a request is sent until its
+    /// response has been read. Time spent waiting for the rate limit or
+    /// between retries does not count against it.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.service.timeout = Some(timeout);
+        self
+    }
+
+    /// Retries requests that fail according to `policy`.
+    ///
+    /// Requests are retried if they fail to reach the AI service, or if it
+    /// responds with `429 Too Many Requests` or a server error. The delay
+    /// requested by a response's `Retry-After` header is honored.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.service.retry = Some(policy);
+        self
+    }
+
+    /// Sends no more than `max_requests` requests in each period of `per`.
+    ///
+    /// Requests are spaced evenly across the period rather than sent in a
+    /// burst, and clones of the built service share the same limit.
+    pub fn rate_limit(mut self, max_requests: u32, per: Duration) -> Self {
+        self.service.rate_limiter = Some(RateLimiter::new(max_requests, per));
+        self
+    }
+
+    /// Adds a header to every request, replacing any value the service
+    /// would otherwise send.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is not a valid header name or `value` is not a valid
+    /// header value.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::try_from(name).expect("invalid header name");
+        let value = HeaderValue::try_from(value).expect("invalid header value");
+        self.service.headers.insert(name, value);
+        self
+    }
+
+    /// Sends the API key using `scheme` instead of as a bearer token.
+    ///
+    /// See [`Service::auth_scheme()`].
+    pub fn auth_scheme(mut self, scheme: AuthScheme) -> Self {
+        self.service.auth_scheme = scheme;
+        self
+    }
+
+    /// Sends requests to `uri` instead of the AI service's own host.
+    ///
+    /// The path of each request is appended to the path of `uri`, so with
+    /// a base URI of `https://gateway.example.com/ai`, a request to
+    /// `https://api.openai.com/v1/responses` is sent to
+    /// `https://gateway.example.com/ai/v1/responses`.
+    ///
+    /// # Panics
+    ///
+    /// If `uri` is not a valid absolute URI.
+    pub fn base_uri(mut self, uri: &str) -> Self {
+        let uri = Url::parse(uri).expect("invalid base URI");
+        self.service.base_uri = Some(uri);
+        self
+    }
+
+    /// Sends pretty-printed JSON request bodies.
+    ///
+    /// See [`Service::with_pretty_json()`].
+    pub fn pretty_json(mut self) -> Self {
+        self.service.pretty_json = true;
+        self
+    }
+
+    /// Builds the service.
+    pub fn build(self) -> Service {
+        self.service
+    }
 }
 
 impl Service {
     /// Creates a new HTTP service that communicate using clients from the
     /// given factory.
     pub fn new(factory: HttpClientFactory) -> Self {
-        let client = factory.create();
-        Self { client }
+        Self::with_client(factory.create())
+    }
+
+    fn with_client(client: HttpClient) -> Self {
+        Self {
+            client,
+            pretty_json: false,
+            auth_scheme: AuthScheme::default(),
+            refresher: None,
+            timeout: None,
+            retry: None,
+            rate_limiter: None,
+            headers: HeaderMap::new(),
+            base_uri: None,
+        }
+    }
+
+    /// Creates a new HTTP service that sends pretty-printed JSON request
+    /// bodies.
+    ///
+    /// Pretty-printed bodies are larger than the compact bodies sent by
+    /// a service created with [`new()`](Service::new), but they are much
+    /// easier to read when inspecting traffic with a debugging proxy.
+    pub fn with_pretty_json(factory: HttpClientFactory) -> Self {
+        Self {
+            pretty_json: true,
+            ..Self::new(factory)
+        }
+    }
+
+    /// Creates a new HTTP service that does **not** verify the TLS
+    /// certificates of the servers it connects to.
+    ///
+    /// # Danger
+    ///
+    /// **Never use this in production.** A service created this way trusts
+    /// _any_ certificate for _any_ server, including expired and
+    /// self-signed certificates, so anyone who can intercept its traffic
+    /// can read and tamper with requests, including your API key. It exists
+    /// only to allow testing against a local HTTPS gateway, such as a
+    /// self-hosted OpenAI-compatible server, that uses a self-signed
+    /// certificate.
+    ///
+    /// An [`HttpClientFactory`] cannot create clients with TLS verification
+    /// disabled, so the service builds its own client instead, which
+    /// identifies itself as `product/version`, just like the clients
+    /// created by [`HttpClientFactory::new()`].
+    ///
+    /// Requires the `insecure-tls` feature.
+    ///
+    /// # Errors
+    ///
+    /// If the HTTP client cannot be built, such as when the TLS backend
+    /// cannot be initialized.
+    #[cfg(feature = "insecure-tls")]
+    pub fn with_insecure_tls(product: &str, version: &str) -> HttpResult<Self> {
+        let client = HttpClient::builder()
+            .user_agent(format!("{product}/{version}"))
+            .tls_danger_accept_invalid_certs(true)
+            .build()?;
+        Ok(Self::with_client(client))
+    }
+
+    /// Fetches authentication data from `refresher` before each request,
+    /// instead of using the authentication data passed to the service.
+    ///
+    /// This is useful for gateways that issue short-lived tokens, such as
+    /// an OIDC-protected proxy in front of an OpenAI-compatible API. If
+    /// the AI service responds with `401 Unauthorized`, the token is
+    /// fetched again and the request is retried once, so a token that
+    /// expired in flight does not cause the request to fail. The retry
+    /// counts against the service's [rate limit](ServiceBuilder::rate_limit).
+    ///
+    /// The refresher's authentication data is used for _every_ request,
+    /// even one sent with its own authentication data, such as by
+    /// [`AiClient::send_with_auth()`](crate::client::AiClient::send_with_auth).
+    /// Use a separate service without a refresher for requests that must
+    /// be sent with their own credentials.
+    pub fn auth_refresher(self, refresher: impl Fn() -> Auth + Send + Sync + 'static) -> Self {
+        let refresher = Some(AuthRefresher(Arc::new(refresher)));
+        Self { refresher, ..self }
+    }
+
+    /// Sends the API key using `scheme`.
+    ///
+    /// By default, the key is sent as a [bearer token](AuthScheme::Bearer),
+    /// as most OpenAI-compatible APIs expect. Gateways that expect the key
+    /// somewhere else, such as in a custom header, can be reached by
+    /// changing the scheme, so the same service can be used no matter
+    /// where the key belongs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cogito::service::{AuthScheme, Service};
+    /// use hypertyper::prelude::*;
+    ///
+    /// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+    /// let service = Service::new(factory).auth_scheme(AuthScheme::Header("x-gateway-key"));
+    /// ```
+    pub fn auth_scheme(self, auth_scheme: AuthScheme) -> Self {
+        Self {
+            auth_scheme,
+            ..self
+        }
+    }
+
+    fn request<U, D>(&self, uri: U, auth: &Auth, data: &D) -> HttpResult<RequestBuilder>
+    where
+        U: IntoUrl,
+        D: Serialize,
+    {
+        let body = self.serialize(data)?;
+        let request = self
+            .client
+            .post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body);
+        Ok(self.authorize(request, auth))
+    }
+
+    fn get_request<U: IntoUrl>(&self, uri: U, auth: &Auth) -> RequestBuilder {
+        self.authorize(self.client.get(uri), auth)
+    }
+
+    /// Adds authentication data to the request, preferring fresh data from
+    /// the service's refresher, if it has one, over `auth`.
+    fn authorize(&self, request: RequestBuilder, auth: &Auth) -> RequestBuilder {
+        match &self.refresher {
+            Some(AuthRefresher(refresh)) => self.auth_scheme.apply(request, &refresh()),
+            None => self.auth_scheme.apply(request, auth),
+        }
+    }
+
+    /// Sends the request, retrying it according to the service's retry
+    /// policy, if it has one.
+    async fn send(&self, request: RequestBuilder, auth: &Auth) -> HttpResult<Response> {
+        let mut request = request.build()?;
+        self.prepare(&mut request);
+        let max_attempts = self.retry.map_or(1, |policy| policy.max_attempts());
+        let mut attempt = 1;
+        loop {
+            let next = if attempt < max_attempts {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = self.execute(request, auth).await;
+            let (Some(next), Some(policy)) = (next, self.retry) else {
+                return result;
+            };
+            let retry_after = match &result {
+                Ok(response) if is_retryable(response.status()) => retry_after(response.headers()),
+                Ok(_) => return result,
+                Err(_) => None,
+            };
+            crate::time::sleep(policy.delay(attempt, retry_after)).await;
+            request = next;
+            attempt += 1;
+        }
+    }
+
+    /// Applies the service's base URI, timeout, and headers to the request.
+    fn prepare(&self, request: &mut Request) {
+        if let Some(base_uri) = &self.base_uri {
+            let url = request.url_mut();
+            let path = format!("{}{}", base_uri.path().trim_end_matches('/'), url.path());
+            let query = url.query().map(str::to_string);
+            *url = base_uri.clone();
+            url.set_path(&path);
+            url.set_query(query.as_deref());
+        }
+        if self.timeout.is_some() {
+            *request.timeout_mut() = self.timeout;
+        }
+        for (name, value) in &self.headers {
+            request.headers_mut().insert(name, value.clone());
+        }
+    }
+
+    /// Sends the request, retrying it once with fresh authentication data
+    /// if it is rejected as unauthorized and the service has a refresher.
+    ///
+    /// The retry waits for the rate limit like any other request, and its
+    /// response is returned in place of the unauthorized one, so it is
+    /// subject to the service's retry policy as well.
+    async fn execute(&self, request: Request, auth: &Auth) -> HttpResult<Response> {
+        let retry = match self.refresher {
+            Some(_) => request.try_clone(),
+            None => None,
+        };
+        let response = self.dispatch(request).await?;
+        match retry {
+            Some(mut retry) if response.status() == StatusCode::UNAUTHORIZED => {
+                self.auth_scheme.remove(&mut retry);
+                let retry = RequestBuilder::from_parts(self.client.clone(), retry);
+                let retry = self.authorize(retry, auth).build()?;
+                self.dispatch(retry).await
+            }
+            _ => Ok(response),
+        }
+    }
+
+    /// Sends the request once it is allowed by the rate limit.
+    async fn dispatch(&self, request: Request) -> HttpResult<Response> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.wait().await;
+        }
+        Ok(self.client.execute(request).await?)
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and stream the newline-delimited JSON
+    /// response.
+    ///
+    /// This is useful for gateways that stream responses as
+    /// `application/x-ndjson` rather than as server-sent events. Each line
+    /// of the response is deserialized to the type specified by the `R`
+    /// type parameter as soon as it is received.
+    ///
+    /// Unauthorized and rate-limited responses are reported in the same way
+    /// as [`decode_response()`].
+    pub async fn post_json_lines<U, D, R>(
+        &self,
+        uri: U,
+        auth: &Auth,
+        data: &D,
+    ) -> AiResult<EventStream<R>>
+    where
+        U: IntoUrl,
+        D: Serialize,
+        R: DeserializeOwned,
+    {
+        let request = self
+            .request(uri, auth, data)?
+            .header(header::ACCEPT, JSON_LINES_CONTENT_TYPE);
+        let response = self.send(request, auth).await?;
+        let response = check_status(response).await?;
+        Ok(EventStream::new(response))
+    }
+
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, and return the raw response.
+    ///
+    /// Unlike [`post()`](HttpPost::post), the response's status and
+    /// headers are returned along with its body, which is not decoded, and
+    /// unsuccessful responses are not reported as errors. This is useful
+    /// for inspecting rate-limit headers, such as
+    /// `x-ratelimit-remaining-requests`, to pace requests.
+    pub async fn post_raw<U, D>(&self, uri: U, auth: &Auth, data: &D) -> HttpResult<RawResponse>
+    where
+        U: IntoUrl,
+        D: Serialize,
+    {
+        let request = self.request(uri, auth, data)?;
+        let response = self.send(request, auth).await?;
+        read_raw_response(response).await
+    }
+
+    fn serialize<D: Serialize>(&self, data: &D) -> serde_json::Result<String> {
+        if self.pretty_json {
+            serde_json::to_string_pretty(data)
+        } else {
+            serde_json::to_string(data)
+        }
     }
 }
 
@@ -63,17 +792,864 @@ impl HttpPost for Service {
         // json as a reqwest feature...
         // Maybe there's a public JSON API out there for integration testing?
 
-        let auth_header = format!("Bearer {}", auth.api_key());
-        let json_object = self
-            .client
-            .post(uri)
-            .header(header::CONTENT_TYPE, "application/json")
-            .header(header::AUTHORIZATION, auth_header)
-            .json(data)
-            .send()
-            .await?
-            .json::<R>()
-            .await?;
+        let request = self.request(uri, auth, data)?;
+        let json_object = self.send(request, auth).await?.json::<R>().await?;
         Ok(json_object)
     }
 }
+
+impl AiPost for Service {
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body.
+    ///
+    /// Unlike [`post()`](HttpPost::post), if the response cannot be
+    /// deserialized, the returned error includes the beginning of the
+    /// raw response body.
+    async fn try_post<U, D, R>(&self, uri: U, auth: &Auth, data: &D) -> AiResult<R>
+    where
+        U: IntoUrl + Send,
+        D: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        let request = self.request(uri, auth, data)?;
+        let response = self.send(request, auth).await?;
+        decode_response(response).await
+    }
+
+    /// Send a GET request to the `uri`.
+    ///
+    /// If the response cannot be deserialized, the returned error includes
+    /// the beginning of the raw response body.
+    async fn try_get<U, R>(&self, uri: U, auth: &Auth) -> AiResult<R>
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned,
+    {
+        let request = self.get_request(uri, auth);
+        let response = self.send(request, auth).await?;
+        decode_response(response).await
+    }
+}
+
+impl HttpGet for Service {
+    /// Send a GET request to the `uri`.
+    ///
+    /// The response is deserialized from a string to the JSON object
+    /// specified by the `R` type parameter.
+    async fn get<U, R>(&self, uri: U, auth: &Auth) -> HttpResult<R>
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned,
+    {
+        let request = self.get_request(uri, auth);
+        let json_object = self.send(request, auth).await?.json::<R>().await?;
+        Ok(json_object)
+    }
+}
+
+/// The number of responses a [`CachingService`] keeps by default.
+const DEFAULT_MAX_CACHED_RESPONSES: usize = 1024;
+
+/// An HTTP service that caches the responses of another service.
+///
+/// Responses are cached in memory, so a request that is identical to an
+/// earlier one returns the earlier response instead of being sent again.
+/// This is useful for idempotent prompts, and for saving time and money by
+/// not repeating identical requests during development.
+///
+/// Only successful responses to [`try_post()`](AiPost::try_post) are
+/// cached. Requests are considered identical if they are sent to the same
+/// URI with the same API key and identical bodies, so a caching service can
+/// be shared by clients that send requests on behalf of different users.
+///
+/// No more than [a limited number](CachingService::max_entries) of
+/// responses are kept. When the cache is full, expired responses are
+/// removed first, followed by the least recently used responses.
+///
+/// Cloning a caching service is cheap: clones share the same cache.
+///
+/// # Examples
+///
+/// Cache responses from a standard service for up to an hour:
+///
+/// ```
+/// use cogito::service::{CachingService, Service};
+/// use hypertyper::prelude::*;
+/// use std::time::Duration;
+///
+/// let factory = HttpClientFactory::new("my-package", "v1.0.0");
+/// let service = CachingService::with_ttl(Service::new(factory), Duration::from_secs(3600));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachingService<T> {
+    inner: T,
+    ttl: Option<Duration>,
+    max_entries: usize,
+    cache: Arc<Mutex<ResponseCache>>,
+}
+
+#[derive(Debug, Default)]
+struct ResponseCache {
+    responses: HashMap<String, CachedResponse>,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct CachedResponse {
+    response: Value,
+    cached_at: Instant,
+    last_used: u64,
+}
+
+impl<T> CachingService<T> {
+    /// Creates a new service that caches the responses of the `inner`
+    /// service indefinitely.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            ttl: None,
+            max_entries: DEFAULT_MAX_CACHED_RESPONSES,
+            cache: Arc::new(Mutex::new(ResponseCache::default())),
+        }
+    }
+
+    /// Creates a new service that caches the responses of the `inner`
+    /// service for the given amount of time.
+    pub fn with_ttl(inner: T, ttl: Duration) -> Self {
+        Self {
+            ttl: Some(ttl),
+            ..Self::new(inner)
+        }
+    }
+
+    /// Keeps no more than `max_entries` responses, instead of the default
+    /// of 1,024.
+    pub fn max_entries(self, max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            ..self
+        }
+    }
+
+    /// Removes all cached responses.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().responses.clear();
+    }
+
+    /// The number of responses currently cached, including any that have
+    /// expired but have not been removed yet.
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().responses.len()
+    }
+
+    /// True if no responses are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The cache key of a request, which is a hex-encoded SHA-256 digest
+    /// of the request's URI, API key, and serialized body.
+    fn key<D: Serialize>(uri: &Url, auth: &Auth, data: &D) -> serde_json::Result<String> {
+        let body = serde_json::to_vec(data)?;
+        let mut hasher = Sha256::new();
+        for part in [uri.as_str().as_bytes(), auth.api_key().as_bytes(), &body] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    fn is_expired(&self, cached: &CachedResponse) -> bool {
+        self.ttl
+            .is_some_and(|ttl| cached.cached_at.elapsed() >= ttl)
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        let mut cache = self.cache.lock().unwrap();
+        cache.clock += 1;
+        let clock = cache.clock;
+        let cached = cache.responses.get_mut(key)?;
+        if self.is_expired(cached) {
+            cache.responses.remove(key);
+            return None;
+        }
+        cached.last_used = clock;
+        Some(cached.response.clone())
+    }
+
+    fn insert(&self, key: String, response: Value) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut cache = self.cache.lock().unwrap();
+        cache.responses.retain(|_, cached| !self.is_expired(cached));
+        while cache.responses.len() >= self.max_entries {
+            let oldest = cache
+                .responses
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone());
+            let Some(oldest) = oldest else { break };
+            cache.responses.remove(&oldest);
+        }
+        cache.clock += 1;
+        let cached = CachedResponse {
+            response,
+            cached_at: Instant::now(),
+            last_used: cache.clock,
+        };
+        cache.responses.insert(key, cached);
+    }
+}
+
+/// Resolves `uri` to a URL, so that it can be part of a cache key.
+fn resolve_uri<U: IntoUrl>(uri: U) -> HttpResult<Url> {
+    static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+    Ok(CLIENT.get(uri).build()?.url().clone())
+}
+
+impl<T: HttpPost + Sync> HttpPost for CachingService<T> {
+    /// Send a POST request to the `uri` using the inner service.
+    ///
+    /// Responses to `post()` are not cached, because `post()` does not
+    /// report unsuccessful responses as errors, so an error object could be
+    /// cached in place of a response. Use [`try_post()`](AiPost::try_post)
+    /// to cache responses.
+    async fn post<U, D, R>(&self, uri: U, auth: &Auth, data: &D) -> HttpResult<R>
+    where
+        U: IntoUrl + Send,
+        D: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        self.inner.post(uri, auth, data).await
+    }
+}
+
+impl<T: AiPost> AiPost for CachingService<T> {
+    /// Send a POST request to the `uri` with the JSON object `data` as
+    /// the POST request body, unless the response to an identical request
+    /// is already cached.
+    async fn try_post<U, D, R>(&self, uri: U, auth: &Auth, data: &D) -> AiResult<R>
+    where
+        U: IntoUrl + Send,
+        D: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        let uri = resolve_uri(uri)?;
+        let key = Self::key(&uri, auth, data).map_err(HttpError::from)?;
+        let response = match self.get(&key) {
+            Some(response) => response,
+            None => {
+                let response: Value = self.inner.try_post(uri, auth, data).await?;
+                self.insert(key, response.clone());
+                response
+            }
+        };
+        R::deserialize(&response).map_err(|source| AiError::Decode {
+            source,
+            body_snippet: snippet(&response.to_string()),
+        })
+    }
+
+    /// Send a GET request to the `uri` using the inner service.
+    ///
+    /// GET responses are not cached.
+    async fn try_get<U, R>(&self, uri: U, auth: &Auth) -> AiResult<R>
+    where
+        U: IntoUrl + Send,
+        R: DeserializeOwned,
+    {
+        self.inner.try_get(uri, auth).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod caching_service {
+        use super::super::{AiPost, CachingService};
+        use hypertyper::prelude::*;
+        use serde::Serialize;
+        use serde::de::DeserializeOwned;
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        #[derive(Clone, Default)]
+        struct CountingService {
+            count: Arc<AtomicUsize>,
+        }
+
+        impl CountingService {
+            fn count(&self) -> usize {
+                self.count.load(Ordering::SeqCst)
+            }
+        }
+
+        impl HttpPost for CountingService {
+            async fn post<U, D, R>(&self, _uri: U, _auth: &Auth, data: &D) -> HttpResult<R>
+            where
+                U: IntoUrl + Send,
+                D: Serialize + Sync,
+                R: DeserializeOwned,
+            {
+                let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+                let data = serde_json::to_value(data)?;
+                let response = serde_json::json!({ "echo": data, "count": count });
+                Ok(serde_json::from_value(response)?)
+            }
+        }
+
+        impl AiPost for CountingService {}
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Response {
+            echo: Vec<String>,
+            count: usize,
+        }
+
+        async fn send<T: AiPost>(service: &T, input: &str) -> Response {
+            let uri = "https://example.com/v1/responses";
+            send_to(service, uri, "some-api-key", input).await
+        }
+
+        async fn send_to<T: AiPost>(service: &T, uri: &str, key: &str, input: &str) -> Response {
+            let auth = Auth::new(key);
+            service
+                .try_post(uri, &auth, &vec![input])
+                .await
+                .expect("could not send request")
+        }
+
+        #[tokio::test]
+        async fn it_returns_a_cached_response_for_an_identical_request() {
+            let inner = CountingService::default();
+            let service = CachingService::new(inner.clone());
+            let first = send(&service, "Cache me!").await;
+            let second = send(&service, "Cache me!").await;
+            assert_eq!(first, second);
+            assert_eq!(inner.count(), 1);
+        }
+
+        #[tokio::test]
+        async fn it_sends_a_different_request() {
+            let inner = CountingService::default();
+            let service = CachingService::new(inner.clone());
+            send(&service, "Cache me!").await;
+            let second = send(&service, "Cache me too!").await;
+            assert_eq!(second.echo, vec!["Cache me too!"]);
+            assert_eq!(inner.count(), 2);
+        }
+
+        #[tokio::test]
+        async fn it_sends_an_identical_request_to_a_different_uri() {
+            let inner = CountingService::default();
+            let service = CachingService::new(inner.clone());
+            send_to(&service, "https://example.com/v1/responses", "key", "Hi").await;
+            send_to(&service, "https://example.com/v2/responses", "key", "Hi").await;
+            assert_eq!(inner.count(), 2);
+        }
+
+        #[tokio::test]
+        async fn it_sends_an_identical_request_with_a_different_api_key() {
+            let inner = CountingService::default();
+            let service = CachingService::new(inner.clone());
+            let uri = "https://example.com/v1/responses";
+            send_to(&service, uri, "tenant-a", "Hi").await;
+            let second = send_to(&service, uri, "tenant-b", "Hi").await;
+            assert_eq!(second.count, 2);
+            assert_eq!(inner.count(), 2);
+        }
+
+        #[tokio::test]
+        async fn it_evicts_the_least_recently_used_response() {
+            let inner = CountingService::default();
+            let service = CachingService::new(inner.clone()).max_entries(2);
+            send(&service, "first").await;
+            send(&service, "second").await;
+            send(&service, "first").await;
+            send(&service, "third").await;
+            assert_eq!(service.len(), 2);
+            assert_eq!(inner.count(), 3);
+            send(&service, "first").await;
+            assert_eq!(inner.count(), 3);
+            send(&service, "second").await;
+            assert_eq!(inner.count(), 4);
+        }
+
+        #[tokio::test]
+        async fn it_removes_expired_responses_when_caching_another() {
+            let inner = CountingService::default();
+            let service = CachingService::with_ttl(inner.clone(), Duration::ZERO);
+            send(&service, "first").await;
+            send(&service, "second").await;
+            assert_eq!(service.len(), 1);
+        }
+
+        #[tokio::test]
+        async fn it_does_not_cache_responses_to_posts() {
+            let inner = CountingService::default();
+            let service = CachingService::new(inner.clone());
+            let auth = Auth::new("some-api-key");
+            let data = vec!["Don't cache me!"];
+            for count in 1..=2 {
+                let response: Response = service
+                    .post("https://example.com/v1/responses", &auth, &data)
+                    .await
+                    .expect("could not send request");
+                assert_eq!(response.count, count);
+            }
+            assert!(service.is_empty());
+        }
+
+        #[tokio::test]
+        async fn it_expires_cached_responses() {
+            let inner = CountingService::default();
+            let service = CachingService::with_ttl(inner.clone(), Duration::ZERO);
+            send(&service, "Cache me!").await;
+            send(&service, "Cache me!").await;
+            assert_eq!(inner.count(), 2);
+        }
+
+        #[tokio::test]
+        async fn it_shares_its_cache_with_clones() {
+            let inner = CountingService::default();
+            let service = CachingService::new(inner.clone());
+            send(&service, "Cache me!").await;
+            send(&service.clone(), "Cache me!").await;
+            assert_eq!(inner.count(), 1);
+        }
+
+        #[tokio::test]
+        async fn it_clears_its_cache() {
+            let inner = CountingService::default();
+            let service = CachingService::new(inner.clone());
+            send(&service, "Cache me!").await;
+            service.clear();
+            send(&service, "Cache me!").await;
+            assert_eq!(inner.count(), 2);
+        }
+    }
+
+    mod ai_post {
+        use super::super::AiPost;
+        use crate::client::AiError;
+        use hypertyper::prelude::*;
+        use serde::Serialize;
+        use serde::de::DeserializeOwned;
+
+        /// Responds to every request with the same body.
+        struct StaticService(&'static str);
+
+        impl HttpPost for StaticService {
+            async fn post<U, D, R>(&self, _uri: U, _auth: &Auth, _data: &D) -> HttpResult<R>
+            where
+                U: IntoUrl + Send,
+                D: Serialize + Sync,
+                R: DeserializeOwned,
+            {
+                Ok(serde_json::from_str(self.0)?)
+            }
+        }
+
+        impl AiPost for StaticService {}
+
+        async fn send(body: &'static str) -> Result<Vec<u32>, AiError> {
+            let auth = Auth::new("some-api-key");
+            StaticService(body)
+                .try_post("https://example.com/v1/responses", &auth, &"Hi")
+                .await
+        }
+
+        #[tokio::test]
+        async fn it_decodes_a_response() {
+            assert_eq!(send("[1, 2, 3]").await.unwrap(), vec![1, 2, 3]);
+        }
+
+        #[tokio::test]
+        async fn it_reports_a_malformed_response_as_a_decode_error() {
+            let err = send("<html>Bad Gateway</html>").await.unwrap_err();
+            assert!(matches!(err, AiError::Decode { .. }), "{err:?}");
+            assert!(!err.is_retryable());
+        }
+
+        #[tokio::test]
+        async fn it_includes_an_unexpected_response_in_a_decode_error() {
+            let err = send(r#"{"error": "Invalid model"}"#).await.unwrap_err();
+            assert!(!err.is_retryable());
+            match err {
+                AiError::Decode { body_snippet, .. } => {
+                    assert_eq!(body_snippet, r#"{"error":"Invalid model"}"#)
+                }
+                other => panic!("expected decode error, got {other:?}"),
+            }
+        }
+    }
+
+    mod service {
+        use super::super::Service;
+        use hypertyper::prelude::*;
+
+        fn request_body(service: &Service) -> String {
+            let auth = Auth::new("some-api-key");
+            let data = vec!["Serialize me!"];
+            let request = service
+                .request("https://example.com/v1/responses", &auth, &data)
+                .expect("could not create request")
+                .build()
+                .expect("could not build request");
+            let body = request.body().and_then(|b| b.as_bytes()).expect("no body");
+            String::from_utf8(body.to_vec()).expect("body is not utf-8")
+        }
+
+        #[test]
+        fn it_sends_compact_json_by_default() {
+            let factory = HttpClientFactory::new("cogito", "test");
+            let service = Service::new(factory);
+            assert_eq!(request_body(&service), r#"["Serialize me!"]"#);
+        }
+
+        #[test]
+        fn it_sends_pretty_json_when_configured() {
+            let factory = HttpClientFactory::new("cogito", "test");
+            let service = Service::with_pretty_json(factory);
+            assert_eq!(request_body(&service), "[\n  \"Serialize me!\"\n]");
+        }
+
+        #[test]
+        #[cfg(feature = "insecure-tls")]
+        fn it_builds_a_service_with_insecure_tls() {
+            let service =
+                Service::with_insecure_tls("cogito", "test").expect("could not build service");
+            assert_eq!(request_body(&service), r#"["Serialize me!"]"#);
+        }
+    }
+
+    mod auth_refresher {
+        use super::super::{AiPost, Service, ServiceBuilder};
+        use hypertyper::prelude::*;
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+        use std::time::{Duration, Instant};
+
+        /// Serves `count` requests, rejecting any request that uses the
+        /// "expired" token, and records the Authorization header of each.
+        fn serve(count: usize) -> (String, Arc<Mutex<Vec<String>>>) {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind");
+            let uri = format!("http://{}/v1/responses", listener.local_addr().unwrap());
+            let tokens = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&tokens);
+            thread::spawn(move || {
+                for stream in listener.incoming().take(count) {
+                    let mut stream = stream.expect("could not accept connection");
+                    let mut reader = BufReader::new(&stream);
+                    let mut token = String::new();
+                    let mut length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).expect("could not read request");
+                        let line = line.trim_end();
+                        if line.is_empty() {
+                            break;
+                        }
+                        let (name, value) = line.split_once(": ").unwrap_or((line, ""));
+                        match name.to_ascii_lowercase().as_str() {
+                            "authorization" => token = value.to_string(),
+                            "content-length" => length = value.parse().unwrap(),
+                            _ => {}
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    std::io::Read::read_exact(&mut reader, &mut body).unwrap();
+                    let (status, body) = match token.as_str() {
+                        "Bearer expired" => ("401 Unauthorized", "Token expired"),
+                        _ => ("200 OK", "[1, 2, 3]"),
+                    };
+                    recorded.lock().unwrap().push(token);
+                    let response = format!(
+                        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    stream.write_all(response.as_bytes()).unwrap();
+                }
+            });
+            (uri, tokens)
+        }
+
+        fn service(tokens: &'static [&'static str]) -> Service {
+            let factory = HttpClientFactory::new("cogito", "test");
+            let calls = AtomicUsize::new(0);
+            Service::new(factory).auth_refresher(move || {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                Auth::new(tokens[call.min(tokens.len() - 1)])
+            })
+        }
+
+        #[test]
+        fn it_uses_a_refreshed_token() {
+            let service = service(&["fresh"]);
+            let auth = Auth::new("stale");
+            let request = service
+                .request("https://example.com/v1/responses", &auth, &"Hi")
+                .expect("could not create request")
+                .build()
+                .expect("could not build request");
+            let actual = request.headers().get("authorization").unwrap();
+            assert_eq!(actual, "Bearer fresh");
+        }
+
+        #[tokio::test]
+        async fn it_refreshes_the_token_and_retries_an_unauthorized_request() {
+            let (uri, tokens) = serve(2);
+            let service = service(&["expired", "fresh"]);
+            let auth = Auth::new("stale");
+            let actual: Vec<u32> = service
+                .try_post(uri, &auth, &"Hi")
+                .await
+                .expect("request was not retried");
+            assert_eq!(actual, vec![1, 2, 3]);
+            assert_eq!(*tokens.lock().unwrap(), ["Bearer expired", "Bearer fresh"]);
+        }
+
+        #[tokio::test]
+        async fn it_rate_limits_the_retry_of_an_unauthorized_request() {
+            let (uri, tokens) = serve(2);
+            let factory = HttpClientFactory::new("cogito", "test");
+            let calls = AtomicUsize::new(0);
+            let service = ServiceBuilder::new(factory)
+                .rate_limit(1, Duration::from_millis(200))
+                .build()
+                .auth_refresher(move || match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => Auth::new("expired"),
+                    _ => Auth::new("fresh"),
+                });
+            let start = Instant::now();
+            let actual: Vec<u32> = service
+                .try_post(uri, &Auth::new("stale"), &"Hi")
+                .await
+                .expect("request was not retried");
+            assert_eq!(actual, vec![1, 2, 3]);
+            assert_eq!(tokens.lock().unwrap().len(), 2);
+            assert!(start.elapsed() >= Duration::from_millis(200));
+        }
+    }
+
+    mod auth_scheme {
+        use super::super::AuthScheme;
+        use hypertyper::prelude::*;
+        use reqwest::header;
+
+        fn build_request(scheme: AuthScheme) -> reqwest::Request {
+            let auth = Auth::new("some-api-key");
+            let request = reqwest::Client::new().post("https://example.com/v1/responses");
+            scheme
+                .apply(request, &auth)
+                .build()
+                .expect("could not build request")
+        }
+
+        #[test]
+        fn it_sends_a_bearer_token() {
+            let request = build_request(AuthScheme::Bearer);
+            let actual = request.headers().get(header::AUTHORIZATION).unwrap();
+            assert_eq!(actual, "Bearer some-api-key");
+        }
+
+        #[test]
+        fn it_sends_the_key_in_a_header() {
+            let request = build_request(AuthScheme::Header("x-api-key"));
+            let actual = request.headers().get("x-api-key").unwrap();
+            assert_eq!(actual, "some-api-key");
+            assert!(request.headers().get(header::AUTHORIZATION).is_none());
+        }
+
+        #[test]
+        fn it_sends_the_key_in_a_query_parameter() {
+            let request = build_request(AuthScheme::Query("key"));
+            assert_eq!(request.url().query(), Some("key=some-api-key"));
+            assert!(request.headers().get(header::AUTHORIZATION).is_none());
+        }
+
+        #[test]
+        fn it_removes_the_key_from_a_query_parameter() {
+            let mut request = reqwest::Client::new()
+                .post("https://example.com/v1/responses?beta=true")
+                .query(&[("key", "some-api-key")])
+                .build()
+                .expect("could not build request");
+            AuthScheme::Query("key").remove(&mut request);
+            assert_eq!(request.url().query(), Some("beta=true"));
+        }
+    }
+
+    mod raw_response {
+        use super::super::{decode_raw_response, read_raw_response};
+        use crate::client::AiError;
+        use reqwest::StatusCode;
+        use reqwest::header::{self, HeaderMap};
+        use std::time::Duration;
+
+        #[test]
+        fn it_decodes_a_raw_response() {
+            let response = (StatusCode::OK, HeaderMap::new(), "[1, 2, 3]".to_string());
+            let actual: Vec<u32> = decode_raw_response(&response).unwrap();
+            assert_eq!(actual, vec![1, 2, 3]);
+        }
+
+        #[test]
+        fn it_reports_an_unsuccessful_raw_response() {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::RETRY_AFTER, "20".parse().unwrap());
+            let body = "Slow down".to_string();
+            let response = (StatusCode::TOO_MANY_REQUESTS, headers, body);
+            match decode_raw_response::<Vec<u32>>(&response) {
+                Err(AiError::RateLimited {
+                    retry_after,
+                    body_snippet,
+                }) => {
+                    assert_eq!(retry_after, Some(Duration::from_secs(20)));
+                    assert_eq!(body_snippet, "Slow down");
+                }
+                other => panic!("expected a rate limit error, got {other:?}"),
+            }
+        }
+
+        #[tokio::test]
+        async fn it_reads_the_status_headers_and_body() {
+            let response = http::Response::builder()
+                .status(503)
+                .header("x-ratelimit-remaining-requests", "42")
+                .body("Overloaded".to_string())
+                .expect("could not build response");
+            let (status, headers, body) = read_raw_response(reqwest::Response::from(response))
+                .await
+                .expect("could not read response");
+            assert_eq!(status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(headers["x-ratelimit-remaining-requests"], "42");
+            assert_eq!(body, "Overloaded");
+        }
+    }
+
+    mod decode {
+        use super::super::{BODY_SNIPPET_LENGTH, decode_body, decode_response};
+        use crate::client::AiError;
+        use std::time::Duration;
+
+        fn response(status: u16, body: &str) -> reqwest::Response {
+            let response = http::Response::builder()
+                .status(status)
+                .body(body.to_string())
+                .expect("could not build response");
+            reqwest::Response::from(response)
+        }
+
+        #[tokio::test]
+        async fn it_decodes_a_response() {
+            let actual: Vec<u32> = decode_response(response(200, "[1, 2, 3]"))
+                .await
+                .expect("could not decode response");
+            assert_eq!(actual, vec![1, 2, 3]);
+        }
+
+        fn rate_limited_response(retry_after: &str) -> reqwest::Response {
+            let response = http::Response::builder()
+                .status(429)
+                .header("retry-after", retry_after)
+                .body("Slow down!".to_string())
+                .expect("could not build response");
+            reqwest::Response::from(response)
+        }
+
+        #[tokio::test]
+        async fn it_reports_a_rate_limited_response() {
+            match decode_response::<Vec<u32>>(rate_limited_response("20")).await {
+                Err(AiError::RateLimited {
+                    retry_after,
+                    body_snippet,
+                }) => {
+                    assert_eq!(retry_after, Some(Duration::from_secs(20)));
+                    assert_eq!(body_snippet, "Slow down!");
+                }
+                other => panic!("expected rate limited error, got {other:?}"),
+            }
+        }
+
+        #[tokio::test]
+        async fn it_ignores_a_retry_after_date() {
+            let date = "Wed, 21 Oct 2015 07:28:00 GMT";
+            match decode_response::<Vec<u32>>(rate_limited_response(date)).await {
+                Err(AiError::RateLimited { retry_after, .. }) => assert_eq!(retry_after, None),
+                other => panic!("expected rate limited error, got {other:?}"),
+            }
+        }
+
+        #[tokio::test]
+        async fn it_reports_an_unauthorized_response() {
+            let body = r#"{"error": {"message": "Invalid API key"}}"#;
+            match decode_response::<Vec<u32>>(response(401, body)).await {
+                Err(AiError::Unauthorized(message)) => assert_eq!(message, body),
+                other => panic!("expected unauthorized error, got {other:?}"),
+            }
+        }
+
+        #[tokio::test]
+        async fn it_reports_a_forbidden_response() {
+            let body = r#"{"error": {"message": "You do not have access to this model"}}"#;
+            match decode_response::<Vec<u32>>(response(403, body)).await {
+                Err(AiError::Forbidden(message)) => assert_eq!(message, body),
+                other => panic!("expected forbidden error, got {other:?}"),
+            }
+        }
+
+        #[tokio::test]
+        async fn it_reports_a_bad_request_response() {
+            match decode_response::<Vec<u32>>(response(400, "Missing model")).await {
+                Err(AiError::BadRequest(message)) => assert_eq!(message, "Missing model"),
+                other => panic!("expected bad request error, got {other:?}"),
+            }
+        }
+
+        #[tokio::test]
+        async fn it_reports_any_other_unsuccessful_response() {
+            match decode_response::<Vec<u32>>(response(503, "Overloaded")).await {
+                Err(AiError::Status {
+                    status,
+                    body_snippet,
+                }) => {
+                    assert_eq!(status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+                    assert_eq!(body_snippet, "Overloaded");
+                }
+                other => panic!("expected status error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn it_decodes_a_json_body() {
+            let actual: Vec<u32> = decode_body("[1, 2, 3]").expect("could not decode body");
+            assert_eq!(actual, vec![1, 2, 3]);
+        }
+
+        #[test]
+        fn it_includes_the_body_in_a_decode_error() {
+            let body = r#"{"error": {"message": "Invalid model"}}"#;
+            match decode_body::<Vec<u32>>(body) {
+                Err(AiError::Decode { body_snippet, .. }) => assert_eq!(body_snippet, body),
+                other => panic!("expected decode error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn it_truncates_a_long_body_in_a_decode_error() {
+            let body = "x".repeat(BODY_SNIPPET_LENGTH * 2);
+            match decode_body::<Vec<u32>>(&body) {
+                Err(AiError::Decode { body_snippet, .. }) => {
+                    let expected = format!("{}...", "x".repeat(BODY_SNIPPET_LENGTH));
+                    assert_eq!(body_snippet, expected);
+                }
+                other => panic!("expected decode error, got {other:?}"),
+            }
+        }
+    }
+}
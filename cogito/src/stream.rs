@@ -0,0 +1,985 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Incremental decoding of streamed responses.
+//!
+//! Some AI services, particularly self-hosted gateways that are compatible
+//! with the OpenAI API, stream responses as [newline-delimited JSON]
+//! (served as `application/x-ndjson`) instead of server-sent events. Each
+//! line of the response body is a complete JSON object representing one
+//! chunk of the response.
+//!
+//! [`JsonLines`] decodes such a stream from arbitrarily-sized pieces of the
+//! response body. AI services that stream [server-sent events] (served as
+//! `text/event-stream`), such as the OpenAI API itself, send each chunk as
+//! the `data` of an event instead, which [`ServerSentEvents`] decodes.
+//! [`EventStream`] reads those pieces from a live HTTP response, using
+//! whichever decoder suits the response's `Content-Type`, and yields each
+//! chunk as soon as it arrives. If the connection may drop partway through
+//! a long response, a [`ResumableStream`] can reconnect and continue where
+//! it left off.
+//!
+//! However a stream is decoded, its chunks describe pieces of a response.
+//! Provider crates translate each chunk into [`ResponseDelta`]s, and a
+//! [`ResponseAccumulator`] reassembles them into a complete response.
+//!
+//! [newline-delimited JSON]: https://github.com/ndjson/ndjson-spec
+//! [server-sent events]: https://html.spec.whatwg.org/multipage/server-sent-events.html
+
+use crate::client::{AiError, AiResponse, AiResult, Usage};
+use crate::service::snippet;
+use hypertyper::prelude::*;
+use reqwest::{Response, header};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+/// The media type of a newline-delimited JSON stream.
+pub const JSON_LINES_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// The media type of a stream of server-sent events.
+pub const EVENT_STREAM_CONTENT_TYPE: &str = "text/event-stream";
+
+/// Decodes a newline-delimited JSON stream into objects of type `R`.
+///
+/// Bytes are fed to the decoder with [`push()`](JsonLines::push) as they
+/// are received, and complete objects are taken from it with
+/// [`next_item()`](JsonLines::next_item). A line may be split across any
+/// number of pushes; the decoder buffers partial lines until they are
+/// complete. Blank lines are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::stream::JsonLines;
+///
+/// let mut decoder = JsonLines::<Vec<u32>>::new();
+/// decoder.push(b"[1, 2]\n[3");
+/// assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1, 2]);
+/// assert!(decoder.next_item().is_none());
+///
+/// decoder.push(b"]");
+/// assert_eq!(decoder.finish().unwrap().unwrap(), vec![3]);
+/// ```
+#[derive(Debug)]
+pub struct JsonLines<R> {
+    buffer: Vec<u8>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R: DeserializeOwned> JsonLines<R> {
+    /// Creates a new, empty decoder.
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Appends a piece of the stream to the decoder's buffer.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Decodes the next complete line in the buffer, if any.
+    ///
+    /// Returns `None` if the buffer does not yet contain a complete line.
+    pub fn next_item(&mut self) -> Option<AiResult<R>> {
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            if let Some(item) = Self::decode(&line) {
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    /// Decodes whatever remains in the buffer once the stream has ended.
+    ///
+    /// The last line of a stream is not required to end in a newline, so
+    /// any unterminated line is decoded as a final object.
+    pub fn finish(&mut self) -> Option<AiResult<R>> {
+        let line = std::mem::take(&mut self.buffer);
+        Self::decode(&line)
+    }
+
+    fn decode(line: &[u8]) -> Option<AiResult<R>> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        let item = serde_json::from_str(line).map_err(|source| AiError::Decode {
+            source,
+            body_snippet: snippet(line),
+        });
+        Some(item)
+    }
+}
+
+impl<R: DeserializeOwned> Default for JsonLines<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decodes a stream of [server-sent events] whose data are JSON objects of
+/// type `R`.
+///
+/// Like [`JsonLines`], bytes are fed to the decoder with
+/// [`push()`](ServerSentEvents::push) as they are received, and complete
+/// objects are taken from it with [`next_item()`](ServerSentEvents::next_item).
+/// Each event's `data` lines are joined and decoded as a single object.
+/// Comments, events without data, and the `[DONE]` sentinel that some
+/// services send to mark the end of a stream are ignored, as are the
+/// `event`, `id`, and `retry` fields, since the objects describe
+/// themselves.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::stream::ServerSentEvents;
+///
+/// let mut decoder = ServerSentEvents::<Vec<u32>>::new();
+/// decoder.push(b"event: numbers\ndata: [1, 2]\n\ndata: [3");
+/// assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1, 2]);
+/// assert!(decoder.next_item().is_none());
+///
+/// decoder.push(b"]\n\n");
+/// assert_eq!(decoder.next_item().unwrap().unwrap(), vec![3]);
+/// ```
+///
+/// [server-sent events]: https://html.spec.whatwg.org/multipage/server-sent-events.html
+#[derive(Debug)]
+pub struct ServerSentEvents<R> {
+    buffer: Vec<u8>,
+    data: Option<String>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R: DeserializeOwned> ServerSentEvents<R> {
+    /// Creates a new, empty decoder.
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            data: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Appends a piece of the stream to the decoder's buffer.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Decodes the next complete event in the buffer, if any.
+    ///
+    /// Returns `None` if the buffer does not yet contain a complete event.
+    pub fn next_item(&mut self) -> Option<AiResult<R>> {
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            if let Some(item) = self.read_line(&line) {
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    /// Decodes whatever remains in the buffer once the stream has ended.
+    ///
+    /// The last event of a stream is not required to end in a blank line,
+    /// so any unfinished event is decoded as a final object.
+    pub fn finish(&mut self) -> Option<AiResult<R>> {
+        let line = std::mem::take(&mut self.buffer);
+        self.read_line(&line).or_else(|| self.dispatch())
+    }
+
+    /// Reads one line of the stream, returning the event it finishes, if
+    /// any.
+    fn read_line(&mut self, line: &[u8]) -> Option<AiResult<R>> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        if field == "data" {
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            }
+        }
+        None
+    }
+
+    /// Decodes the data of the event that has just finished, if any.
+    fn dispatch(&mut self) -> Option<AiResult<R>> {
+        let data = self.data.take()?;
+        let data = data.trim();
+        if data.is_empty() || data == "[DONE]" {
+            return None;
+        }
+        let item = serde_json::from_str(data).map_err(|source| AiError::Decode {
+            source,
+            body_snippet: snippet(data),
+        });
+        Some(item)
+    }
+}
+
+impl<R: DeserializeOwned> Default for ServerSentEvents<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decodes the body of a streamed response, according to its framing.
+#[derive(Debug)]
+enum Decoder<R> {
+    JsonLines(JsonLines<R>),
+    ServerSentEvents(ServerSentEvents<R>),
+}
+
+impl<R: DeserializeOwned> Decoder<R> {
+    /// The decoder for the response's `Content-Type`.
+    ///
+    /// Responses that are not server-sent events are assumed to be
+    /// newline-delimited JSON.
+    fn for_response(response: &Response) -> Self {
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if content_type.starts_with(EVENT_STREAM_CONTENT_TYPE) {
+            Decoder::ServerSentEvents(ServerSentEvents::new())
+        } else {
+            Decoder::JsonLines(JsonLines::new())
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        match self {
+            Decoder::JsonLines(decoder) => decoder.push(bytes),
+            Decoder::ServerSentEvents(decoder) => decoder.push(bytes),
+        }
+    }
+
+    fn next_item(&mut self) -> Option<AiResult<R>> {
+        match self {
+            Decoder::JsonLines(decoder) => decoder.next_item(),
+            Decoder::ServerSentEvents(decoder) => decoder.next_item(),
+        }
+    }
+
+    fn finish(&mut self) -> Option<AiResult<R>> {
+        match self {
+            Decoder::JsonLines(decoder) => decoder.finish(),
+            Decoder::ServerSentEvents(decoder) => decoder.finish(),
+        }
+    }
+}
+
+/// Reads a stream of JSON objects from an HTTP response.
+///
+/// The response is decoded as [server-sent events](ServerSentEvents) if
+/// its `Content-Type` is `text/event-stream`, and as
+/// [newline-delimited JSON](JsonLines) otherwise.
+///
+/// Each call to [`next()`](EventStream::next) waits until another
+/// complete object has been received, so callers can process a response
+/// incrementally as it is generated.
+#[derive(Debug)]
+pub struct EventStream<R> {
+    response: Response,
+    decoder: Decoder<R>,
+    done: bool,
+}
+
+/// The former name of [`EventStream`], from when it only read
+/// newline-delimited JSON.
+#[deprecated(since = "0.2.2", note = "renamed to `EventStream`")]
+pub type JsonLinesStream<R> = EventStream<R>;
+
+impl<R: DeserializeOwned> EventStream<R> {
+    /// Streams objects from the body of the given response.
+    pub fn new(response: Response) -> Self {
+        let decoder = Decoder::for_response(&response);
+        Self {
+            response,
+            decoder,
+            done: false,
+        }
+    }
+
+    /// Waits for the next object in the stream.
+    ///
+    /// Returns `None` once the stream has been exhausted.
+    pub async fn next(&mut self) -> Option<AiResult<R>> {
+        loop {
+            if let Some(item) = self.decoder.next_item() {
+                return Some(item);
+            }
+            if self.done {
+                return None;
+            }
+            match self.response.chunk().await {
+                Ok(Some(bytes)) => self.decoder.push(&bytes),
+                Ok(None) => {
+                    self.done = true;
+                    return self.decoder.finish();
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(HttpError::from(err).into()));
+                }
+            }
+        }
+    }
+
+    /// Reads the rest of the stream, collecting every remaining object.
+    ///
+    /// Returns the first error encountered, if any.
+    pub async fn collect(mut self) -> AiResult<Vec<R>> {
+        let mut items = Vec::new();
+        while let Some(item) = self.next().await {
+            items.push(item?);
+        }
+        Ok(items)
+    }
+}
+
+/// An item of a stream that can be [resumed](ResumableStream) after its
+/// connection drops.
+pub trait Resumable {
+    /// The item's position in the stream, if the AI service numbers the
+    /// items it sends.
+    ///
+    /// When a stream is resumed, the AI service may send some items again.
+    /// Items whose position is at or before that of the last item already
+    /// received are skipped, so they are not duplicated, unless the stream
+    /// [starts a new response](ResumableStream::renumbers_on_reconnect)
+    /// each time it reconnects. By default, items are not numbered, so
+    /// nothing is skipped.
+    fn sequence_number(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// A stream that reconnects if its connection drops partway through a
+/// response.
+///
+/// If reading the stream fails with a [retryable](AiError::is_retryable)
+/// error, such as a connection that drops partway through the response,
+/// `reconnect` is called with every item received so far, and should send
+/// a request that continues the response from that point. How to do so
+/// depends on the AI service: with OpenAI, a request can continue from a
+/// `previous_response_id`, while with Claude, the text received so far can
+/// be resent as the beginning of the assistant's response. The stream
+/// reconnects at most `max_retries` times before giving up and returning
+/// the error.
+pub struct ResumableStream<R, F> {
+    stream: EventStream<R>,
+    reconnect: F,
+    retries_left: u32,
+    received: Vec<R>,
+    last_sequence_number: Option<u64>,
+    renumbers_on_reconnect: bool,
+}
+
+impl<R, F, Fut> ResumableStream<R, F>
+where
+    R: DeserializeOwned + Resumable + Clone,
+    F: FnMut(&[R]) -> Fut,
+    Fut: Future<Output = AiResult<EventStream<R>>>,
+{
+    /// Resumes `stream` by calling `reconnect` if its connection drops, up
+    /// to `max_retries` times.
+    pub fn new(stream: EventStream<R>, max_retries: u32, reconnect: F) -> Self {
+        Self {
+            stream,
+            reconnect,
+            retries_left: max_retries,
+            received: Vec::new(),
+            last_sequence_number: None,
+            renumbers_on_reconnect: false,
+        }
+    }
+
+    /// Treats each reconnection as the start of a new response, whose
+    /// items are [numbered](Resumable::sequence_number) from the beginning
+    /// again.
+    ///
+    /// This is the case when a stream is resumed by sending a new request,
+    /// such as an OpenAI request that continues from a
+    /// `previous_response_id`. Without this, the beginning of the new
+    /// response would be skipped as a duplicate of items already received.
+    pub fn renumbers_on_reconnect(self) -> Self {
+        Self {
+            renumbers_on_reconnect: true,
+            ..self
+        }
+    }
+
+    /// Waits for the next object in the stream, reconnecting if necessary.
+    ///
+    /// Returns `None` once the stream has been exhausted.
+    pub async fn next(&mut self) -> Option<AiResult<R>> {
+        loop {
+            match self.stream.next().await {
+                Some(Ok(item)) => {
+                    let sequence_number = item.sequence_number();
+                    if let (Some(n), Some(last)) = (sequence_number, self.last_sequence_number) {
+                        if n <= last {
+                            continue;
+                        }
+                    }
+                    self.last_sequence_number = sequence_number.or(self.last_sequence_number);
+                    self.received.push(item.clone());
+                    return Some(Ok(item));
+                }
+                Some(Err(err)) if err.is_retryable() => {
+                    if let Err(err) = self.reconnect(err).await {
+                        return Some(Err(err));
+                    }
+                }
+                other => return other,
+            }
+        }
+    }
+
+    /// Reads the rest of the stream, collecting every remaining object.
+    ///
+    /// Returns the first error encountered that could not be recovered
+    /// from by reconnecting, if any.
+    pub async fn collect(mut self) -> AiResult<Vec<R>> {
+        let mut items = Vec::new();
+        while let Some(item) = self.next().await {
+            items.push(item?);
+        }
+        Ok(items)
+    }
+
+    async fn reconnect(&mut self, mut err: AiError) -> AiResult<()> {
+        while self.retries_left > 0 {
+            self.retries_left -= 1;
+            match (self.reconnect)(&self.received).await {
+                Ok(stream) => {
+                    self.stream = stream;
+                    if self.renumbers_on_reconnect {
+                        self.last_sequence_number = None;
+                    }
+                    return Ok(());
+                }
+                Err(e) if e.is_retryable() => err = e,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(err)
+    }
+}
+
+/// A boxed request that reconnects a [`ResumableStream`].
+///
+/// Provider clients that return a `ResumableStream` reconnect it with a
+/// function that returns this type, so the stream's type can be named
+/// without naming the type of each provider's request future.
+pub type Reconnection<'a, R> = Pin<Box<dyn Future<Output = AiResult<EventStream<R>>> + Send + 'a>>;
+
+/// A piece of a streamed response.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResponseDelta {
+    /// More of the response's text.
+    Text(String),
+
+    /// A fragment of a tool call.
+    ///
+    /// Fragments of the same tool call share an `index`. The call's ID and
+    /// name usually arrive with its first fragment, and its arguments are
+    /// split across any number of fragments.
+    ToolCall {
+        /// The position of the tool call in the response.
+        index: usize,
+
+        /// The tool call's ID, if this fragment carries it.
+        id: Option<String>,
+
+        /// The name of the tool being called, if this fragment carries it.
+        name: Option<String>,
+
+        /// The next piece of the tool call's JSON-encoded arguments.
+        arguments: String,
+    },
+
+    /// The tokens used by the request and its response, usually sent at
+    /// the end of a stream.
+    Usage(Usage),
+}
+
+/// A tool call reassembled from a streamed response.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ToolCall {
+    /// The tool call's ID.
+    pub id: String,
+
+    /// The name of the tool being called.
+    pub name: String,
+
+    /// The tool call's JSON-encoded arguments.
+    pub arguments: String,
+}
+
+/// Reassembles a streamed response from its [deltas](ResponseDelta).
+///
+/// # Examples
+///
+/// ```
+/// use cogito::client::AiResponse;
+/// use cogito::stream::{ResponseAccumulator, ResponseDelta};
+///
+/// let mut accumulator = ResponseAccumulator::new();
+/// accumulator.push(ResponseDelta::Text("Hello, ".to_string()));
+/// accumulator.push(ResponseDelta::Text("world!".to_string()));
+/// let response = accumulator.finish();
+/// assert_eq!(response.result(), "Hello, world!");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ResponseAccumulator {
+    text: String,
+    tool_calls: Vec<ToolCall>,
+    usage: Option<Usage>,
+}
+
+impl ResponseAccumulator {
+    /// Creates a new, empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a delta to the response.
+    pub fn push(&mut self, delta: ResponseDelta) {
+        match delta {
+            ResponseDelta::Text(text) => self.text.push_str(&text),
+            ResponseDelta::ToolCall {
+                index,
+                id,
+                name,
+                arguments,
+            } => {
+                if self.tool_calls.len() <= index {
+                    self.tool_calls.resize_with(index + 1, ToolCall::default);
+                }
+                let call = &mut self.tool_calls[index];
+                if let Some(id) = id {
+                    call.id = id;
+                }
+                if let Some(name) = name {
+                    call.name = name;
+                }
+                call.arguments.push_str(&arguments);
+            }
+            ResponseDelta::Usage(usage) => self.usage = Some(usage),
+        }
+    }
+
+    /// The text received so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Finishes the response.
+    pub fn finish(self) -> AccumulatedResponse {
+        AccumulatedResponse {
+            text: self.text,
+            tool_calls: self.tool_calls,
+            usage: self.usage,
+        }
+    }
+}
+
+impl Extend<ResponseDelta> for ResponseAccumulator {
+    fn extend<T: IntoIterator<Item = ResponseDelta>>(&mut self, deltas: T) {
+        for delta in deltas {
+            self.push(delta);
+        }
+    }
+}
+
+/// A complete response reassembled by a [`ResponseAccumulator`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccumulatedResponse {
+    text: String,
+    tool_calls: Vec<ToolCall>,
+    usage: Option<Usage>,
+}
+
+impl AccumulatedResponse {
+    /// The tool calls in the response, in order.
+    pub fn tool_calls(&self) -> &[ToolCall] {
+        &self.tool_calls
+    }
+}
+
+impl AiResponse for AccumulatedResponse {
+    /// The concatenated text of every text delta.
+    fn result(&self) -> String {
+        self.text.clone()
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod json_lines {
+        use super::super::JsonLines;
+        use crate::client::AiError;
+
+        #[test]
+        fn it_decodes_complete_lines() {
+            let mut decoder = JsonLines::<Vec<u32>>::new();
+            decoder.push(b"[1]\n[2, 3]\n");
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1]);
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![2, 3]);
+            assert!(decoder.next_item().is_none());
+            assert!(decoder.finish().is_none());
+        }
+
+        #[test]
+        fn it_buffers_a_line_split_across_pushes() {
+            let mut decoder = JsonLines::<Vec<u32>>::new();
+            decoder.push(b"[1, ");
+            assert!(decoder.next_item().is_none());
+            decoder.push(b"2]\r\n");
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1, 2]);
+        }
+
+        #[test]
+        fn it_skips_blank_lines() {
+            let mut decoder = JsonLines::<Vec<u32>>::new();
+            decoder.push(b"\n\n[1]\n  \n");
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1]);
+            assert!(decoder.next_item().is_none());
+        }
+
+        #[test]
+        fn it_includes_the_line_in_a_decode_error() {
+            let mut decoder = JsonLines::<Vec<u32>>::new();
+            decoder.push(b"not json\n[1]\n");
+            match decoder.next_item() {
+                Some(Err(AiError::Decode { body_snippet, .. })) => {
+                    assert_eq!(body_snippet, "not json")
+                }
+                other => panic!("expected decode error, got {other:?}"),
+            }
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1]);
+        }
+    }
+
+    mod server_sent_events {
+        use super::super::ServerSentEvents;
+        use crate::client::AiError;
+
+        #[test]
+        fn it_decodes_the_data_of_each_event() {
+            let mut decoder = ServerSentEvents::<Vec<u32>>::new();
+            decoder.push(b"event: first\ndata: [1]\n\nevent: second\r\ndata: [2, 3]\r\n\r\n");
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1]);
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![2, 3]);
+            assert!(decoder.next_item().is_none());
+            assert!(decoder.finish().is_none());
+        }
+
+        #[test]
+        fn it_buffers_an_event_split_across_pushes() {
+            let mut decoder = ServerSentEvents::<Vec<u32>>::new();
+            decoder.push(b"data: [1, ");
+            assert!(decoder.next_item().is_none());
+            decoder.push(b"2]\n");
+            assert!(decoder.next_item().is_none());
+            decoder.push(b"\n");
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1, 2]);
+        }
+
+        #[test]
+        fn it_joins_multiple_data_lines() {
+            let mut decoder = ServerSentEvents::<Vec<u32>>::new();
+            decoder.push(b"data: [1,\ndata: 2]\n\n");
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1, 2]);
+        }
+
+        #[test]
+        fn it_skips_comments_and_the_done_sentinel() {
+            let mut decoder = ServerSentEvents::<Vec<u32>>::new();
+            decoder.push(b": keep-alive\n\nid: 1\n\ndata: [1]\n\ndata: [DONE]\n\n");
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1]);
+            assert!(decoder.next_item().is_none());
+        }
+
+        #[test]
+        fn it_decodes_an_unfinished_event_at_the_end_of_the_stream() {
+            let mut decoder = ServerSentEvents::<Vec<u32>>::new();
+            decoder.push(b"data: [1]");
+            assert!(decoder.next_item().is_none());
+            assert_eq!(decoder.finish().unwrap().unwrap(), vec![1]);
+        }
+
+        #[test]
+        fn it_includes_the_data_in_a_decode_error() {
+            let mut decoder = ServerSentEvents::<Vec<u32>>::new();
+            decoder.push(b"data: not json\n\ndata: [1]\n\n");
+            match decoder.next_item() {
+                Some(Err(AiError::Decode { body_snippet, .. })) => {
+                    assert_eq!(body_snippet, "not json")
+                }
+                other => panic!("expected decode error, got {other:?}"),
+            }
+            assert_eq!(decoder.next_item().unwrap().unwrap(), vec![1]);
+        }
+    }
+
+    mod event_stream {
+        use super::super::EventStream;
+
+        fn response(body: &str) -> reqwest::Response {
+            let response = http::Response::builder()
+                .header("content-type", "application/x-ndjson")
+                .body(body.to_string())
+                .expect("could not build response");
+            reqwest::Response::from(response)
+        }
+
+        #[tokio::test]
+        async fn it_yields_each_object_in_the_response() {
+            let mut stream = EventStream::<Vec<u32>>::new(response("[1]\n[2]\n[3]"));
+            assert_eq!(stream.next().await.unwrap().unwrap(), vec![1]);
+            assert_eq!(stream.next().await.unwrap().unwrap(), vec![2]);
+            assert_eq!(stream.next().await.unwrap().unwrap(), vec![3]);
+            assert!(stream.next().await.is_none());
+            assert!(stream.next().await.is_none());
+        }
+
+        #[tokio::test]
+        async fn it_collects_the_response() {
+            let stream = EventStream::<String>::new(response("\"Hello\"\n\", world\"\n"));
+            let actual = stream.collect().await.expect("could not read stream");
+            assert_eq!(actual.concat(), "Hello, world");
+        }
+
+        #[tokio::test]
+        async fn it_decodes_server_sent_events() {
+            let response = http::Response::builder()
+                .header("content-type", "text/event-stream; charset=utf-8")
+                .body("event: greeting\ndata: \"Hello\"\n\ndata: \", world\"\n\n".to_string())
+                .expect("could not build response");
+            let stream = EventStream::<String>::new(reqwest::Response::from(response));
+            let actual = stream.collect().await.expect("could not read stream");
+            assert_eq!(actual.concat(), "Hello, world");
+        }
+
+        #[tokio::test]
+        #[allow(deprecated)]
+        async fn it_can_still_be_named_by_its_former_name() {
+            let stream = super::super::JsonLinesStream::<Vec<u32>>::new(response("[1]\n[2]"));
+            let actual = stream.collect().await.expect("could not read stream");
+            assert_eq!(actual, vec![vec![1], vec![2]]);
+        }
+    }
+
+    mod resumable_stream {
+        use super::super::{EventStream, Resumable, ResumableStream};
+        use crate::client::AiError;
+        use hypertyper::prelude::*;
+        use serde::Deserialize;
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        use std::thread;
+
+        #[derive(Clone, Debug, Deserialize)]
+        struct Delta {
+            seq: u64,
+            text: String,
+        }
+
+        impl Resumable for Delta {
+            fn sequence_number(&self) -> Option<u64> {
+                Some(self.seq)
+            }
+        }
+
+        /// Serves each body in turn, one per connection. A body that is
+        /// shorter than `length` is cut off, so the connection drops.
+        fn serve(bodies: Vec<(usize, &'static str)>) -> String {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind");
+            let uri = format!("http://{}/v1/stream", listener.local_addr().unwrap());
+            thread::spawn(move || {
+                for (stream, (length, body)) in listener.incoming().zip(bodies) {
+                    let mut stream = stream.expect("could not accept connection");
+                    let mut reader = BufReader::new(&stream);
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).expect("could not read request");
+                        if line.trim_end().is_empty() {
+                            break;
+                        }
+                    }
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n{body}"
+                    );
+                    stream.write_all(response.as_bytes()).unwrap();
+                }
+            });
+            uri
+        }
+
+        async fn connect(uri: &str) -> Result<EventStream<Delta>, AiError> {
+            let response = reqwest::get(uri).await.map_err(HttpError::from)?;
+            Ok(EventStream::new(response))
+        }
+
+        #[tokio::test]
+        async fn it_completes_a_stream_that_drops_once() {
+            let dropped = "{\"seq\": 1, \"text\": \"Hel\"}\n{\"seq\": 2, \"text\": \"lo\"}\n";
+            let resumed = "{\"seq\": 2, \"text\": \"lo\"}\n{\"seq\": 3, \"text\": \", world\"}\n";
+            let uri = serve(vec![(1000, dropped), (resumed.len(), resumed)]);
+            let stream = connect(&uri).await.expect("could not connect");
+
+            let mut reconnects = Vec::new();
+            let stream = ResumableStream::new(stream, 1, |received: &[Delta]| {
+                reconnects.push(received.len());
+                connect(&uri)
+            });
+            let actual = stream.collect().await.expect("could not read stream");
+
+            let text: String = actual.iter().map(|d| d.text.as_str()).collect();
+            assert_eq!(text, "Hello, world");
+            assert_eq!(reconnects, vec![2]);
+        }
+
+        #[tokio::test]
+        async fn it_keeps_a_new_response_that_is_numbered_from_the_start() {
+            let dropped = "{\"seq\": 1, \"text\": \"Hel\"}\n{\"seq\": 2, \"text\": \"lo\"}\n";
+            let resumed = "{\"seq\": 0, \"text\": \", \"}\n{\"seq\": 1, \"text\": \"world\"}\n";
+            let uri = serve(vec![(1000, dropped), (resumed.len(), resumed)]);
+            let stream = connect(&uri).await.expect("could not connect");
+
+            let stream = ResumableStream::new(stream, 1, |_: &[Delta]| connect(&uri))
+                .renumbers_on_reconnect();
+            let actual = stream.collect().await.expect("could not read stream");
+
+            let text: String = actual.iter().map(|d| d.text.as_str()).collect();
+            assert_eq!(text, "Hello, world");
+        }
+
+        #[tokio::test]
+        async fn it_gives_up_after_the_maximum_number_of_retries() {
+            let dropped = "{\"seq\": 1, \"text\": \"Hel\"}\n";
+            let uri = serve(vec![(1000, dropped), (1000, dropped)]);
+            let stream = connect(&uri).await.expect("could not connect");
+
+            let stream = ResumableStream::new(stream, 1, |_: &[Delta]| connect(&uri));
+            match stream.collect().await {
+                Err(AiError::Transport(_)) => {}
+                other => panic!("expected transport error, got {other:?}"),
+            }
+        }
+
+        #[tokio::test]
+        async fn it_does_not_reconnect_after_a_decode_error() {
+            let body = "{\"seq\": 1, \"text\": \"Hel\"}\nnot json\n";
+            let uri = serve(vec![(body.len(), body)]);
+            let stream = connect(&uri).await.expect("could not connect");
+
+            let mut reconnects = 0;
+            let stream = ResumableStream::new(stream, 1, |_: &[Delta]| {
+                reconnects += 1;
+                connect(&uri)
+            });
+            match stream.collect().await {
+                Err(AiError::Decode { .. }) => {}
+                other => panic!("expected decode error, got {other:?}"),
+            }
+            assert_eq!(reconnects, 0);
+        }
+    }
+
+    mod response_accumulator {
+        use super::super::{ResponseAccumulator, ResponseDelta, ToolCall};
+        use crate::client::{AiResponse, Usage};
+
+        fn text(text: &str) -> ResponseDelta {
+            ResponseDelta::Text(text.to_string())
+        }
+
+        fn tool_call(
+            index: usize,
+            id: Option<&str>,
+            name: Option<&str>,
+            arguments: &str,
+        ) -> ResponseDelta {
+            ResponseDelta::ToolCall {
+                index,
+                id: id.map(str::to_string),
+                name: name.map(str::to_string),
+                arguments: arguments.to_string(),
+            }
+        }
+
+        #[test]
+        fn it_concatenates_text() {
+            let mut accumulator = ResponseAccumulator::new();
+            accumulator.extend([text("Silent "), text("circuits "), text("hum")]);
+            assert_eq!(accumulator.text(), "Silent circuits hum");
+            let response = accumulator.finish();
+            assert_eq!(response.result(), "Silent circuits hum");
+            assert!(response.tool_calls().is_empty());
+            assert_eq!(response.usage(), None);
+        }
+
+        #[test]
+        fn it_reassembles_tool_calls() {
+            let mut accumulator = ResponseAccumulator::new();
+            accumulator.extend([
+                tool_call(0, Some("call_1"), Some("get_weather"), r#"{"city""#),
+                tool_call(1, Some("call_2"), Some("get_time"), "{}"),
+                tool_call(0, None, None, r#": "Paris"}"#),
+            ]);
+            let response = accumulator.finish();
+            let expected = vec![
+                ToolCall {
+                    id: "call_1".to_string(),
+                    name: "get_weather".to_string(),
+                    arguments: r#"{"city": "Paris"}"#.to_string(),
+                },
+                ToolCall {
+                    id: "call_2".to_string(),
+                    name: "get_time".to_string(),
+                    arguments: "{}".to_string(),
+                },
+            ];
+            assert_eq!(response.tool_calls(), expected);
+        }
+
+        #[test]
+        fn it_reports_usage_sent_at_the_end() {
+            let usage = Usage {
+                input_tokens: 12,
+                output_tokens: 3,
+                cached_input_tokens: 0,
+            };
+            let mut accumulator = ResponseAccumulator::new();
+            accumulator.extend([text("Hi!"), ResponseDelta::Usage(usage)]);
+            let response = accumulator.finish();
+            assert_eq!(response.result(), "Hi!");
+            assert_eq!(response.usage(), Some(usage));
+            assert_eq!(response.output_tokens(), Some(3));
+        }
+    }
+}
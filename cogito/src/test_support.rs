@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Stub models, requests, and responses shared by the crate's unit tests.
+
+use crate::AiModel;
+use crate::client::{AiRequest, AiResponse};
+use std::borrow::Cow;
+
+/// A family of three models: a flagship, a fast one, and a cheap one.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum Model {
+    #[default]
+    Flagship,
+    Fast,
+    Cheap,
+}
+
+impl AiModel for Model {
+    fn flagship() -> Self {
+        Model::Flagship
+    }
+
+    fn best() -> Self {
+        Model::Flagship
+    }
+
+    fn cheapest() -> Self {
+        Model::Cheap
+    }
+
+    fn fastest() -> Self {
+        Model::Fast
+    }
+}
+
+/// A request that keeps its model, instructions, and input, and ignores
+/// any extra parameters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Request {
+    pub(crate) model: Model,
+    pub(crate) instructions: Option<String>,
+    pub(crate) input: String,
+}
+
+impl AiRequest for Request {
+    type Model = Model;
+
+    fn model(self, model: Model) -> Self {
+        Self { model, ..self }
+    }
+
+    fn model_used(&self) -> Model {
+        self.model
+    }
+
+    fn instructions(self, instructions: impl Into<String>) -> Self {
+        let instructions = Some(instructions.into());
+        Self {
+            instructions,
+            ..self
+        }
+    }
+
+    fn input(self, input: impl Into<String>) -> Self {
+        let input = input.into();
+        Self { input, ..self }
+    }
+
+    fn input_text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.input)
+    }
+
+    fn extra(self, _key: &str, _value: impl Into<serde_json::Value>) -> Self {
+        self
+    }
+}
+
+/// A response whose result is the text it holds.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Response(pub(crate) String);
+
+impl AiResponse for Response {
+    fn result(&self) -> String {
+        self.0.clone()
+    }
+}
@@ -0,0 +1,418 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Helpers for testing AI provider implementations.
+//!
+//! Provider crates usually test their [`AiResponse`] implementations
+//! against JSON responses captured from a live API. The helpers in this
+//! module take care of the deserialization boilerplate, so each fixture
+//! can be checked in a single line, and [`MockService`] serves canned
+//! responses to clients under test without making any HTTP requests.
+//!
+//! This module is only available when the `testing` feature is enabled,
+//! which is usually done in a provider crate's `dev-dependencies`:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! cogito = { version = "0.2", features = ["testing"] }
+//! ```
+
+use crate::client::AiResponse;
+use crate::service::AiPost;
+use hypertyper::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+#[cfg(feature = "tracing")]
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt;
+#[cfg(feature = "tracing")]
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "tracing")]
+use tracing::field::{Field, Visit};
+#[cfg(feature = "tracing")]
+use tracing::span::{Attributes, Id, Record};
+#[cfg(feature = "tracing")]
+use tracing::{Event, Metadata, Subscriber};
+
+/// Asserts that the `json` response deserializes to an `R` whose
+/// [result](AiResponse::result) is `expected`.
+///
+/// # Panics
+///
+/// Panics if `json` cannot be deserialized to an `R`, or if the result
+/// of the response does not match `expected`.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::client::AiResponse;
+/// use cogito::testing::assert_response_result;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct EchoResponse {
+///     text: String,
+/// }
+///
+/// impl AiResponse for EchoResponse {
+///     fn result(&self) -> String {
+///         self.text.clone()
+///     }
+/// }
+///
+/// assert_response_result::<EchoResponse>(r#"{"text": "Hello!"}"#, "Hello!");
+/// ```
+#[track_caller]
+pub fn assert_response_result<R>(json: &str, expected: &str)
+where
+    R: AiResponse + DeserializeOwned,
+{
+    let response: R = match serde_json::from_str(json) {
+        Ok(response) => response,
+        Err(err) => panic!("could not deserialize response: {err}"),
+    };
+    let actual = response.result();
+    assert_eq!(
+        actual, expected,
+        "\n\nleft:\n{actual}\n\nright:\n{expected}\n"
+    );
+}
+
+/// A service that serves canned responses instead of sending requests.
+///
+/// Responses are served in the order they were given, one per request,
+/// under a mutex, so code that sends many requests concurrently, such as
+/// batch or fan-out code, can be tested deterministically. Once every
+/// response has been served, the last response is repeated for any further
+/// requests. Clones share the same responses and recorded requests.
+///
+/// Alternatively, a service created [with a router](MockService::with_router)
+/// chooses the response to each request based on its content.
+///
+/// # Panics
+///
+/// Sending a request panics if the service has no responses at all.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::service::AiPost;
+/// use cogito::testing::MockService;
+/// use hypertyper::prelude::*;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let service = MockService::with_responses(vec!["1".to_string(), "2".to_string()]);
+/// let auth = Auth::new("some-api-key");
+/// let uri = "https://example.com/v1/responses";
+/// let first: u32 = service.try_post(uri, &auth, &"Hi").await.unwrap();
+/// let second: u32 = service.try_post(uri, &auth, &"Hi").await.unwrap();
+/// let third: u32 = service.try_post(uri, &auth, &"Hi").await.unwrap();
+/// assert_eq!((first, second, third), (1, 2, 2));
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockService {
+    responses: Arc<Mutex<VecDeque<String>>>,
+    requests: Arc<Mutex<Vec<Value>>>,
+    router: Option<Router>,
+}
+
+type Router = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+impl MockService {
+    /// Creates a service that serves `response` to every request.
+    pub fn with_response(response: impl Into<String>) -> Self {
+        Self::with_responses(vec![response.into()])
+    }
+
+    /// Creates a service that serves each of `responses` in turn, and then
+    /// repeats the last one.
+    pub fn with_responses(responses: Vec<String>) -> Self {
+        Self {
+            responses: Arc::new(Mutex::new(responses.into())),
+            requests: Arc::default(),
+            router: None,
+        }
+    }
+
+    /// Creates a service that serves the response returned by `router` for
+    /// each request.
+    ///
+    /// `router` is given the body of the request, serialized as JSON, so
+    /// tests can serve different responses for different prompts without
+    /// depending on the order in which requests are sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cogito::service::AiPost;
+    /// use cogito::testing::MockService;
+    /// use hypertyper::prelude::*;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let service = MockService::with_router(|body| {
+    ///     if body.contains("haiku") {
+    ///         r#""Silent circuits hum""#.to_string()
+    ///     } else {
+    ///         r#""I only write haiku""#.to_string()
+    ///     }
+    /// });
+    /// let auth = Auth::new("some-api-key");
+    /// let uri = "https://example.com/v1/responses";
+    /// let poem: String = service.try_post(uri, &auth, &"Write a haiku").await.unwrap();
+    /// assert_eq!(poem, "Silent circuits hum");
+    /// # }
+    /// ```
+    pub fn with_router(router: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            router: Some(Arc::new(router)),
+            ..Self::default()
+        }
+    }
+
+    /// The body of every request the service has received, in the order
+    /// they were received.
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn next_response(&self) -> String {
+        let mut responses = self.responses.lock().unwrap();
+        if responses.len() > 1 {
+            responses.pop_front().unwrap()
+        } else {
+            responses
+                .front()
+                .cloned()
+                .expect("MockService has no responses to serve")
+        }
+    }
+}
+
+impl HttpPost for MockService {
+    async fn post<U, D, R>(&self, _uri: U, _auth: &Auth, data: &D) -> HttpResult<R>
+    where
+        U: IntoUrl + Send,
+        D: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        let request = serde_json::to_value(data)?;
+        let response = match &self.router {
+            Some(router) => router(&request.to_string()),
+            None => self.next_response(),
+        };
+        self.requests.lock().unwrap().push(request);
+        Ok(serde_json::from_str(&response)?)
+    }
+}
+
+impl AiPost for MockService {}
+
+impl fmt::Debug for MockService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockService")
+            .field("responses", &self.responses)
+            .field("requests", &self.requests)
+            .field(
+                "router",
+                &self.router.as_ref().map(|_| "Fn(&str) -> String"),
+            )
+            .finish()
+    }
+}
+
+/// A tracing subscriber that records the fields of every span and event.
+///
+/// `SpanRecorder` is useful for testing that a client records the expected
+/// fields in its [send span](crate::client::send_span), or that it logs an
+/// event, whose text is recorded as its `message` field. Install it for the
+/// duration of a test with [`tracing::subscriber::set_default()`]. Clones
+/// share the same recorded fields.
+///
+/// This is only available when both the `testing` and `tracing` features
+/// are enabled.
+///
+/// # Examples
+///
+/// ```
+/// use cogito::testing::SpanRecorder;
+///
+/// let recorder = SpanRecorder::default();
+/// let _guard = tracing::subscriber::set_default(recorder.clone());
+/// tracing::info_span!("send", model = "gpt-5");
+/// assert_eq!(recorder.field("model").as_deref(), Some("gpt-5"));
+/// ```
+#[cfg(feature = "tracing")]
+#[derive(Clone, Debug, Default)]
+pub struct SpanRecorder {
+    fields: Arc<Mutex<HashMap<String, String>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+#[cfg(feature = "tracing")]
+impl SpanRecorder {
+    /// The most recently recorded value of the named field, if any.
+    pub fn field(&self, name: &str) -> Option<String> {
+        self.fields.lock().unwrap().get(name).cloned()
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Visit for SpanRecorder {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        let mut fields = self.fields.lock().unwrap();
+        fields.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let mut fields = self.fields.lock().unwrap();
+        fields.insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        span.record(&mut self.clone());
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, _span: &Id, values: &Record<'_>) {
+        values.record(&mut self.clone());
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        event.record(&mut self.clone());
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[cfg(test)]
+mod tests {
+    mod mock_service {
+        use super::super::MockService;
+        use crate::service::AiPost;
+        use hypertyper::prelude::*;
+
+        const URI: &str = "https://example.com/v1/responses";
+
+        #[tokio::test]
+        async fn it_serves_queued_responses_to_concurrent_requests() {
+            let responses = vec!["\"one\"", "\"two\"", "\"three\""];
+            let service =
+                MockService::with_responses(responses.iter().map(|s| s.to_string()).collect());
+            let auth = Auth::new("some-api-key");
+            let (a, b, c) = tokio::join!(
+                service.try_post::<_, _, String>(URI, &auth, &1),
+                service.try_post::<_, _, String>(URI, &auth, &2),
+                service.try_post::<_, _, String>(URI, &auth, &3),
+            );
+            let mut actual = vec![a.unwrap(), b.unwrap(), c.unwrap()];
+            actual.sort();
+            assert_eq!(actual, vec!["one", "three", "two"]);
+            assert_eq!(service.requests().len(), 3);
+        }
+
+        #[tokio::test]
+        async fn it_repeats_the_last_response() {
+            let service = MockService::with_response("[1, 2]");
+            let auth = Auth::new("some-api-key");
+            for _ in 0..2 {
+                let actual: Vec<u32> = service.try_post(URI, &auth, &"Hi").await.unwrap();
+                assert_eq!(actual, vec![1, 2]);
+            }
+        }
+
+        #[tokio::test]
+        async fn it_records_requests() {
+            let service = MockService::with_response("null");
+            let auth = Auth::new("some-api-key");
+            let _: () = service.try_post(URI, &auth, &["Hi"]).await.unwrap();
+            assert_eq!(service.requests(), vec![serde_json::json!(["Hi"])]);
+        }
+
+        #[tokio::test]
+        async fn it_routes_requests_by_content() {
+            let service = MockService::with_router(|body| {
+                if body.contains("haiku") {
+                    "\"haiku\"".to_string()
+                } else {
+                    "\"limerick\"".to_string()
+                }
+            });
+            let auth = Auth::new("some-api-key");
+            let first: String = service
+                .try_post(URI, &auth, &"Write a haiku")
+                .await
+                .unwrap();
+            let second: String = service.try_post(URI, &auth, &"Write a poem").await.unwrap();
+            let third: String = service
+                .try_post(URI, &auth, &"Another haiku")
+                .await
+                .unwrap();
+            assert_eq!(
+                (first.as_str(), second.as_str(), third.as_str()),
+                ("haiku", "limerick", "haiku")
+            );
+            assert_eq!(service.requests().len(), 3);
+        }
+
+        #[tokio::test]
+        #[should_panic(expected = "MockService has no responses")]
+        async fn it_panics_without_responses() {
+            let service = MockService::default();
+            let auth = Auth::new("some-api-key");
+            let _ = service.try_post::<_, _, ()>(URI, &auth, &"Hi").await;
+        }
+    }
+
+    mod assert_response_result {
+        use super::super::assert_response_result;
+        use crate::client::AiResponse;
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        struct TestResponse {
+            output: Vec<String>,
+        }
+
+        impl AiResponse for TestResponse {
+            fn result(&self) -> String {
+                self.output.join("\n")
+            }
+        }
+
+        #[test]
+        fn it_passes_when_the_result_matches() {
+            let json = r#"{"output": ["Hello,", "world!"]}"#;
+            assert_response_result::<TestResponse>(json, "Hello,\nworld!");
+        }
+
+        #[test]
+        #[should_panic(expected = "left:\nHello,\nworld!")]
+        fn it_panics_when_the_result_does_not_match() {
+            let json = r#"{"output": ["Hello,", "world!"]}"#;
+            assert_response_result::<TestResponse>(json, "Goodbye, world!");
+        }
+
+        #[test]
+        #[should_panic(expected = "could not deserialize response")]
+        fn it_panics_when_the_response_cannot_be_deserialized() {
+            assert_response_result::<TestResponse>(r#"{"output": 42}"#, "");
+        }
+    }
+}
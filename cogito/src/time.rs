@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (C) 2025 Michael Dippery <michael@monkey-robot.com>
+
+//! Waiting without depending on tokio's timer.
+//!
+//! Cogito waits between [retries](crate::client::RetryPolicy) and while
+//! [rate limiting](crate::service::ServiceBuilder) requests. By default,
+//! it waits using [`sleep()`], which is timed by a background thread
+//! rather than by a runtime's timer, so it can be awaited from any
+//! executor. Only the timer is independent of the runtime: a
+//! [`Service`](crate::service::Service) sends requests using reqwest,
+//! which still needs a tokio runtime.
+//!
+//! When the `tokio` feature is enabled, `sleep()` uses tokio's timer
+//! instead, which is cheaper, but must be called from within a tokio
+//! runtime.
+
+#[cfg(not(feature = "tokio"))]
+use std::cmp::Reverse;
+#[cfg(not(feature = "tokio"))]
+use std::collections::{BinaryHeap, HashMap};
+#[cfg(not(feature = "tokio"))]
+use std::future::Future;
+#[cfg(not(feature = "tokio"))]
+use std::pin::Pin;
+#[cfg(not(feature = "tokio"))]
+use std::sync::{Condvar, LazyLock, Mutex};
+#[cfg(not(feature = "tokio"))]
+use std::task::{Context, Poll, Waker};
+#[cfg(not(feature = "tokio"))]
+use std::thread;
+use std::time::Duration;
+#[cfg(not(feature = "tokio"))]
+use std::time::Instant;
+
+/// Waits until `duration` has elapsed.
+///
+/// Without the `tokio` feature, every wait is timed by a single shared
+/// background thread, so it does not block the executor and does not need
+/// a runtime-specific timer.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// cogito::time::sleep(Duration::from_millis(10)).await;
+/// # }
+/// ```
+pub async fn sleep(duration: Duration) {
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+
+    #[cfg(not(feature = "tokio"))]
+    if !duration.is_zero() {
+        Sleep::new(duration).await;
+    }
+}
+
+/// The timer that wakes every pending [`Sleep`].
+///
+/// Its thread is started the first time the timer is used.
+#[cfg(not(feature = "tokio"))]
+static TIMER: LazyLock<Timer> = LazyLock::new(|| {
+    thread::Builder::new()
+        .name("cogito-timer".to_string())
+        .spawn(|| TIMER.run())
+        .expect("could not start the timer thread");
+    Timer::default()
+});
+
+/// Wakes tasks at their deadlines from a single thread.
+///
+/// Deadlines are kept in a heap, so the thread only has to wait for the
+/// earliest one. It is notified whenever a deadline is added, in case the
+/// new deadline is earlier than the one it is waiting for.
+#[cfg(not(feature = "tokio"))]
+#[derive(Default)]
+struct Timer {
+    state: Mutex<TimerState>,
+    changed: Condvar,
+}
+
+#[cfg(not(feature = "tokio"))]
+#[derive(Default)]
+struct TimerState {
+    deadlines: BinaryHeap<Reverse<(Instant, u64)>>,
+    wakers: HashMap<u64, Waker>,
+    next_id: u64,
+}
+
+#[cfg(not(feature = "tokio"))]
+impl Timer {
+    /// Wakes `waker` at `deadline`, and returns an ID that can be used to
+    /// update or cancel the wakeup.
+    fn register(&self, deadline: Instant, waker: Waker) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.deadlines.push(Reverse((deadline, id)));
+        state.wakers.insert(id, waker);
+        self.changed.notify_one();
+        id
+    }
+
+    /// Replaces the waker that is woken for `id`.
+    ///
+    /// If the deadline has already passed, `waker` is woken immediately,
+    /// in case the timer woke a waker that is no longer current.
+    fn update(&self, id: u64, waker: &Waker) {
+        match self.state.lock().unwrap().wakers.get_mut(&id) {
+            Some(current) => current.clone_from(waker),
+            None => waker.wake_by_ref(),
+        }
+    }
+
+    /// Cancels the wakeup for `id`.
+    ///
+    /// Its deadline stays in the heap until it passes, but nothing is
+    /// woken.
+    fn cancel(&self, id: u64) {
+        self.state.lock().unwrap().wakers.remove(&id);
+    }
+
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let mut expired = Vec::new();
+            while let Some(&Reverse((deadline, id))) = state.deadlines.peek() {
+                if deadline > now {
+                    break;
+                }
+                state.deadlines.pop();
+                expired.extend(state.wakers.remove(&id));
+            }
+
+            if !expired.is_empty() {
+                // Tasks are woken without holding the lock, since waking a
+                // task may poll it, and polling registers it with the timer.
+                drop(state);
+                expired.into_iter().for_each(Waker::wake);
+                state = self.state.lock().unwrap();
+                continue;
+            }
+
+            state = match state.deadlines.peek() {
+                Some(&Reverse((deadline, _))) => {
+                    let timeout = deadline - now;
+                    self.changed.wait_timeout(state, timeout).unwrap().0
+                }
+                None => self.changed.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+/// A future that is ready once its deadline has passed.
+///
+/// The first time it is polled, it registers with the shared [`Timer`],
+/// which wakes the task at the deadline. Later polls only update the
+/// waker, so the task is woken correctly even if it moves between threads.
+#[cfg(not(feature = "tokio"))]
+struct Sleep {
+    deadline: Instant,
+    id: Option<u64>,
+}
+
+#[cfg(not(feature = "tokio"))]
+impl Sleep {
+    fn new(duration: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + duration,
+            id: None,
+        }
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        match self.id {
+            Some(id) => TIMER.update(id, cx.waker()),
+            None => self.id = Some(TIMER.register(self.deadline, cx.waker().clone())),
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            TIMER.cancel(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod sleep {
+        use super::super::sleep;
+        use std::time::{Duration, Instant};
+
+        #[tokio::test]
+        async fn it_waits_for_the_duration() {
+            let start = Instant::now();
+            sleep(Duration::from_millis(20)).await;
+            assert!(start.elapsed() >= Duration::from_millis(20));
+        }
+
+        #[cfg(not(feature = "tokio"))]
+        #[test]
+        fn it_waits_without_a_tokio_runtime() {
+            use std::future::Future;
+            use std::pin::pin;
+            use std::sync::Arc;
+            use std::task::{Context, Poll, Wake};
+            use std::thread::{self, Thread};
+
+            /// Wakes a task by unparking the thread that is polling it.
+            struct ThreadWaker(Thread);
+
+            impl Wake for ThreadWaker {
+                fn wake(self: Arc<Self>) {
+                    self.0.unpark();
+                }
+            }
+
+            let start = Instant::now();
+            let waker = Arc::new(ThreadWaker(thread::current())).into();
+            let mut cx = Context::from_waker(&waker);
+            let mut future = pin!(sleep(Duration::from_millis(20)));
+            while future.as_mut().poll(&mut cx) == Poll::Pending {
+                thread::park();
+            }
+            assert!(start.elapsed() >= Duration::from_millis(20));
+        }
+
+        #[cfg(not(feature = "tokio"))]
+        #[tokio::test]
+        async fn it_wakes_an_earlier_deadline_before_a_later_one() {
+            let start = Instant::now();
+            let later = tokio::spawn(sleep(Duration::from_secs(2)));
+            tokio::task::yield_now().await;
+            sleep(Duration::from_millis(20)).await;
+            let elapsed = start.elapsed();
+            assert!(elapsed >= Duration::from_millis(20));
+            assert!(elapsed < Duration::from_secs(2));
+            later.abort();
+        }
+    }
+}
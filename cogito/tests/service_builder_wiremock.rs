@@ -0,0 +1,192 @@
+use cogito::client::{AiError, RetryPolicy};
+use cogito::service::{AiPost, Auth, AuthScheme, ServiceBuilder};
+use hypertyper::prelude::*;
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// These tests exercise the layers composed by a ServiceBuilder against a
+// local mock server standing in for a gateway in front of an AI service.
+
+fn builder() -> ServiceBuilder {
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    ServiceBuilder::new(factory)
+}
+
+fn auth() -> Auth {
+    Auth::new("some-api-key")
+}
+
+fn ok() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({"ok": true}))
+}
+
+#[tokio::test]
+async fn it_composes_every_layer() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/gateway/v1/responses"))
+        .and(header("x-team", "research"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/gateway/v1/responses"))
+        .and(header("x-team", "research"))
+        .respond_with(ok())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let service = builder()
+        .timeout(Duration::from_secs(5))
+        .retry(RetryPolicy::new(3).initial_delay(Duration::ZERO))
+        .rate_limit(10, Duration::from_secs(1))
+        .header("x-team", "research")
+        .base_uri(&format!("{}/gateway", server.uri()))
+        .build();
+
+    let start = Instant::now();
+    let response: Value = service
+        .try_post("https://api.example.com/v1/responses", &auth(), &json!({}))
+        .await
+        .expect("request failed");
+    assert_eq!(response, json!({"ok": true}));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn it_keeps_the_query_when_rebasing_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(query_param("api-version", "2025-10-01"))
+        .respond_with(ok())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let service = builder().base_uri(&server.uri()).build();
+    let uri = "https://api.example.com/v1/responses?api-version=2025-10-01";
+    let response: Value = service
+        .try_post(uri, &auth(), &json!({}))
+        .await
+        .expect("request failed");
+    assert_eq!(response, json!({"ok": true}));
+}
+
+#[tokio::test]
+async fn it_gives_up_after_the_last_attempt() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let service = builder().retry(RetryPolicy::new(2)).build();
+    let result = service
+        .try_post::<_, _, Value>(server.uri(), &auth(), &json!({}))
+        .await;
+    assert!(
+        matches!(result, Err(AiError::RateLimited { .. })),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn it_does_not_retry_client_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(400))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let service = builder()
+        .retry(RetryPolicy::new(3).initial_delay(Duration::ZERO))
+        .build();
+    let result = service
+        .try_post::<_, _, Value>(server.uri(), &auth(), &json!({}))
+        .await;
+    assert!(matches!(result, Err(AiError::BadRequest(_))), "{result:?}");
+}
+
+#[tokio::test]
+async fn it_times_out_slow_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ok().set_delay(Duration::from_secs(2)))
+        .mount(&server)
+        .await;
+
+    let service = builder().timeout(Duration::from_millis(100)).build();
+    let start = Instant::now();
+    let result = service
+        .try_post::<_, _, Value>(server.uri(), &auth(), &json!({}))
+        .await;
+    assert!(result.is_err());
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn it_spaces_out_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ok())
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let service = builder().rate_limit(5, Duration::from_secs(1)).build();
+    let start = Instant::now();
+    for _ in 0..3 {
+        service
+            .try_post::<_, _, Value>(server.uri(), &auth(), &json!({}))
+            .await
+            .expect("request failed");
+    }
+    assert!(start.elapsed() >= Duration::from_millis(400));
+}
+
+#[tokio::test]
+async fn it_sends_the_key_in_a_custom_header() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(header("x-gateway-key", "some-api-key"))
+        .respond_with(ok())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let service = builder()
+        .auth_scheme(AuthScheme::Header("x-gateway-key"))
+        .build();
+    let uri = format!("{}/v1/responses", server.uri());
+    let actual: Value = service.try_post(uri, &auth(), &"Hi").await.unwrap();
+    assert_eq!(actual, json!({"ok": true}));
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("authorization").is_none());
+}
+
+#[tokio::test]
+async fn it_sends_the_key_in_a_query_parameter() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(query_param("key", "some-api-key"))
+        .respond_with(ok())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let service = builder().auth_scheme(AuthScheme::Query("key")).build();
+    let uri = format!("{}/v1/responses", server.uri());
+    let actual: Value = service.try_post(uri, &auth(), &"Hi").await.unwrap();
+    assert_eq!(actual, json!({"ok": true}));
+}
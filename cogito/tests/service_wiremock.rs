@@ -0,0 +1,70 @@
+use cogito::service::{AiPost, Auth, CachingService, HttpGet, Service};
+use hypertyper::prelude::*;
+use serde::Deserialize;
+use serde_json::Value;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// These tests exercise the generic HTTP service against a local mock
+// server, so they don't need an API key.
+
+fn service() -> Service {
+    let factory = HttpClientFactory::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    Service::new(factory)
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Model {
+    id: String,
+}
+
+#[tokio::test]
+async fn it_sends_a_get_request_and_decodes_the_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models/gpt-5"))
+        .and(header("authorization", "Bearer some-api-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": "gpt-5"}"#))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let auth = Auth::new("some-api-key");
+    let uri = format!("{}/v1/models/gpt-5", server.uri());
+    let model: Model = service()
+        .get(uri, &auth)
+        .await
+        .expect("could not make GET request");
+    assert_eq!(model.id, "gpt-5");
+}
+
+#[tokio::test]
+async fn it_does_not_cache_an_error_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(500)
+                .set_body_string(r#"{"error": {"message": "The server had an error"}}"#),
+        )
+        .expect(4)
+        .mount(&server)
+        .await;
+
+    let service = CachingService::new(service());
+    let auth = Auth::new("some-api-key");
+    let uri = format!("{}/v1/responses", server.uri());
+    let data = vec!["Don't cache me!"];
+    for _ in 0..2 {
+        let error = service
+            .try_post::<_, _, Value>(uri.as_str(), &auth, &data)
+            .await
+            .expect_err("expected an error");
+        assert!(error.is_retryable());
+        let _: Value = service
+            .post(uri.as_str(), &auth, &data)
+            .await
+            .expect("could not make POST request");
+    }
+    assert!(service.is_empty());
+}